@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_light_2d::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, Light2dPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, toggle_ambient_occlusion)
+        .run();
+}
+
+const WALL_THICKNESS: f32 = 20.0;
+const BOX_SIZE: f32 = 60.0;
+
+const AMBIENT_OCCLUSION: AmbientOcclusion2d = AmbientOcclusion2d {
+    radius: 40.0,
+    strength: 0.8,
+};
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Light2d {
+            ambient_light: AmbientLight2d {
+                brightness: 0.8,
+                ..default()
+            },
+            ambient_occlusion: Some(AMBIENT_OCCLUSION),
+            ..default()
+        },
+    ));
+
+    commands.spawn(Sprite {
+        custom_size: Some(Vec2::splat(1000.0)),
+        color: Color::srgb(0.8, 0.75, 0.7),
+        ..default()
+    });
+
+    // The walls of the corner.
+    spawn_box(
+        &mut commands,
+        Vec2::new(0.0, -200.0),
+        Vec2::new(600.0, WALL_THICKNESS),
+    );
+    spawn_box(
+        &mut commands,
+        Vec2::new(-300.0, 0.0),
+        Vec2::new(WALL_THICKNESS, 400.0),
+    );
+
+    // Boxes stacked in the corner.
+    let floor = -200.0 + (WALL_THICKNESS + BOX_SIZE) / 2.0;
+    let wall = -300.0 + (WALL_THICKNESS + BOX_SIZE) / 2.0;
+    for (column, height) in [3, 2, 1].into_iter().enumerate() {
+        for row in 0..height {
+            spawn_box(
+                &mut commands,
+                Vec2::new(
+                    wall + column as f32 * BOX_SIZE,
+                    floor + row as f32 * BOX_SIZE,
+                ),
+                Vec2::splat(BOX_SIZE - 4.0),
+            );
+        }
+    }
+
+    // A lone box, away from the corner.
+    spawn_box(
+        &mut commands,
+        Vec2::new(150.0, floor),
+        Vec2::splat(BOX_SIZE - 4.0),
+    );
+}
+
+fn spawn_box(commands: &mut Commands, position: Vec2, size: Vec2) {
+    commands.spawn((
+        Sprite {
+            custom_size: Some(size),
+            color: Color::srgb(0.45, 0.3, 0.2),
+            ..default()
+        },
+        LightOccluder2d {
+            shape: LightOccluder2dShape::Rectangle {
+                half_size: size / 2.0,
+            },
+            ..default()
+        },
+        Transform::from_translation(position.extend(1.0)),
+    ));
+}
+
+// Press space to compare the scene with and without ambient occlusion.
+fn toggle_ambient_occlusion(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut light_2ds: Query<&mut Light2d>,
+) {
+    if !keyboard.just_pressed(KeyCode::Space) {
+        return;
+    }
+    for mut light_2d in &mut light_2ds {
+        light_2d.ambient_occlusion = match light_2d.ambient_occlusion {
+            Some(_) => None,
+            None => Some(AMBIENT_OCCLUSION),
+        };
+    }
+}
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use bevy_light_2d::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, Light2dPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_shade)
+        .run();
+}
+
+#[derive(Component)]
+struct Shade;
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Light2d {
+            ambient_light: AmbientLight2d {
+                brightness: 0.5,
+                ..default()
+            },
+            ..default()
+        },
+    ));
+
+    commands.spawn(Sprite {
+        custom_size: Some(Vec2::splat(400.)),
+        color: Color::WHITE,
+        ..default()
+    });
+
+    commands.spawn((
+        PointLight2d {
+            color: Color::Srgba(Srgba::rgb(1.0, 0.8, 0.5)),
+            radius: 150.,
+            intensity: 1.5,
+            ..default()
+        },
+        Transform::from_xyz(-100., 0., 0.),
+    ));
+
+    // A shadow creature, surrounded by an aura of darkness which subtracts from both the point
+    // light and the ambient light.
+    commands.spawn((
+        Shade,
+        Sprite {
+            custom_size: Some(Vec2::splat(20.)),
+            color: Color::BLACK,
+            ..default()
+        },
+        children![PointLight2d {
+            radius: 100.,
+            intensity: -2.0,
+            ..default()
+        }],
+    ));
+}
+
+fn move_shade(time: Res<Time>, mut query: Query<&mut Transform, With<Shade>>) {
+    for mut transform in &mut query {
+        let angle = time.elapsed_secs() * 0.5;
+        transform.translation = (Vec2::from_angle(angle) * 120.).extend(0.);
+    }
+}
@@ -38,6 +38,7 @@ fn setup(mut commands: Commands) {
             falloff: 10.0,
             cast_shadows: true,
             color: Color::Srgba(YELLOW),
+            ..default()
         },
         Transform::from_translation(Vec3::new(0.0, 200.0, 0.0)),
         YellowLight,
@@ -50,6 +51,7 @@ fn setup(mut commands: Commands) {
             falloff: 10.0,
             cast_shadows: true,
             color: Color::Srgba(BLUE),
+            ..default()
         },
         Transform::from_translation(Vec3::new(0.0, 200.0, 0.0)),
         BlueLight,
@@ -61,11 +63,12 @@ fn setup(mut commands: Commands) {
             radius: 500.0,
             falloff: 10.0,
             direction: 90.0,
-            inner_angle: 180.0,
-            outer_angle: 120.0,
+            inner_angle: 0.0,
+            outer_angle: 60.0,
             source_width: 10.0,
             cast_shadows: true,
             color: Color::Srgba(RED),
+            ..default()
         },
         Transform::from_translation(Vec3::new(0.0, -200.0, 0.0)),
         RedLight,
@@ -77,11 +80,12 @@ fn setup(mut commands: Commands) {
             radius: 500.0,
             falloff: 10.0,
             direction: 90.0,
-            inner_angle: 180.0,
-            outer_angle: 120.0,
+            inner_angle: 0.0,
+            outer_angle: 60.0,
             source_width: 10.0,
             cast_shadows: true,
             color: Color::Srgba(GREEN),
+            ..default()
         },
         Transform::from_translation(Vec3::new(0.0, -200.0, 0.0)),
         GreenLight,
@@ -92,6 +96,7 @@ fn setup(mut commands: Commands) {
             shape: LightOccluder2dShape::Rectangle {
                 half_size: Vec2::splat(25.0),
             },
+            ..default()
         },
         Transform::from_xyz(-400.0, 0., 0.0),
     ));
@@ -101,6 +106,7 @@ fn setup(mut commands: Commands) {
             shape: LightOccluder2dShape::Rectangle {
                 half_size: Vec2::splat(25.0),
             },
+            ..default()
         },
         Transform::from_xyz(-200.0, 0.0, 0.0),
     ));
@@ -110,6 +116,7 @@ fn setup(mut commands: Commands) {
             shape: LightOccluder2dShape::Rectangle {
                 half_size: Vec2::splat(25.0),
             },
+            ..default()
         },
         Transform::from_xyz(0.0, 0.0, 0.0),
     ));
@@ -119,6 +126,7 @@ fn setup(mut commands: Commands) {
             shape: LightOccluder2dShape::Rectangle {
                 half_size: Vec2::splat(25.0),
             },
+            ..default()
         },
         Transform::from_xyz(200.0, 0.0, 0.0),
     ));
@@ -128,6 +136,7 @@ fn setup(mut commands: Commands) {
             shape: LightOccluder2dShape::Rectangle {
                 half_size: Vec2::splat(25.0),
             },
+            ..default()
         },
         Transform::from_xyz(400.0, 0.0, 0.0),
     ));
@@ -0,0 +1,270 @@
+//! A module which contains animatable properties of lights, for use with Bevy's animation
+//! system.
+//!
+//! Each function returns a property which can be keyframed by an `AnimatableCurve`, such as to
+//! fade a lamp in over a couple of seconds when a switch is flipped:
+//!
+//! ```ignore
+//! let curve = AnimatableCurve::new(
+//!     point_light_intensity(),
+//!     AnimatableKeyframeCurve::new([(0.0, 0.0), (2.0, 5.0)]).unwrap(),
+//! );
+//! animation_clip.add_curve_to_target(target_id, curve);
+//! ```
+//!
+//! Colors are animated with [`PointLight2dColor`], [`SpotLight2dColor`] and
+//! [`AmbientLight2dColor`], which interpolate through linear RGB rather than sRGB, so blends
+//! between colors don't dip in brightness. A camera's exposure is animated with
+//! [`light_2d_exposure`], in stops, so fades between exposures look even.
+//!
+//! The light's entity needs an `AnimationTarget` whose player plays the clip. Animated fields
+//! are written before lights are extracted, so the animated values are rendered in the same
+//! frame.
+
+use core::any::TypeId;
+
+use bevy::{
+    animation::{
+        AnimationEntityMut, AnimationEvaluationError, animated_field,
+        animation_curves::{AnimatableProperty, EvaluatorId},
+    },
+    color::{Color, LinearRgba},
+};
+
+use crate::light::{Light2d, PointLight2d, SpotLight2d};
+
+/// Returns the animatable [`PointLight2d::intensity`].
+pub fn point_light_intensity() -> impl AnimatableProperty<Property = f32> + Clone {
+    animated_field!(PointLight2d::intensity)
+}
+
+/// Returns the animatable [`PointLight2d::radius`].
+pub fn point_light_radius() -> impl AnimatableProperty<Property = f32> + Clone {
+    animated_field!(PointLight2d::radius)
+}
+
+/// Returns the animatable [`PointLight2d::falloff`].
+pub fn point_light_falloff() -> impl AnimatableProperty<Property = f32> + Clone {
+    animated_field!(PointLight2d::falloff)
+}
+
+/// Returns the animatable [`SpotLight2d::intensity`].
+pub fn spot_light_intensity() -> impl AnimatableProperty<Property = f32> + Clone {
+    animated_field!(SpotLight2d::intensity)
+}
+
+/// Returns the animatable [`SpotLight2d::radius`].
+pub fn spot_light_radius() -> impl AnimatableProperty<Property = f32> + Clone {
+    animated_field!(SpotLight2d::radius)
+}
+
+/// Returns the animatable [`SpotLight2d::falloff`].
+pub fn spot_light_falloff() -> impl AnimatableProperty<Property = f32> + Clone {
+    animated_field!(SpotLight2d::falloff)
+}
+
+/// Returns the animatable [`Exposure2d::stops`] of a camera's [`Light2d`].
+///
+/// [`Exposure2d::stops`]: crate::light::Exposure2d::stops
+pub fn light_2d_exposure() -> impl AnimatableProperty<Property = f32> + Clone {
+    Light2dExposureStops
+}
+
+/// The animatable [`PointLight2d::color`], which is interpolated in linear RGB.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PointLight2dColor;
+
+/// The animatable [`SpotLight2d::color`], which is interpolated in linear RGB.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpotLight2dColor;
+
+/// The animatable color of a camera's [`AmbientLight2d`], found on its [`Light2d`], which is
+/// interpolated in linear RGB.
+///
+/// [`AmbientLight2d`]: crate::light::AmbientLight2d
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AmbientLight2dColor;
+
+macro_rules! impl_animatable_color {
+    ($property:ty, $component:ty, $($field:ident).+) => {
+        impl AnimatableProperty for $property {
+            type Property = LinearRgba;
+
+            fn get_mut<'a>(
+                &self,
+                entity: &'a mut AnimationEntityMut,
+            ) -> Result<&'a mut LinearRgba, AnimationEvaluationError> {
+                let not_present =
+                    AnimationEvaluationError::ComponentNotPresent(TypeId::of::<$component>());
+                let component = entity.get_mut::<$component>().ok_or(not_present)?;
+                Ok(linear_color_mut(&mut component.into_inner().$($field).+))
+            }
+
+            fn evaluator_id(&self) -> EvaluatorId<'_> {
+                EvaluatorId::Type(TypeId::of::<Self>())
+            }
+        }
+    };
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Light2dExposureStops;
+
+impl AnimatableProperty for Light2dExposureStops {
+    type Property = f32;
+
+    fn get_mut<'a>(
+        &self,
+        entity: &'a mut AnimationEntityMut,
+    ) -> Result<&'a mut f32, AnimationEvaluationError> {
+        let not_present = AnimationEvaluationError::ComponentNotPresent(TypeId::of::<Light2d>());
+        let light_2d = entity.get_mut::<Light2d>().ok_or(not_present)?;
+        Ok(&mut light_2d.into_inner().exposure.stops)
+    }
+
+    fn evaluator_id(&self) -> EvaluatorId<'_> {
+        EvaluatorId::Type(TypeId::of::<Self>())
+    }
+}
+
+impl_animatable_color!(PointLight2dColor, PointLight2d, color);
+impl_animatable_color!(SpotLight2dColor, SpotLight2d, color);
+impl_animatable_color!(AmbientLight2dColor, Light2d, ambient_light.color);
+
+/// Converts a color to linear RGB in place, so that it can be interpolated by the animation
+/// system, and returns it.
+fn linear_color_mut(color: &mut Color) -> &mut LinearRgba {
+    if !matches!(color, Color::LinearRgba(_)) {
+        *color = Color::LinearRgba(color.to_linear());
+    }
+    match color {
+        Color::LinearRgba(linear) => linear,
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use bevy::{
+        animation::{
+            AnimationClip, AnimationPlayer, AnimationPlugin, AnimationTarget, AnimationTargetId,
+            animation_curves::{AnimatableCurve, AnimatableKeyframeCurve},
+            graph::{AnimationGraph, AnimationGraphHandle},
+        },
+        app::{App, TaskPoolPlugin},
+        asset::{AssetPlugin, Assets},
+        ecs::{name::Name, world::World},
+        image::Image,
+        render::sync_world::RenderEntity,
+        time::{TimePlugin, TimeUpdateStrategy},
+        transform::components::GlobalTransform,
+        utils::default,
+    };
+
+    use super::*;
+    use crate::{
+        lod::DemotedLights,
+        modulation::Light2dAnimationTime,
+        render::{
+            extract::{ExtractedPointLight2d, extract_point_lights},
+            light_map::{LightCookieAtlas, LightExtrasBuffer, OccluderExclusionBuffer},
+        },
+        test_utils::{run_extract, visible},
+    };
+
+    #[test]
+    fn animated_intensity_is_extracted_mid_animation() {
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            TimePlugin,
+            AssetPlugin::default(),
+            AnimationPlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            250,
+        )));
+
+        // A lamp which fades in from 0 to 5 over two seconds.
+        let target_id = AnimationTargetId::from_name(&Name::new("lamp"));
+        let mut clip = AnimationClip::default();
+        clip.add_curve_to_target(
+            target_id,
+            AnimatableCurve::new(
+                point_light_intensity(),
+                AnimatableKeyframeCurve::new([(0.0, 0.0), (2.0, 5.0)]).unwrap(),
+            ),
+        );
+        let clip = app
+            .world_mut()
+            .resource_mut::<Assets<AnimationClip>>()
+            .add(clip);
+        let (graph, node) = AnimationGraph::from_clip(clip);
+        let graph = app
+            .world_mut()
+            .resource_mut::<Assets<AnimationGraph>>()
+            .add(graph);
+
+        let mut player = AnimationPlayer::default();
+        player.play(node);
+        let player = app
+            .world_mut()
+            .spawn((player, AnimationGraphHandle(graph)))
+            .id();
+
+        let light = app
+            .world_mut()
+            .spawn((
+                PointLight2d {
+                    intensity: 0.0,
+                    ..default()
+                },
+                GlobalTransform::default(),
+                visible(),
+                AnimationTarget {
+                    id: target_id,
+                    player,
+                },
+            ))
+            .id();
+
+        for _ in 0..5 {
+            app.update();
+        }
+
+        let seek_time = app
+            .world()
+            .get::<AnimationPlayer>(player)
+            .unwrap()
+            .animation(node)
+            .unwrap()
+            .seek_time();
+        assert!(seek_time > 0.0 && seek_time < 2.0);
+        let expected = seek_time / 2.0 * 5.0;
+        let animated = app.world().get::<PointLight2d>(light).unwrap().intensity;
+        assert!((animated - expected).abs() < 1e-4);
+
+        // The animated intensity is extracted in the same frame.
+        let mut render_world = World::new();
+        render_world.init_resource::<OccluderExclusionBuffer>();
+        render_world.init_resource::<LightExtrasBuffer>();
+        render_world.init_resource::<LightCookieAtlas>();
+        let render_entity = render_world.spawn_empty().id();
+
+        let mut main_world = core::mem::take(app.world_mut());
+        main_world
+            .entity_mut(light)
+            .insert(RenderEntity::from(render_entity));
+        main_world.init_resource::<Assets<Image>>();
+        main_world.init_resource::<Light2dAnimationTime>();
+        main_world.init_resource::<DemotedLights>();
+        run_extract(&mut render_world, main_world, extract_point_lights);
+
+        let extracted = render_world
+            .get::<ExtractedPointLight2d>(render_entity)
+            .unwrap();
+        assert!((extracted.intensity - expected).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,280 @@
+//! A module which contains [`Light2dBaker`], which bakes static lights into a lightmap.
+
+use bevy::{
+    asset::{Assets, Handle, RenderAssetUsages},
+    camera::visibility::InheritedVisibility,
+    ecs::{query::With, world::World},
+    image::Image,
+    log::warn,
+    math::{Rect, UVec2, Vec2, Vec3},
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    cpu_light::{CpuLight, CpuOccluder},
+    light::{
+        AreaLight2d, BeamLight2d, CapsuleLight2d, LineLight2d, PointLight2d, PolygonLight2d,
+        PolylineLight2d, RingLight2d, SpotLight2d, StaticLight2d,
+    },
+    occluder::{LightOccluder2d, OcclusionLayers, StaticLightOccluder2d},
+};
+
+/// Bakes [`StaticLight2d`]s into a lightmap, for use with a [`BakedLightmap2d`].
+///
+/// Baking evaluates every visible static light over a region of the world, shadowed by every
+/// visible [`StaticLightOccluder2d`], on the CPU. Other occluders move, so they don't shadow
+/// baked lights. Baked shadows are hard, and follow the outlines of occluders: the softness,
+/// height, shadow distance, absorption and one-sidedness of occluders are ignored, and curved
+/// shapes are approximated. Light cookies, noise textures and projection textures aren't
+/// baked.
+///
+/// Baking is slow, so it's meant to be done once when a level is loaded, such as in an
+/// exclusive system. Use [`Light2dBaker::rebake`] when the level changes, which updates an
+/// existing lightmap in place.
+///
+/// [`BakedLightmap2d`]: crate::light::BakedLightmap2d
+pub struct Light2dBaker;
+
+impl Light2dBaker {
+    /// Bakes the static lights within the world space `region` into a new lightmap image of
+    /// `resolution` texels.
+    pub fn bake(world: &mut World, region: Rect, resolution: UVec2) -> Handle<Image> {
+        let image = bake_image(world, region, resolution);
+        world.resource_mut::<Assets<Image>>().add(image)
+    }
+
+    /// Bakes the static lights within the world space `region` into an existing lightmap
+    /// image, replacing its contents.
+    ///
+    /// Cameras using the lightmap draw the new lights once the image has been uploaded.
+    pub fn rebake(world: &mut World, image: &Handle<Image>, region: Rect, resolution: UVec2) {
+        let baked_image = bake_image(world, region, resolution);
+        let mut images = world.resource_mut::<Assets<Image>>();
+
+        let Some(image) = images.get_mut(image) else {
+            warn!("Can't rebake a lightmap whose image doesn't exist.");
+            return;
+        };
+        *image = baked_image;
+    }
+}
+
+fn bake_image(world: &mut World, region: Rect, resolution: UVec2) -> Image {
+    let lights = collect_lights(world);
+    let occluders = collect_occluders(world);
+
+    let resolution = resolution.max(UVec2::ONE);
+    let texel_size = region.size() / resolution.as_vec2();
+    let mut data = Vec::with_capacity((resolution.x * resolution.y) as usize * 16);
+
+    // Image rows go downwards, while world space y goes upwards.
+    for y in 0..resolution.y {
+        for x in 0..resolution.x {
+            let pos = Vec2::new(
+                region.min.x + (x as f32 + 0.5) * texel_size.x,
+                region.max.y - (y as f32 + 0.5) * texel_size.y,
+            );
+            let color = lights
+                .iter()
+                .map(|light| light.illuminate(pos, &occluders))
+                .sum::<Vec3>();
+
+            for component in color.extend(1.0).to_array() {
+                data.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+    }
+
+    // Baked light can be negative or brighter than white, so it's stored as floats.
+    Image::new(
+        Extent3d {
+            width: resolution.x,
+            height: resolution.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba32Float,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn collect_lights(world: &mut World) -> Vec<CpuLight> {
+    let mut lights = Vec::new();
+
+    let mut point_lights = world.query_filtered::<(
+        &PointLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in point_lights.iter(world) {
+        if visibility.get() && light.enabled {
+            lights.push(CpuLight::point(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    let mut spot_lights = world.query_filtered::<(
+        &SpotLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in spot_lights.iter(world) {
+        if visibility.get() && light.enabled {
+            lights.push(CpuLight::spot(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    let mut line_lights = world.query_filtered::<(
+        &LineLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in line_lights.iter(world) {
+        if visibility.get() {
+            lights.push(CpuLight::line(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    let mut capsule_lights = world.query_filtered::<(
+        &CapsuleLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in capsule_lights.iter(world) {
+        if visibility.get() {
+            lights.push(CpuLight::capsule(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    let mut area_lights = world.query_filtered::<(
+        &AreaLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in area_lights.iter(world) {
+        if visibility.get() {
+            lights.push(CpuLight::area(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    // Polygon lights need at least three vertices, and polyline lights at least one point, to
+    // be drawn.
+    let mut polygon_lights = world.query_filtered::<(
+        &PolygonLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in polygon_lights.iter(world) {
+        if visibility.get() && light.vertices.len() >= 3 {
+            lights.push(CpuLight::polygon(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    let mut polyline_lights = world.query_filtered::<(
+        &PolylineLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in polyline_lights.iter(world) {
+        if visibility.get() && !light.points.is_empty() {
+            lights.push(CpuLight::polyline(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    let mut ring_lights = world.query_filtered::<(
+        &RingLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in ring_lights.iter(world) {
+        if visibility.get() {
+            lights.push(CpuLight::ring(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    let mut beam_lights = world.query_filtered::<(
+        &BeamLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLight2d>>();
+    for (light, global_transform, visibility, occlusion_layers) in beam_lights.iter(world) {
+        if visibility.get() {
+            lights.extend(CpuLight::beam(
+                light,
+                global_transform,
+                occlusion_layers,
+                None,
+            ));
+        }
+    }
+
+    lights
+}
+
+fn collect_occluders(world: &mut World) -> Vec<CpuOccluder> {
+    let mut occluders_query = world.query_filtered::<(
+        &LightOccluder2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    ), With<StaticLightOccluder2d>>();
+    let images = world.resource::<Assets<Image>>();
+
+    occluders_query
+        .iter(world)
+        .filter(|(_, _, visibility, _)| visibility.get())
+        .filter_map(|(occluder, global_transform, _, occlusion_layers)| {
+            CpuOccluder::new(occluder, global_transform, occlusion_layers, images)
+        })
+        .collect()
+}
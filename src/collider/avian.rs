@@ -0,0 +1,170 @@
+use avian2d::{
+    parry::{
+        math::{Isometry, Point},
+        shape::{Shape, TypedShape},
+    },
+    prelude::Collider,
+};
+use bevy::{
+    ecs::{
+        entity::Entity,
+        lifecycle::RemovedComponents,
+        query::{Changed, With},
+        system::{Commands, Query},
+    },
+    log::warn_once,
+    math::Vec2,
+};
+
+use crate::occluder::{LightOccluder2d, LightOccluder2dShape};
+
+use super::{OccluderFromCollider, apply_shapes};
+
+/// Mirrors the shapes of Avian [`Collider`]s into the [`LightOccluder2d`]s of entities with
+/// [`OccluderFromCollider`], removing the occluder when the collider is removed.
+pub(crate) fn sync_avian_collider_occluders(
+    mut commands: Commands,
+    mut removed_colliders: RemovedComponents<Collider>,
+    mut colliders: Query<
+        (Entity, &Collider, Option<&mut LightOccluder2d>),
+        (With<OccluderFromCollider>, Changed<Collider>),
+    >,
+    markers: Query<(), (With<OccluderFromCollider>, With<LightOccluder2d>)>,
+) {
+    for entity in removed_colliders.read() {
+        if markers.contains(entity) {
+            commands.entity(entity).remove::<LightOccluder2d>();
+        }
+    }
+
+    for (entity, collider, occluder) in &mut colliders {
+        let mut shapes = Vec::new();
+        collect_shapes(
+            collider.shape().as_ref(),
+            &Isometry::identity(),
+            &mut shapes,
+        );
+
+        apply_shapes(&mut commands, entity, occluder, shapes);
+    }
+}
+
+/// Converts a collider shape, positioned within the collider by `isometry`, into occluder
+/// shapes and their offsets.
+fn collect_shapes(
+    shape: &dyn Shape,
+    isometry: &Isometry<f32>,
+    shapes: &mut Vec<(LightOccluder2dShape, Vec2)>,
+) {
+    let to_vec2 = |point: Point<f32>| Vec2::new(point.x, point.y);
+    let offset = to_vec2(isometry * Point::origin());
+    let is_rotated = isometry.rotation.angle().abs() > f32::EPSILON;
+
+    match shape.as_typed_shape() {
+        TypedShape::Ball(ball) => shapes.push((
+            LightOccluder2dShape::Ellipse {
+                half_size: Vec2::splat(ball.radius),
+            },
+            offset,
+        )),
+        TypedShape::Cuboid(cuboid) if !is_rotated => shapes.push((
+            LightOccluder2dShape::Rectangle {
+                half_size: Vec2::new(cuboid.half_extents.x, cuboid.half_extents.y),
+            },
+            offset,
+        )),
+        TypedShape::RoundCuboid(round_cuboid) if !is_rotated => {
+            let border_radius = round_cuboid.border_radius;
+            let half_extents = round_cuboid.inner_shape.half_extents;
+
+            shapes.push((
+                LightOccluder2dShape::RoundedRectangle {
+                    half_size: Vec2::new(half_extents.x, half_extents.y) + border_radius,
+                    corner_radius: border_radius,
+                },
+                offset,
+            ));
+        }
+        // Rotated cuboids are polygons in the collider's space.
+        TypedShape::Cuboid(cuboid) => shapes.push((
+            LightOccluder2dShape::ConvexPolygon(
+                cuboid
+                    .to_polyline()
+                    .into_iter()
+                    .map(|point| to_vec2(isometry * point))
+                    .collect(),
+            ),
+            Vec2::ZERO,
+        )),
+        TypedShape::Capsule(capsule) => {
+            let start = to_vec2(isometry * capsule.segment.a);
+            let end = to_vec2(isometry * capsule.segment.b);
+
+            // Occluder capsules are aligned with the y axis.
+            if (start.x - end.x).abs() <= f32::EPSILON {
+                shapes.push((
+                    LightOccluder2dShape::Capsule {
+                        half_length: (end.y - start.y).abs() * 0.5,
+                        radius: capsule.radius,
+                    },
+                    (start + end) * 0.5,
+                ));
+            } else {
+                push_bounds(shape, isometry, shapes);
+            }
+        }
+        TypedShape::ConvexPolygon(polygon) => shapes.push((
+            LightOccluder2dShape::ConvexPolygon(
+                polygon
+                    .points()
+                    .iter()
+                    .map(|point| to_vec2(isometry * point))
+                    .collect(),
+            ),
+            Vec2::ZERO,
+        )),
+        TypedShape::Triangle(triangle) => shapes.push((
+            LightOccluder2dShape::Triangle {
+                a: to_vec2(isometry * triangle.a),
+                b: to_vec2(isometry * triangle.b),
+                c: to_vec2(isometry * triangle.c),
+            },
+            Vec2::ZERO,
+        )),
+        TypedShape::Segment(segment) => shapes.push((
+            LightOccluder2dShape::Segment {
+                start: to_vec2(isometry * segment.a),
+                end: to_vec2(isometry * segment.b),
+            },
+            Vec2::ZERO,
+        )),
+        TypedShape::Compound(compound) => {
+            for (part_isometry, part) in compound.shapes() {
+                collect_shapes(part.as_ref(), &(isometry * part_isometry), shapes);
+            }
+        }
+        _ => push_bounds(shape, isometry, shapes),
+    }
+}
+
+/// Falls back to the bounding box of shapes that can't be represented by an occluder.
+fn push_bounds(
+    shape: &dyn Shape,
+    isometry: &Isometry<f32>,
+    shapes: &mut Vec<(LightOccluder2dShape, Vec2)>,
+) {
+    warn_once!(
+        "A collider shape can't be represented by an occluder, so the collider's bounding box \
+        will be used instead."
+    );
+
+    let aabb = shape.compute_aabb(isometry);
+    let center = aabb.center();
+
+    shapes.push((
+        LightOccluder2dShape::Rectangle {
+            half_size: Vec2::new(aabb.half_extents().x, aabb.half_extents().y),
+        },
+        Vec2::new(center.x, center.y),
+    ));
+}
@@ -0,0 +1,64 @@
+//! A module which contains integrations that create occluders from physics colliders.
+
+#[cfg(feature = "avian2d")]
+mod avian;
+#[cfg(feature = "rapier2d")]
+mod rapier;
+
+use bevy::{
+    ecs::{component::Component, entity::Entity, system::Commands, world::Mut},
+    math::Vec2,
+};
+
+#[cfg(feature = "avian2d")]
+pub(crate) use avian::sync_avian_collider_occluders;
+#[cfg(feature = "rapier2d")]
+pub(crate) use rapier::sync_rapier_collider_occluders;
+
+use crate::occluder::{LightOccluder2d, LightOccluder2dCompoundShape, LightOccluder2dShape};
+
+/// A marker component which keeps an entity's [`LightOccluder2d`] in sync with its physics
+/// collider.
+///
+/// A [`LightOccluder2d`] matching the collider's shape is inserted, and updated whenever the
+/// collider changes. The occluder is removed when the collider is removed. Other occluder
+/// properties, such as its opacity, are left untouched.
+///
+/// Collider shapes that can't be represented by an occluder use the collider's bounding box
+/// instead.
+#[derive(Component, Default)]
+pub struct OccluderFromCollider;
+
+/// Replaces an occluder's shapes with the given shapes and their offsets, inserting the
+/// occluder if it doesn't exist.
+fn apply_shapes(
+    commands: &mut Commands,
+    entity: Entity,
+    occluder: Option<Mut<LightOccluder2d>>,
+    shapes: Vec<(LightOccluder2dShape, Vec2)>,
+) {
+    let mut shapes = shapes.into_iter().peekable();
+
+    // The main shape can't be offset, so offset shapes are all added as compound shapes.
+    let shape = shapes
+        .next_if(|(_, offset)| *offset == Vec2::ZERO)
+        .map(|(shape, _)| shape)
+        .unwrap_or_default();
+    let compound_shapes = shapes
+        .map(|(shape, offset)| LightOccluder2dCompoundShape { shape, offset })
+        .collect();
+
+    match occluder {
+        Some(mut occluder) => {
+            occluder.shape = shape;
+            occluder.compound_shapes = compound_shapes;
+        }
+        None => {
+            commands.entity(entity).insert(LightOccluder2d {
+                shape,
+                compound_shapes,
+                ..LightOccluder2d::default()
+            });
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::{
+    ecs::{
+        entity::Entity,
+        lifecycle::RemovedComponents,
+        query::{Changed, With},
+        system::{Commands, Query},
+    },
+    log::warn_once,
+    math::{Rot2, Vec2},
+};
+use bevy_rapier2d::{geometry::ColliderView, prelude::Collider};
+
+use crate::occluder::{LightOccluder2d, LightOccluder2dShape};
+
+use super::{OccluderFromCollider, apply_shapes};
+
+/// Mirrors the shapes of Rapier [`Collider`]s into the [`LightOccluder2d`]s of entities with
+/// [`OccluderFromCollider`], removing the occluder when the collider is removed.
+pub(crate) fn sync_rapier_collider_occluders(
+    mut commands: Commands,
+    mut removed_colliders: RemovedComponents<Collider>,
+    mut colliders: Query<
+        (Entity, &Collider, Option<&mut LightOccluder2d>),
+        (With<OccluderFromCollider>, Changed<Collider>),
+    >,
+    markers: Query<(), (With<OccluderFromCollider>, With<LightOccluder2d>)>,
+) {
+    for entity in removed_colliders.read() {
+        if markers.contains(entity) {
+            commands.entity(entity).remove::<LightOccluder2d>();
+        }
+    }
+
+    for (entity, collider, occluder) in &mut colliders {
+        let mut shapes = Vec::new();
+
+        // The occluder is scaled by the entity's transform, so the unscaled shape is used.
+        if collect_shapes(
+            collider.as_unscaled_typed_shape(),
+            Vec2::ZERO,
+            Rot2::IDENTITY,
+            &mut shapes,
+        )
+        .is_none()
+        {
+            warn_once!(
+                "A collider shape can't be represented by an occluder, so the collider's \
+                bounding box will be used instead."
+            );
+
+            shapes.clear();
+            shapes.push(collider_bounds(collider));
+        }
+
+        apply_shapes(&mut commands, entity, occluder, shapes);
+    }
+}
+
+/// Converts a collider shape, positioned within the collider by a translation and rotation,
+/// into occluder shapes and their offsets.
+///
+/// Returns `None` if the shape can't be represented by an occluder.
+fn collect_shapes(
+    shape: ColliderView,
+    translation: Vec2,
+    rotation: Rot2,
+    shapes: &mut Vec<(LightOccluder2dShape, Vec2)>,
+) -> Option<()> {
+    let transform = |point: Vec2| translation + rotation * point;
+    let is_rotated = rotation.as_radians().abs() > f32::EPSILON;
+
+    match shape {
+        ColliderView::Ball(ball) => shapes.push((
+            LightOccluder2dShape::Ellipse {
+                half_size: Vec2::splat(ball.radius()),
+            },
+            translation,
+        )),
+        ColliderView::Cuboid(cuboid) if !is_rotated => shapes.push((
+            LightOccluder2dShape::Rectangle {
+                half_size: cuboid.half_extents(),
+            },
+            translation,
+        )),
+        ColliderView::RoundCuboid(round_cuboid) if !is_rotated => {
+            let border_radius = round_cuboid.border_radius();
+
+            shapes.push((
+                LightOccluder2dShape::RoundedRectangle {
+                    half_size: round_cuboid.inner_shape().half_extents() + border_radius,
+                    corner_radius: border_radius,
+                },
+                translation,
+            ));
+        }
+        // Rotated cuboids are polygons in the collider's space.
+        ColliderView::Cuboid(cuboid) => {
+            let half_extents = cuboid.half_extents();
+
+            shapes.push((
+                LightOccluder2dShape::ConvexPolygon(
+                    [
+                        Vec2::new(-half_extents.x, -half_extents.y),
+                        Vec2::new(half_extents.x, -half_extents.y),
+                        Vec2::new(half_extents.x, half_extents.y),
+                        Vec2::new(-half_extents.x, half_extents.y),
+                    ]
+                    .map(transform)
+                    .to_vec(),
+                ),
+                Vec2::ZERO,
+            ));
+        }
+        ColliderView::Capsule(capsule) => {
+            let segment = capsule.segment();
+            let start = transform(segment.a());
+            let end = transform(segment.b());
+
+            // Occluder capsules are aligned with the y axis.
+            if (start.x - end.x).abs() > f32::EPSILON {
+                return None;
+            }
+
+            shapes.push((
+                LightOccluder2dShape::Capsule {
+                    half_length: (end.y - start.y).abs() * 0.5,
+                    radius: capsule.radius(),
+                },
+                (start + end) * 0.5,
+            ));
+        }
+        ColliderView::ConvexPolygon(polygon) => shapes.push((
+            LightOccluder2dShape::ConvexPolygon(polygon.points().map(transform).collect()),
+            Vec2::ZERO,
+        )),
+        ColliderView::Triangle(triangle) => shapes.push((
+            LightOccluder2dShape::Triangle {
+                a: transform(triangle.a()),
+                b: transform(triangle.b()),
+                c: transform(triangle.c()),
+            },
+            Vec2::ZERO,
+        )),
+        ColliderView::Segment(segment) => shapes.push((
+            LightOccluder2dShape::Segment {
+                start: transform(segment.a()),
+                end: transform(segment.b()),
+            },
+            Vec2::ZERO,
+        )),
+        ColliderView::Compound(compound) => {
+            for (part_translation, part_rotation, part) in compound.shapes() {
+                collect_shapes(
+                    part,
+                    transform(part_translation),
+                    rotation * Rot2::radians(part_rotation),
+                    shapes,
+                )?;
+            }
+        }
+        _ => return None,
+    }
+
+    Some(())
+}
+
+/// Returns the unscaled bounding box of a collider, as a rectangle and its offset.
+fn collider_bounds(collider: &Collider) -> (LightOccluder2dShape, Vec2) {
+    let aabb = collider.raw.compute_local_aabb();
+    let scale = collider.scale().abs().max(Vec2::splat(f32::EPSILON));
+    let min = Vec2::new(aabb.mins.x, aabb.mins.y) / scale;
+    let max = Vec2::new(aabb.maxs.x, aabb.maxs.y) / scale;
+
+    (
+        LightOccluder2dShape::Rectangle {
+            half_size: (max - min) * 0.5,
+        },
+        (min + max) * 0.5,
+    )
+}
@@ -0,0 +1,949 @@
+//! Evaluates lights on the CPU, matching the light map pass, for baking lightmaps and sampling
+//! light probes.
+
+use core::{
+    f32::consts::{PI, TAU},
+    iter,
+};
+
+use bevy::{
+    asset::Assets,
+    color::ColorToComponents,
+    image::Image,
+    math::{Rect, Vec2, Vec3, Vec3Swizzles},
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    light::{
+        AreaLight2d, BeamLight2d, CapsuleLight2d, FalloffCurve, LightBlend2d, LineLight2d,
+        PointLight2d, PolygonLight2d, PolylineLight2d, RingLight2d, SpotLight2d,
+    },
+    modulation::LightModulation2d,
+    occluder::{LightOccluder2d, LightOccluder2dShape, OcclusionLayers},
+};
+
+// Rays that get this close to a light are considered to have reached it, matching the
+// light map pass.
+const RAY_TARGET_EPSILON: f32 = 1.0;
+
+// The number of segments that curved occluder outlines are approximated with.
+const CURVE_SEGMENTS: usize = 24;
+
+/// A light, in world space.
+pub(crate) struct CpuLight {
+    shape: CpuLightShape,
+    color: Vec3,
+    edge_color: Option<Vec3>,
+    intensity: f32,
+    radius: f32,
+    inner_radius: f32,
+    falloff: f32,
+    falloff_curve: FalloffCurve,
+    attenuation_exponent: f32,
+    cast_shadows: bool,
+    shadow_strength: f32,
+    max_brightness: Option<f32>,
+    occlusion_layers: u32,
+}
+
+enum CpuLightShape {
+    Point {
+        center: Vec2,
+        ellipse_axis: Vec2,
+        ellipse_aspect: f32,
+    },
+    Spot {
+        center: Vec2,
+        direction: Vec2,
+        cos_inner: f32,
+        cos_outer: f32,
+        edge_exponent: f32,
+        source_width: f32,
+    },
+    Line {
+        start: Vec2,
+        end: Vec2,
+    },
+    Area {
+        center: Vec2,
+        half_size: Vec2,
+        rotation: Vec2,
+    },
+    Ring {
+        center: Vec2,
+        radius: f32,
+        // The direction of the middle of the lit arc, and half of the arc's angle.
+        arc_direction: Vec2,
+        arc_half_angle: f32,
+    },
+    Polygon {
+        vertices: Vec<Vec2>,
+        // Whether the vertices form a closed polygon, rather than an open polyline.
+        closed: bool,
+    },
+}
+
+/// The outline of an occluder's shapes, in world space.
+pub(crate) struct CpuOccluder {
+    segments: Vec<(Vec2, Vec2)>,
+    bounds: Rect,
+    transmittance: Vec3,
+    occlusion_layers: u32,
+}
+
+impl CpuLight {
+    pub(crate) fn point(
+        light: &PointLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let radius_scale = light.radius_scale(global_transform);
+        let radius = light.radius * radius_scale;
+        let modulation = modulation.copied().unwrap_or_default();
+        let ellipticity = light.ellipticity.abs().max(Vec2::splat(f32::EPSILON));
+        Self {
+            shape: CpuLightShape::Point {
+                center: global_transform.translation().xy() + modulation.offset,
+                ellipse_axis: rotation_2d(global_transform) / ellipticity.x,
+                ellipse_aspect: ellipticity.x / ellipticity.y,
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: light
+                .edge_color
+                .map(|color| (color.to_linear() * modulation.color).to_vec3()),
+            intensity: light.intensity_unit.to_intensity(light.intensity, radius)
+                * modulation.intensity,
+            radius,
+            inner_radius: (light.inner_radius * radius_scale).clamp(0.0, radius.max(0.0)),
+            falloff: light.falloff,
+            falloff_curve: light.falloff_curve,
+            attenuation_exponent: light.attenuation_exponent,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: light.shadow_strength.clamp(0.0, 1.0),
+            max_brightness: light.max_brightness,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    pub(crate) fn spot(
+        light: &SpotLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let radius_scale = light.radius_scale(global_transform);
+        let radius = light.radius * radius_scale;
+        let modulation = modulation.copied().unwrap_or_default();
+        let (_, inner_angle, outer_angle) = light.normalized_angles();
+        Self {
+            shape: CpuLightShape::Spot {
+                center: global_transform.translation().xy() + modulation.offset,
+                direction: light.world_direction(global_transform),
+                cos_inner: inner_angle.to_radians().cos(),
+                cos_outer: outer_angle.to_radians().cos(),
+                edge_exponent: light.normalized_edge_exponent(),
+                source_width: light.source_width * radius_scale,
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: light
+                .edge_color
+                .map(|color| (color.to_linear() * modulation.color).to_vec3()),
+            intensity: light.intensity_unit.to_intensity(light.intensity, radius)
+                * modulation.intensity,
+            radius,
+            inner_radius: (light.inner_radius * radius_scale).clamp(0.0, radius.max(0.0)),
+            falloff: light.falloff,
+            falloff_curve: light.falloff_curve,
+            attenuation_exponent: light.attenuation_exponent,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: light.shadow_strength.clamp(0.0, 1.0),
+            max_brightness: light.max_brightness,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    pub(crate) fn line(
+        light: &LineLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let modulation = modulation.copied().unwrap_or_default();
+        let half_length = light.length.max(0.0) * 0.5;
+        let to_world = |x: f32| {
+            global_transform
+                .transform_point(Vec3::new(x, 0.0, 0.0))
+                .xy()
+                + modulation.offset
+        };
+        Self {
+            shape: CpuLightShape::Line {
+                start: to_world(-half_length),
+                end: to_world(half_length),
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: None,
+            intensity: light.intensity * modulation.intensity,
+            radius: light.radius,
+            inner_radius: 0.0,
+            falloff: light.falloff,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: 1.0,
+            max_brightness: None,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    pub(crate) fn capsule(
+        light: &CapsuleLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let modulation = modulation.copied().unwrap_or_default();
+        let half_length = light.half_length.max(0.0);
+        let to_world = |x: f32| {
+            global_transform
+                .transform_point(Vec3::new(x, 0.0, 0.0))
+                .xy()
+                + modulation.offset
+        };
+        Self {
+            shape: CpuLightShape::Line {
+                start: to_world(-half_length),
+                end: to_world(half_length),
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: None,
+            intensity: light.intensity * modulation.intensity,
+            radius: light.radius,
+            inner_radius: 0.0,
+            falloff: light.falloff,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: 1.0,
+            max_brightness: None,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    pub(crate) fn area(
+        light: &AreaLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let modulation = modulation.copied().unwrap_or_default();
+        Self {
+            shape: CpuLightShape::Area {
+                center: global_transform.translation().xy() + modulation.offset,
+                half_size: light.half_size.max(Vec2::ZERO) * global_transform.scale().xy().abs(),
+                rotation: rotation_2d(global_transform),
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: None,
+            intensity: light.intensity * modulation.intensity,
+            radius: light.radius,
+            inner_radius: 0.0,
+            falloff: light.falloff,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: 1.0,
+            max_brightness: None,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    pub(crate) fn polygon(
+        light: &PolygonLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let modulation = modulation.copied().unwrap_or_default();
+        Self {
+            shape: CpuLightShape::Polygon {
+                vertices: light
+                    .vertices
+                    .iter()
+                    .map(|vertex| {
+                        global_transform.transform_point(vertex.extend(0.0)).xy()
+                            + modulation.offset
+                    })
+                    .collect(),
+                closed: true,
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: None,
+            intensity: light.intensity * modulation.intensity,
+            radius: light.radius,
+            inner_radius: 0.0,
+            falloff: light.falloff,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: 1.0,
+            max_brightness: None,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    pub(crate) fn polyline(
+        light: &PolylineLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let modulation = modulation.copied().unwrap_or_default();
+        Self {
+            shape: CpuLightShape::Polygon {
+                vertices: light
+                    .points
+                    .iter()
+                    .map(|point| {
+                        global_transform.transform_point(point.extend(0.0)).xy() + modulation.offset
+                    })
+                    .collect(),
+                closed: false,
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: None,
+            intensity: light.intensity * modulation.intensity,
+            radius: light.radius,
+            inner_radius: 0.0,
+            falloff: light.falloff,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: 1.0,
+            max_brightness: None,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    /// Ring lights are attenuated across half of their thickness on either side of their arc.
+    pub(crate) fn ring(
+        light: &RingLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> Self {
+        let modulation = modulation.copied().unwrap_or_default();
+        let arc_start = light.arc_start.to_radians();
+        let arc_end = light.arc_end.to_radians();
+        Self {
+            shape: CpuLightShape::Ring {
+                center: global_transform.translation().xy() + modulation.offset,
+                radius: light.radius.max(0.0),
+                arc_direction: Vec2::from_angle((arc_start + arc_end) * 0.5)
+                    .rotate(rotation_2d(global_transform)),
+                arc_half_angle: ((arc_end - arc_start) * 0.5).clamp(0.0, PI),
+            },
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: None,
+            intensity: light.intensity * modulation.intensity,
+            radius: light.thickness.max(0.0) * 0.5,
+            inner_radius: 0.0,
+            falloff: light.falloff,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: 1.0,
+            max_brightness: None,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        }
+    }
+
+    /// Beam lights are a line light, along with a point light for the glow around their end.
+    pub(crate) fn beam(
+        light: &BeamLight2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        modulation: Option<&LightModulation2d>,
+    ) -> [Self; 2] {
+        let modulation = modulation.copied().unwrap_or_default();
+        let (start, end) = light.endpoints(global_transform);
+        let beam_light = |shape, radius| Self {
+            shape,
+            color: (light.color.to_linear() * modulation.color).to_vec3(),
+            edge_color: None,
+            intensity: light.intensity * modulation.intensity,
+            radius,
+            inner_radius: 0.0,
+            falloff: light.lateral_falloff,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            cast_shadows: light.cast_shadows,
+            shadow_strength: 1.0,
+            max_brightness: None,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        };
+        [
+            beam_light(
+                CpuLightShape::Line {
+                    start: start + modulation.offset,
+                    end: end + modulation.offset,
+                },
+                light.thickness.max(0.0) * 0.5,
+            ),
+            beam_light(
+                CpuLightShape::Point {
+                    center: end + modulation.offset,
+                    ellipse_axis: Vec2::X,
+                    ellipse_aspect: 1.0,
+                },
+                light.impact_glow_radius.max(0.0),
+            ),
+        ]
+    }
+}
+
+impl CpuOccluder {
+    /// Returns the outline of an occluder, or `None` if it doesn't block any light.
+    ///
+    /// Outlines don't account for the softness, height, shadow distance, absorption or
+    /// one-sidedness of occluders, and curved shapes are approximated.
+    pub(crate) fn new(
+        occluder: &LightOccluder2d,
+        global_transform: &GlobalTransform,
+        occlusion_layers: Option<&OcclusionLayers>,
+        images: &Assets<Image>,
+    ) -> Option<Self> {
+        if !occluder.enabled || occluder.opacity <= 0.0 {
+            return None;
+        }
+
+        let shapes = iter::once((&occluder.shape, occluder.offset)).chain(
+            occluder.compound_shapes.iter().map(|compound_shape| {
+                (
+                    &compound_shape.shape,
+                    occluder.offset + compound_shape.offset,
+                )
+            }),
+        );
+
+        let mut segments = Vec::new();
+        for (shape, offset) in shapes {
+            push_outline(shape, offset, global_transform, images, &mut segments);
+        }
+
+        let bounds = segments
+            .iter()
+            .map(|(start, end)| Rect::from_corners(*start, *end))
+            .reduce(|a, b| a.union(b))?;
+
+        // Light that passes through an occluder is filtered by its tint, and light that's
+        // blocked is modulated by its shadow color, as in the light map pass.
+        let opacity = occluder.opacity.clamp(0.0, 1.0);
+        let tint = occluder
+            .tint
+            .map_or(Vec3::ZERO, |tint| tint.to_linear().to_vec3());
+        let shadow_color = occluder.shadow_color.map_or(Vec3::ZERO, |shadow_color| {
+            shadow_color.to_linear().to_vec3()
+        });
+        let transmittance = 1.0 - opacity * (1.0 - tint);
+
+        Some(Self {
+            segments,
+            bounds,
+            transmittance: transmittance + (1.0 - transmittance) * shadow_color,
+            occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+        })
+    }
+}
+
+/// Pushes the world space outline of an occluder shape, offset from the occluder's origin in
+/// local space.
+fn push_outline(
+    shape: &LightOccluder2dShape,
+    offset: Vec2,
+    global_transform: &GlobalTransform,
+    images: &Assets<Image>,
+    segments: &mut Vec<(Vec2, Vec2)>,
+) {
+    let to_world = |point: Vec2| {
+        global_transform
+            .transform_point((point + offset).extend(0.0))
+            .xy()
+    };
+    let mut push_loop = |points: Vec<Vec2>| {
+        let points: Vec<Vec2> = points.into_iter().map(to_world).collect();
+        for (index, start) in points.iter().enumerate() {
+            segments.push((*start, points[(index + 1) % points.len()]));
+        }
+    };
+    let rectangle = |half_size: Vec2| {
+        vec![
+            Vec2::new(-half_size.x, -half_size.y),
+            Vec2::new(half_size.x, -half_size.y),
+            half_size,
+            Vec2::new(-half_size.x, half_size.y),
+        ]
+    };
+    let ellipse = |half_size: Vec2| {
+        (0..CURVE_SEGMENTS)
+            .map(|index| half_size * Vec2::from_angle(index as f32 * TAU / CURVE_SEGMENTS as f32))
+            .collect::<Vec<_>>()
+    };
+
+    match shape {
+        LightOccluder2dShape::Rectangle { half_size }
+        | LightOccluder2dShape::RoundedRectangle { half_size, .. } => {
+            push_loop(rectangle(*half_size));
+        }
+        LightOccluder2dShape::Capsule {
+            half_length,
+            radius,
+        } => {
+            // Each half of the capsule is a semicircle around one end of its inner segment.
+            let half_segments = CURVE_SEGMENTS / 2;
+            let points = (0..=half_segments)
+                .map(|index| {
+                    let angle = index as f32 * TAU / CURVE_SEGMENTS as f32;
+                    Vec2::new(0.0, *half_length) + Vec2::from_angle(angle) * *radius
+                })
+                .chain((0..=half_segments).map(|index| {
+                    let angle = TAU * 0.5 + index as f32 * TAU / CURVE_SEGMENTS as f32;
+                    Vec2::new(0.0, -half_length) + Vec2::from_angle(angle) * *radius
+                }))
+                .collect();
+            push_loop(points);
+        }
+        LightOccluder2dShape::Ellipse { half_size } => {
+            push_loop(ellipse(*half_size));
+        }
+        LightOccluder2dShape::Annulus {
+            inner_radius,
+            outer_radius,
+        } => {
+            push_loop(ellipse(Vec2::splat(inner_radius.max(0.0))));
+            push_loop(ellipse(Vec2::splat(outer_radius.max(0.0))));
+        }
+        LightOccluder2dShape::Triangle { a, b, c } => {
+            push_loop(vec![*a, *b, *c]);
+        }
+        LightOccluder2dShape::ConvexPolygon(vertices) | LightOccluder2dShape::Polygon(vertices) => {
+            if vertices.len() >= 2 {
+                push_loop(vertices.clone());
+            }
+        }
+        LightOccluder2dShape::Segment { start, end } => {
+            segments.push((to_world(*start), to_world(*end)));
+        }
+        LightOccluder2dShape::AlphaMask { image, .. } => {
+            // The mask's silhouette isn't available outside of rendering, so its bounds are
+            // used instead.
+            if let Some(image) = images.get(image) {
+                push_loop(rectangle(image.size().as_vec2() * 0.5));
+            }
+        }
+        LightOccluder2dShape::Sdf { world_size, .. } => {
+            push_loop(rectangle(world_size.abs() * 0.5));
+        }
+    }
+}
+
+impl CpuLight {
+    /// Returns the light reaching `pos`.
+    pub(crate) fn illuminate(&self, pos: Vec2, occluders: &[CpuOccluder]) -> Vec3 {
+        let nearest = self.nearest_point(pos);
+        let dist = match self.shape {
+            CpuLightShape::Point {
+                center,
+                ellipse_axis,
+                ellipse_aspect,
+            } => {
+                let d = pos - center;
+                Vec2::new(
+                    d.dot(ellipse_axis),
+                    d.dot(ellipse_axis.perp()) * ellipse_aspect,
+                )
+                .length()
+            }
+            _ => nearest.distance(pos),
+        };
+        if dist >= self.radius {
+            return Vec3::ZERO;
+        }
+
+        let falloff = curve_attenuation(
+            hotspot_distance(dist, self.inner_radius, self.radius),
+            self.radius,
+            1.0,
+            self.falloff,
+            self.falloff_curve,
+            self.attenuation_exponent,
+        );
+        let mut strength = self.intensity * falloff;
+        if let CpuLightShape::Spot {
+            direction,
+            cos_inner,
+            cos_outer,
+            edge_exponent,
+            ..
+        } = self.shape
+        {
+            let to_frag = (pos - nearest).normalize_or_zero();
+            strength *=
+                smoothstep(cos_outer, cos_inner, to_frag.dot(direction)).powf(edge_exponent);
+        }
+        if strength == 0.0 {
+            return Vec3::ZERO;
+        }
+
+        let visibility = if self.cast_shadows {
+            Vec3::ONE.lerp(
+                transmittance(pos, nearest, self.occlusion_layers, occluders),
+                self.shadow_strength,
+            )
+        } else {
+            Vec3::ONE
+        };
+
+        let color = match self.edge_color {
+            Some(edge_color) => edge_color.lerp(self.color, falloff.clamp(0.0, 1.0)),
+            None => self.color,
+        };
+        clamp_brightness(color * strength * visibility, self.max_brightness)
+    }
+
+    /// Returns the point the light is emitted from that's nearest to `pos`.
+    fn nearest_point(&self, pos: Vec2) -> Vec2 {
+        match self.shape {
+            CpuLightShape::Point { center, .. } => center,
+            CpuLightShape::Spot {
+                center,
+                direction,
+                source_width,
+                ..
+            } => {
+                if source_width <= 0.0 {
+                    return center;
+                }
+                let bar_direction = direction.perp();
+                let half_width = source_width * 0.5;
+                center
+                    + bar_direction
+                        * (pos - center)
+                            .dot(bar_direction)
+                            .clamp(-half_width, half_width)
+            }
+            CpuLightShape::Line { start, end } => {
+                let segment = end - start;
+                let length_squared = segment.length_squared();
+                if length_squared == 0.0 {
+                    return start;
+                }
+                start + segment * ((pos - start).dot(segment) / length_squared).clamp(0.0, 1.0)
+            }
+            CpuLightShape::Area {
+                center,
+                half_size,
+                rotation,
+            } => {
+                let d = pos - center;
+                let local_pos = Vec2::new(d.dot(rotation), d.dot(rotation.perp()));
+                let nearest = local_pos.clamp(-half_size, half_size);
+                center + nearest.x * rotation + nearest.y * rotation.perp()
+            }
+            CpuLightShape::Ring {
+                center,
+                radius,
+                arc_direction,
+                arc_half_angle,
+            } => {
+                let to_pos = pos - center;
+                // The ring's center is equally near to every point on the ring.
+                let angle = if to_pos == Vec2::ZERO {
+                    0.0
+                } else {
+                    arc_direction
+                        .perp_dot(to_pos)
+                        .atan2(arc_direction.dot(to_pos))
+                };
+                center
+                    + Vec2::from_angle(angle.clamp(-arc_half_angle, arc_half_angle))
+                        .rotate(arc_direction)
+                        * radius
+            }
+            CpuLightShape::Polygon {
+                ref vertices,
+                closed,
+            } => nearest_point_on_polygon(pos, vertices, closed),
+        }
+    }
+}
+
+/// Returns the point on a polygon's outline nearest to `pos`, or `pos` itself if it's inside a
+/// closed polygon, matching the light map pass.
+fn nearest_point_on_polygon(pos: Vec2, vertices: &[Vec2], closed: bool) -> Vec2 {
+    let Some(&first) = vertices.first() else {
+        return pos;
+    };
+    let mut nearest = first;
+    let mut inside = false;
+
+    // Each edge joins a vertex to the one before it, and open polylines have no edge joining
+    // their first and last vertices.
+    for (i, &vi) in vertices.iter().enumerate() {
+        let vj = match i {
+            0 if !closed => continue,
+            0 => vertices[vertices.len() - 1],
+            _ => vertices[i - 1],
+        };
+
+        let e = vj - vi;
+        let w = pos - vi;
+        let b = vi + e * (w.dot(e) / e.length_squared().max(1e-6)).clamp(0.0, 1.0);
+        if pos.distance_squared(b) < pos.distance_squared(nearest) {
+            nearest = b;
+        }
+
+        let crossings = [pos.y >= vi.y, pos.y < vj.y, e.x * w.y > e.y * w.x];
+        if crossings.iter().all(|&c| c) || crossings.iter().all(|&c| !c) {
+            inside = !inside;
+        }
+    }
+
+    if inside && closed { pos } else { nearest }
+}
+
+/// Returns the fraction of each color channel that travels from a light at `target` to `pos`.
+fn transmittance(
+    pos: Vec2,
+    target: Vec2,
+    occlusion_layers: u32,
+    occluders: &[CpuOccluder],
+) -> Vec3 {
+    let ray_length = pos.distance(target);
+    if ray_length <= RAY_TARGET_EPSILON {
+        return Vec3::ONE;
+    }
+    let ray_end = target + (pos - target) / ray_length * RAY_TARGET_EPSILON;
+    let ray_bounds = Rect::from_corners(pos, ray_end);
+
+    occluders
+        .iter()
+        .filter(|occluder| occluder.occlusion_layers & occlusion_layers != 0)
+        .filter(|occluder| {
+            // Rays and outlines can be axis aligned, so their bounds may have no area.
+            occluder.bounds.min.cmple(ray_bounds.max).all()
+                && occluder.bounds.max.cmpge(ray_bounds.min).all()
+        })
+        .filter(|occluder| {
+            occluder
+                .segments
+                .iter()
+                .any(|(start, end)| segments_intersect(pos, ray_end, *start, *end))
+        })
+        .fold(Vec3::ONE, |transmittance, occluder| {
+            transmittance * occluder.transmittance
+        })
+}
+
+/// Whether the segments from `a0` to `a1` and from `b0` to `b1` cross.
+fn segments_intersect(a0: Vec2, a1: Vec2, b0: Vec2, b1: Vec2) -> bool {
+    let d = a1 - a0;
+    let e = b1 - b0;
+    let denominator = d.perp_dot(e);
+    if denominator.abs() <= f32::EPSILON {
+        return false;
+    }
+
+    let f = b0 - a0;
+    let t = f.perp_dot(e) / denominator;
+    let u = f.perp_dot(d) / denominator;
+    (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)
+}
+
+/// Combines the light of a light with the light accumulated so far, matching the light map
+/// pass.
+pub(crate) fn blend_light(blend: LightBlend2d, accumulated: Vec3, light: Vec3) -> Vec3 {
+    match blend {
+        LightBlend2d::Add => accumulated + light,
+        LightBlend2d::Max => accumulated.max(light),
+        LightBlend2d::Screen => {
+            accumulated + light.min(Vec3::ONE) * (Vec3::ONE - accumulated).max(Vec3::ZERO)
+        }
+    }
+}
+
+/// Scales a color down so that its linear luminance is at most `max_brightness`, keeping its
+/// hue, matching the light map pass.
+pub(crate) fn clamp_brightness(color: Vec3, max_brightness: Option<f32>) -> Vec3 {
+    let Some(max_brightness) = max_brightness else {
+        return color;
+    };
+    let luminance = color.dot(Vec3::new(0.2126, 0.7152, 0.0722));
+    if luminance <= max_brightness {
+        return color;
+    }
+    color * (max_brightness.max(0.0) / luminance)
+}
+
+/// Returns the rotation of a transform around the z axis, as the cosine and sine of its angle.
+fn rotation_2d(global_transform: &GlobalTransform) -> Vec2 {
+    (global_transform.rotation() * Vec3::X)
+        .xy()
+        .normalize_or(Vec2::X)
+}
+
+// Compute light attenuation, matching the light map pass.
+// See https://lisyarus.github.io/blog/posts/point-light-attenuation.html
+fn attenuation(dist: f32, radius: f32, intensity: f32, falloff: f32) -> f32 {
+    let s = dist / radius;
+    if s > 1.0 {
+        return 0.0;
+    }
+    let s2 = s * s;
+    intensity * (1.0 - s2) * (1.0 - s2) / (1.0 + falloff * s2)
+}
+
+// Remaps a distance from a light so that attenuation begins at the light's inner radius,
+// matching the light map pass.
+fn hotspot_distance(dist: f32, inner_radius: f32, radius: f32) -> f32 {
+    if inner_radius <= 0.0 {
+        return dist;
+    }
+    if dist <= inner_radius {
+        return 0.0;
+    }
+    (dist - inner_radius) * radius / (radius - inner_radius)
+}
+
+// Compute light attenuation along a falloff curve, matching the light map pass.
+fn curve_attenuation(
+    dist: f32,
+    radius: f32,
+    intensity: f32,
+    falloff: f32,
+    falloff_curve: FalloffCurve,
+    attenuation_exponent: f32,
+) -> f32 {
+    let s = dist / radius;
+    if s > 1.0 {
+        return 0.0;
+    }
+    match falloff_curve {
+        FalloffCurve::InverseSquare if attenuation_exponent != 2.0 => {
+            let s2 = s * s;
+            intensity * (1.0 - s2).powf(attenuation_exponent.max(0.0)) / (1.0 + falloff * s2)
+        }
+        FalloffCurve::InverseSquare => attenuation(dist, radius, intensity, falloff),
+        FalloffCurve::Linear => intensity * (1.0 - s),
+        FalloffCurve::Smoothstep => intensity * (1.0 - smoothstep(0.0, 1.0, s)),
+        FalloffCurve::Exponent(exponent) => intensity * (1.0 - s).powf(exponent.max(0.0)),
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::luminance;
+
+    #[test]
+    fn clamp_brightness_without_limit_is_unchanged() {
+        let color = Vec3::new(4.0, 2.0, 1.0);
+        assert_eq!(clamp_brightness(color, None), color);
+    }
+
+    #[test]
+    fn clamp_brightness_below_limit_is_unchanged() {
+        let color = Vec3::new(0.2, 0.1, 0.05);
+        assert_eq!(clamp_brightness(color, Some(1.0)), color);
+    }
+
+    #[test]
+    fn clamp_brightness_keeps_hue() {
+        let color = Vec3::new(4.0, 2.0, 1.0);
+        let clamped = clamp_brightness(color, Some(0.5));
+
+        assert!((luminance(clamped) - 0.5).abs() < 1e-5);
+        assert!((clamped / clamped.x - color / color.x).abs().max_element() < 1e-5);
+    }
+
+    #[test]
+    fn clamp_brightness_with_negative_limit_is_black() {
+        assert_eq!(
+            clamp_brightness(Vec3::new(1.0, 1.0, 1.0), Some(-1.0)),
+            Vec3::ZERO
+        );
+    }
+
+    #[test]
+    fn light_max_brightness_clamps_illumination() {
+        let light = PointLight2d {
+            intensity: 10.0,
+            radius: 100.0,
+            max_brightness: Some(0.5),
+            ..Default::default()
+        };
+        let cpu_light = CpuLight::point(&light, &GlobalTransform::default(), None, None);
+
+        let illumination = cpu_light.illuminate(Vec2::new(10.0, 0.0), &[]);
+        assert!((luminance(illumination) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn inverse_square_curve_matches_attenuation() {
+        for step in 0..=20 {
+            let dist = step as f32 * 5.0;
+            let expected = attenuation(dist, 100.0, 2.0, 0.5);
+
+            let exact = curve_attenuation(dist, 100.0, 2.0, 0.5, FalloffCurve::InverseSquare, 2.0);
+            assert_eq!(exact, expected);
+
+            // Exponents near two take the general path, which must agree with the original
+            // attenuation.
+            let general =
+                curve_attenuation(dist, 100.0, 2.0, 0.5, FalloffCurve::InverseSquare, 2.0001);
+            assert!((general - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn nearest_point_inside_closed_polygon_is_itself() {
+        let square = [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+        ];
+        let pos = Vec2::new(0.25, -0.5);
+
+        assert_eq!(nearest_point_on_polygon(pos, &square, true), pos);
+        assert_eq!(
+            nearest_point_on_polygon(pos, &square, false),
+            Vec2::new(0.25, -1.0)
+        );
+    }
+
+    #[test]
+    fn nearest_point_on_ring_is_clamped_to_its_arc() {
+        let light = RingLight2d {
+            radius: 10.0,
+            thickness: 4.0,
+            arc_start: -45.0,
+            arc_end: 45.0,
+            ..Default::default()
+        };
+        let cpu_light = CpuLight::ring(&light, &GlobalTransform::default(), None, None);
+
+        let nearest = cpu_light.nearest_point(Vec2::new(20.0, 0.0));
+        assert!(nearest.distance(Vec2::new(10.0, 0.0)) < 1e-4);
+
+        let nearest = cpu_light.nearest_point(Vec2::new(-20.0, 0.1));
+        assert!(nearest.distance(Vec2::from_angle(45f32.to_radians()) * 10.0) < 1e-4);
+    }
+}
@@ -0,0 +1,310 @@
+//! A module which contains [`DayNightCycle2dPlugin`], which animates the ambient light, and
+//! optionally a sun, over the course of a day.
+
+use core::time::Duration;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    color::Color,
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        resource::Resource,
+        schedule::IntoScheduleConfigs,
+        system::{Query, Res, ResMut},
+        world::Ref,
+    },
+    math::FloatExt,
+    prelude::{ReflectComponent, ReflectDefault, ReflectResource},
+    reflect::Reflect,
+    time::Time,
+};
+
+use crate::{
+    light::{DirectionalLight2d, Light2d, lerp_color_perceptual},
+    probe::sample_light_probes,
+};
+
+/// A plugin which advances the [`TimeOfDay`], and sets the ambient light of every camera with a
+/// [`Light2d`] from the keyframes of the [`DayNightCycle2d`].
+///
+/// [`DirectionalLight2d`]s with a [`DayNightSun2d`] also follow the cycle, turning across the
+/// sky and fading in and out with the day.
+///
+/// The cycle only writes the ambient light's color and brightness, so an ambient gradient is
+/// left in place, and is overwritten every frame the time of day or the cycle changes. Time is
+/// advanced and the lights are updated in [`PostUpdate`], so changes made to the
+/// [`TimeOfDay`] in [`Update`](bevy::app::Update) apply in the same frame.
+///
+/// Requires [`Light2dPlugin`](crate::plugin::Light2dPlugin).
+pub struct DayNightCycle2dPlugin;
+
+impl Plugin for DayNightCycle2dPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<TimeOfDay>()
+            .register_type::<DayNightCycle2d>()
+            .register_type::<DayNightSun2d>()
+            .init_resource::<TimeOfDay>()
+            .init_resource::<DayNightCycle2d>()
+            .add_systems(
+                PostUpdate,
+                (advance_time_of_day, apply_day_night_cycle)
+                    .chain()
+                    .before(sample_light_probes),
+            );
+    }
+}
+
+/// The time of day of the [`DayNightCycle2d`], in hours from `0.0` (midnight) to `24.0`.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct TimeOfDay {
+    /// How long a full day lasts, in seconds of [`Time`]. A day length of zero stops the clock.
+    pub day_length: Duration,
+    /// Whether the time of day is paused.
+    pub paused: bool,
+    hour: f32,
+}
+
+impl Default for TimeOfDay {
+    /// Returns a [`TimeOfDay`] at noon, where a day lasts 10 minutes.
+    fn default() -> Self {
+        Self {
+            day_length: Duration::from_secs(600),
+            paused: false,
+            hour: 12.0,
+        }
+    }
+}
+
+impl TimeOfDay {
+    /// Returns a [`TimeOfDay`] at `hour`, where a day lasts `day_length`.
+    pub fn new(hour: f32, day_length: Duration) -> Self {
+        let mut time_of_day = Self {
+            day_length,
+            ..Self::default()
+        };
+        time_of_day.set_hour(hour);
+        time_of_day
+    }
+
+    /// Returns the time of day, in hours from `0.0` (midnight) up to `24.0`.
+    pub fn hour(&self) -> f32 {
+        self.hour
+    }
+
+    /// Sets the time of day, in hours. Hours outside of `0.0..24.0` wrap around into the
+    /// previous or next day.
+    pub fn set_hour(&mut self, hour: f32) {
+        self.hour = wrap_hour(hour);
+    }
+}
+
+/// The keyframes of the lighting over the course of a day.
+///
+/// Between keyframes, colors are interpolated with [`lerp_color_perceptual`], so dawn blends
+/// from the blue of night to the orange of sunrise without passing through gray, and everything
+/// else is interpolated linearly. The keyframes wrap around midnight, so the last keyframe of
+/// the day blends into the first.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct DayNightCycle2d {
+    /// The keyframes of the cycle, in any order. A cycle without keyframes leaves the lights
+    /// untouched.
+    pub keyframes: Vec<DayNightKeyframe2d>,
+}
+
+impl Default for DayNightCycle2d {
+    /// Returns a [`DayNightCycle2d`] with a dark blue night, an orange dawn and dusk, and a
+    /// white noon, where the sun rises in the east at 6:00 and sets in the west at 18:00.
+    fn default() -> Self {
+        let night = DayNightKeyframe2d {
+            hour: 0.0,
+            ambient_color: Color::srgb(0.2, 0.25, 0.5),
+            ambient_brightness: 0.2,
+            sun_color: Color::srgb(1.0, 0.5, 0.2),
+            sun_illuminance: 0.0,
+            sun_direction: -90.0,
+        };
+        Self {
+            keyframes: vec![
+                night.clone(),
+                DayNightKeyframe2d {
+                    hour: 6.0,
+                    ambient_color: Color::srgb(1.0, 0.6, 0.4),
+                    ambient_brightness: 0.6,
+                    sun_color: Color::srgb(1.0, 0.5, 0.2),
+                    sun_illuminance: 0.3,
+                    sun_direction: -10.0,
+                },
+                DayNightKeyframe2d {
+                    hour: 12.0,
+                    ambient_color: Color::WHITE,
+                    ambient_brightness: 1.0,
+                    sun_color: Color::WHITE,
+                    sun_illuminance: 1.0,
+                    sun_direction: -90.0,
+                },
+                DayNightKeyframe2d {
+                    hour: 18.0,
+                    ambient_color: Color::srgb(1.0, 0.5, 0.3),
+                    ambient_brightness: 0.6,
+                    sun_color: Color::srgb(1.0, 0.4, 0.2),
+                    sun_illuminance: 0.3,
+                    sun_direction: -170.0,
+                },
+                DayNightKeyframe2d {
+                    hour: 21.0,
+                    ..night
+                },
+            ],
+        }
+    }
+}
+
+impl DayNightCycle2d {
+    /// Returns the lighting of the cycle at `hour`, interpolated between the keyframes on
+    /// either side of it, or `None` if the cycle has no keyframes.
+    pub fn sample(&self, hour: f32) -> Option<DayNightKeyframe2d> {
+        let hour = wrap_hour(hour);
+        // The last keyframe at or before the hour, and the first after it, wrapping around
+        // midnight when the hour is before the first keyframe or after the last.
+        let before = self
+            .keyframes
+            .iter()
+            .filter(|keyframe| wrap_hour(keyframe.hour) <= hour)
+            .max_by(|a, b| wrap_hour(a.hour).total_cmp(&wrap_hour(b.hour)))
+            .or_else(|| {
+                self.keyframes
+                    .iter()
+                    .max_by(|a, b| wrap_hour(a.hour).total_cmp(&wrap_hour(b.hour)))
+            })?;
+        let after = self
+            .keyframes
+            .iter()
+            .filter(|keyframe| wrap_hour(keyframe.hour) > hour)
+            .min_by(|a, b| wrap_hour(a.hour).total_cmp(&wrap_hour(b.hour)))
+            .or_else(|| {
+                self.keyframes
+                    .iter()
+                    .min_by(|a, b| wrap_hour(a.hour).total_cmp(&wrap_hour(b.hour)))
+            })?;
+
+        let span = (wrap_hour(after.hour) - wrap_hour(before.hour)).rem_euclid(24.0);
+        let t = if span > 0.0 {
+            (hour - wrap_hour(before.hour)).rem_euclid(24.0) / span
+        } else {
+            0.0
+        };
+        Some(DayNightKeyframe2d {
+            hour,
+            ..before.lerp(after, t)
+        })
+    }
+}
+
+/// The lighting of a [`DayNightCycle2d`] at an hour of the day.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+pub struct DayNightKeyframe2d {
+    /// The time of day of the keyframe, in hours from `0.0` (midnight) to `24.0`.
+    pub hour: f32,
+    /// The color of the ambient light.
+    pub ambient_color: Color,
+    /// The brightness of the ambient light.
+    pub ambient_brightness: f32,
+    /// The color of every [`DirectionalLight2d`] with a [`DayNightSun2d`].
+    pub sun_color: Color,
+    /// The illuminance of every [`DirectionalLight2d`] with a [`DayNightSun2d`].
+    pub sun_illuminance: f32,
+    /// The angle (in degrees) of the direction every [`DirectionalLight2d`] with a
+    /// [`DayNightSun2d`] shines towards. The sun turns the shortest way between keyframes.
+    pub sun_direction: f32,
+}
+
+impl Default for DayNightKeyframe2d {
+    /// Returns a [`DayNightKeyframe2d`] at noon, with a white ambient light and a white sun
+    /// shining downwards.
+    fn default() -> Self {
+        Self {
+            hour: 12.0,
+            ambient_color: Color::WHITE,
+            ambient_brightness: 1.0,
+            sun_color: Color::WHITE,
+            sun_illuminance: 1.0,
+            sun_direction: -90.0,
+        }
+    }
+}
+
+impl DayNightKeyframe2d {
+    /// Returns the keyframe `t` of the way from `self` to `other`, where `t` is clamped between
+    /// `0.0` and `1.0`. The hour is taken from `self`.
+    fn lerp(&self, other: &DayNightKeyframe2d, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let turn = (other.sun_direction - self.sun_direction + 180.0).rem_euclid(360.0) - 180.0;
+        Self {
+            hour: self.hour,
+            ambient_color: lerp_color_perceptual(self.ambient_color, other.ambient_color, t),
+            ambient_brightness: self.ambient_brightness.lerp(other.ambient_brightness, t),
+            sun_color: lerp_color_perceptual(self.sun_color, other.sun_color, t),
+            sun_illuminance: self.sun_illuminance.lerp(other.sun_illuminance, t),
+            sun_direction: self.sun_direction + turn * t,
+        }
+    }
+}
+
+/// Marks a [`DirectionalLight2d`] as the sun of the [`DayNightCycle2d`], so its color,
+/// illuminance and direction follow the time of day.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct DayNightSun2d;
+
+fn advance_time_of_day(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    if time_of_day.paused || time_of_day.day_length.is_zero() {
+        return;
+    }
+    let hours = time.delta_secs() / time_of_day.day_length.as_secs_f32() * 24.0;
+    if hours > 0.0 {
+        let hour = time_of_day.hour + hours;
+        time_of_day.set_hour(hour);
+    }
+}
+
+fn apply_day_night_cycle(
+    time_of_day: Res<TimeOfDay>,
+    cycle: Res<DayNightCycle2d>,
+    mut cameras: Query<&mut Light2d>,
+    mut suns: Query<(&mut DirectionalLight2d, Ref<DayNightSun2d>)>,
+) {
+    let changed = time_of_day.is_changed() || cycle.is_changed();
+    let Some(keyframe) = cycle.sample(time_of_day.hour()) else {
+        return;
+    };
+
+    // Lights are only written when the cycle moves on, or when they're new to it, so that they
+    // aren't marked as changed every frame while the time of day is paused.
+    for mut light_2d in &mut cameras {
+        if !changed && !light_2d.is_added() {
+            continue;
+        }
+        light_2d.ambient_light.color = keyframe.ambient_color;
+        light_2d.ambient_light.brightness = keyframe.ambient_brightness;
+    }
+
+    for (mut directional_light, sun) in &mut suns {
+        if !changed && !directional_light.is_added() && !sun.is_added() {
+            continue;
+        }
+        directional_light.color = keyframe.sun_color;
+        directional_light.illuminance = keyframe.sun_illuminance;
+        directional_light.direction = keyframe.sun_direction;
+    }
+}
+
+/// Wraps an hour between `0.0` and `24.0`.
+fn wrap_hour(hour: f32) -> f32 {
+    let hour = hour.rem_euclid(24.0);
+    // `rem_euclid` can round up to exactly `24.0` for tiny negative hours.
+    if hour >= 24.0 { 0.0 } else { hour }
+}
@@ -0,0 +1,140 @@
+//! A module which contains [`LightFade2d`], which fades lights in when they're spawned and out
+//! before they're despawned.
+
+use core::time::Duration;
+
+use bevy::{
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::Without,
+        system::{Commands, EntityCommands, Query, Res},
+    },
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+};
+
+use crate::modulation::{Light2dAnimationTime, LightModulation2d};
+
+/// Fades a light in when it's spawned, and out when [`FadingOut2d`] is inserted, rather than
+/// popping in and out at full intensity.
+///
+/// Like the other effects in [`modulation`](crate::modulation), the fade multiplies the light's
+/// [`LightModulation2d`], so it combines with flickers and pulses, and user code can freely
+/// change the light's intensity mid-fade. Fades are driven by [`Light2dAnimationTime`], so they
+/// can be paused.
+///
+/// The light starts fading in whenever the component is inserted, so a light can be faded in
+/// again by reinserting it.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+#[require(LightModulation2d)]
+pub struct LightFade2d {
+    /// How long the light takes to fade in to its full intensity.
+    pub fade_in: Duration,
+    /// How long the light takes to fade out, once [`FadingOut2d`] is inserted.
+    pub fade_out: Duration,
+    level: f32,
+}
+
+impl Default for LightFade2d {
+    /// Returns a [`LightFade2d`] which fades a light in and out over half a second.
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_millis(500))
+    }
+}
+
+impl LightFade2d {
+    /// Returns a [`LightFade2d`] which fades a light in over `fade_in`, and out over
+    /// `fade_out`.
+    pub fn new(fade_in: Duration, fade_out: Duration) -> Self {
+        Self {
+            fade_in,
+            fade_out,
+            level: 0.0,
+        }
+    }
+
+    /// Returns how far the light has faded in, from `0.0` while it's dark to `1.0` at its full
+    /// intensity.
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+}
+
+/// Fades out the light on the same entity, over the `fade_out` of its [`LightFade2d`].
+///
+/// Lights without a [`LightFade2d`] fade out instantly. Use
+/// [`fade_out_light`](LightFadeCommandsExt::fade_out_light) to fade out and despawn a light.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+#[require(LightModulation2d)]
+pub enum FadingOut2d {
+    /// The entity, and its descendants, are despawned once the light has faded out.
+    #[default]
+    Despawn,
+    /// The light is kept dark once it has faded out. Removing [`FadingOut2d`] fades the light
+    /// back in.
+    Disable,
+}
+
+/// An extension trait for [`EntityCommands`], which fades out lights.
+pub trait LightFadeCommandsExt {
+    /// Fades out the light on the entity, then despawns the entity.
+    fn fade_out_light(&mut self) -> &mut Self;
+}
+
+impl LightFadeCommandsExt for EntityCommands<'_> {
+    fn fade_out_light(&mut self) -> &mut Self {
+        self.insert(FadingOut2d::Despawn)
+    }
+}
+
+pub(crate) fn fade_lights(
+    mut commands: Commands,
+    animation_time: Res<Light2dAnimationTime>,
+    mut lights: Query<(
+        Entity,
+        &mut LightFade2d,
+        &mut LightModulation2d,
+        Option<&FadingOut2d>,
+    )>,
+    mut unfaded_lights: Query<(Entity, &FadingOut2d, &mut LightModulation2d), Without<LightFade2d>>,
+) {
+    let delta = animation_time.delta_secs();
+
+    for (entity, mut fade, mut modulation, fading_out) in &mut lights {
+        let level = match fading_out {
+            Some(_) => fade.level - fade_step(fade.fade_out, delta),
+            None => fade.level + fade_step(fade.fade_in, delta),
+        }
+        .clamp(0.0, 1.0);
+        // Lights that have finished fading are left untouched, so that they aren't marked as
+        // changed.
+        if fade.level != level {
+            fade.level = level;
+        }
+        modulation.intensity *= level;
+
+        if fading_out == Some(&FadingOut2d::Despawn) && level <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for (entity, fading_out, mut modulation) in &mut unfaded_lights {
+        modulation.intensity = 0.0;
+        if *fading_out == FadingOut2d::Despawn {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Returns how far a fade lasting `duration` moves in `delta` seconds.
+fn fade_step(duration: Duration, delta: f32) -> f32 {
+    let duration = duration.as_secs_f32();
+    if duration > 0.0 {
+        delta / duration
+    } else {
+        1.0
+    }
+}
@@ -0,0 +1,161 @@
+//! A module which contains [`AmbientFlash2d`], which briefly brightens a camera's ambient
+//! light, like a flash of lightning.
+
+use core::time::Duration;
+
+use bevy::{
+    color::{Color, ColorToComponents},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        lifecycle::HookContext,
+        system::{Commands, Query, Res},
+        world::{DeferredWorld, EntityWorldMut},
+    },
+    math::Vec3,
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+};
+
+use crate::modulation::Light2dAnimationTime;
+
+/// Briefly brightens the ambient light of the camera it's inserted on, alongside [`Light2d`],
+/// like a flash of lightning during a storm.
+///
+/// The flash rises from nothing to its peak over `attack`, then fades back out over `decay`,
+/// and removes itself once it has faded. While it plays, the ambient light is at least as
+/// bright as the flash, in each color channel, so a flash never darkens the scene. The
+/// camera's [`AmbientLight2d`] is left untouched, so the ambient light returns to it when the
+/// flash ends.
+///
+/// Inserting another flash while one is playing doesn't cut it short: the flashes overlap,
+/// and the ambient light takes the brightest of them. Flashes are driven by
+/// [`Light2dAnimationTime`], so they can be paused.
+///
+/// [`Light2d`]: crate::light::Light2d
+/// [`AmbientLight2d`]: crate::light::AmbientLight2d
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default)]
+#[require(AmbientFlashes2d)]
+#[component(on_insert = play_ambient_flash)]
+pub struct AmbientFlash2d {
+    /// The brightness of the flash at its peak, which is multiplied against the linear RGB
+    /// representation of its color, like an [`AmbientLight2d`]'s.
+    ///
+    /// [`AmbientLight2d`]: crate::light::AmbientLight2d
+    pub peak_brightness: f32,
+    /// How long the flash takes to rise to its peak.
+    pub attack: Duration,
+    /// How long the flash takes to fade out after its peak.
+    pub decay: Duration,
+    /// The flash's color tint.
+    pub color: Color,
+}
+
+impl Default for AmbientFlash2d {
+    /// Returns a white [`AmbientFlash2d`] with a peak brightness of `2.0`, which rises in 50
+    /// milliseconds and fades out over half a second.
+    fn default() -> Self {
+        Self {
+            peak_brightness: 2.0,
+            attack: Duration::from_millis(50),
+            decay: Duration::from_millis(500),
+            color: Color::WHITE,
+        }
+    }
+}
+
+impl AmbientFlash2d {
+    /// Returns how bright the flash is, from `0.0` to `1.0` at its peak, `elapsed` seconds after
+    /// it started.
+    fn level(&self, elapsed: f32) -> f32 {
+        let attack = self.attack.as_secs_f32();
+        if elapsed < attack {
+            return elapsed / attack;
+        }
+        let decay = self.decay.as_secs_f32();
+        if decay > 0.0 {
+            (1.0 - (elapsed - attack) / decay).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn duration(&self) -> f32 {
+        (self.attack + self.decay).as_secs_f32()
+    }
+}
+
+/// The [`AmbientFlash2d`]s playing on a camera.
+#[derive(Component, Clone, Default)]
+pub struct AmbientFlashes2d {
+    pub(crate) flashes: Vec<PlayingAmbientFlash>,
+}
+
+impl AmbientFlashes2d {
+    /// Returns the linear RGB color the camera's ambient light is brightened to, which is the
+    /// brightest of its flashes in each color channel.
+    pub fn color(&self) -> Vec3 {
+        self.flashes.iter().fold(Vec3::ZERO, |color, playing| {
+            let level = playing.flash.level(playing.elapsed);
+            color.max(
+                playing.flash.color.to_linear().to_vec3() * playing.flash.peak_brightness * level,
+            )
+        })
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct PlayingAmbientFlash {
+    pub(crate) flash: AmbientFlash2d,
+    // How long ago the flash started, in seconds.
+    pub(crate) elapsed: f32,
+}
+
+fn play_ambient_flash(mut world: DeferredWorld, context: HookContext) {
+    let Some(flash) = world.get::<AmbientFlash2d>(context.entity).cloned() else {
+        return;
+    };
+    if let Some(mut flashes) = world.get_mut::<AmbientFlashes2d>(context.entity) {
+        flashes.flashes.push(PlayingAmbientFlash {
+            flash,
+            elapsed: 0.0,
+        });
+    }
+}
+
+pub(crate) fn update_ambient_flashes(
+    mut commands: Commands,
+    animation_time: Res<Light2dAnimationTime>,
+    mut cameras: Query<(Entity, &mut AmbientFlashes2d)>,
+) {
+    let delta = animation_time.delta_secs();
+    if delta <= 0.0 {
+        return;
+    }
+
+    for (entity, mut flashes) in &mut cameras {
+        if flashes.flashes.is_empty() {
+            continue;
+        }
+        for playing in &mut flashes.flashes {
+            playing.elapsed += delta;
+        }
+        flashes
+            .flashes
+            .retain(|playing| playing.elapsed < playing.flash.duration());
+
+        // The flashes are checked again when the command is applied, in case another flash
+        // was inserted in the meantime.
+        if flashes.flashes.is_empty() {
+            commands.entity(entity).queue(|mut entity: EntityWorldMut| {
+                if entity
+                    .get::<AmbientFlashes2d>()
+                    .is_some_and(|flashes| flashes.flashes.is_empty())
+                {
+                    entity.remove::<(AmbientFlash2d, AmbientFlashes2d)>();
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! A module which contains debug gizmos for lights and light occluders.
+
+use core::iter;
+
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    asset::Assets,
+    camera::visibility::{ViewVisibility, VisibilitySystems},
+    color::{
+        Alpha, Color,
+        palettes::css::{LIME, YELLOW},
+    },
+    ecs::{
+        schedule::IntoScheduleConfigs,
+        system::{Query, Res},
+    },
+    gizmos::{
+        AppGizmoBuilder,
+        config::{GizmoConfigGroup, GizmoConfigStore},
+        gizmos::Gizmos,
+    },
+    image::Image,
+    math::{Isometry2d, Rot2, Vec2, Vec3, Vec3Swizzles, primitives::Capsule2d},
+    reflect::Reflect,
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    light::{PointLight2d, SpotLight2d},
+    modulation::LightModulation2d,
+    occluder::{LightOccluder2d, LightOccluder2dShape},
+};
+
+/// A plugin which draws the outline of every [`LightOccluder2d`], the radius of every
+/// [`PointLight2d`], and the cone of every [`SpotLight2d`], using [`Gizmos`].
+///
+/// Outlines are drawn with the transform used when extracting occluders for rendering.
+/// Occluders which are disabled, fully transparent, or culled are drawn in a dimmed color.
+///
+/// Point lights are drawn as their effective radius after any scaling, as an ellipse if the
+/// light is stretched, in the light's own color, with a dot at the light's center. They can be
+/// toggled at runtime with [`Light2dGizmoConfigGroup::draw_point_lights`].
+///
+/// Spot lights are drawn with the same math as the light map pass: the outer cone, the inner
+/// cone, the segment the light is emitted from, and the arc of the light's radius. Lights which
+/// are disabled or hidden are drawn in a dimmed color, or not at all if
+/// [`Light2dGizmoConfigGroup::draw_inactive_lights`] is `false`.
+///
+/// Requires the `gizmos` feature.
+pub struct Light2dGizmosPlugin;
+
+impl Plugin for Light2dGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_gizmo_group::<Light2dGizmoConfigGroup>()
+            .add_systems(
+                PostUpdate,
+                (
+                    draw_occluder_gizmos,
+                    draw_point_light_gizmos,
+                    draw_spot_light_gizmos,
+                )
+                    .after(VisibilitySystems::CheckVisibility),
+            );
+    }
+}
+
+/// The configuration of [`Light2dGizmosPlugin`].
+///
+/// Use [`GizmoConfigStore`] to change the line width or disable the gizmos.
+#[derive(Reflect, GizmoConfigGroup)]
+pub struct Light2dGizmoConfigGroup {
+    /// The color of occluders which cast shadows.
+    pub occluder_color: Color,
+    /// The color of occluders which don't cast shadows, because they are disabled, fully
+    /// transparent, or culled.
+    pub inactive_occluder_color: Color,
+    /// The color of lights.
+    pub light_color: Color,
+    /// The color of lights which don't emit light, because they are disabled or hidden.
+    pub inactive_light_color: Color,
+    /// Whether lights which don't emit light are drawn.
+    pub draw_inactive_lights: bool,
+    /// Whether the radius of each [`PointLight2d`] is drawn.
+    pub draw_point_lights: bool,
+}
+
+impl Default for Light2dGizmoConfigGroup {
+    fn default() -> Self {
+        Self {
+            occluder_color: LIME.into(),
+            inactive_occluder_color: Color::from(LIME).with_alpha(0.25),
+            light_color: YELLOW.into(),
+            inactive_light_color: Color::from(YELLOW).with_alpha(0.25),
+            draw_inactive_lights: true,
+            draw_point_lights: true,
+        }
+    }
+}
+
+fn draw_occluder_gizmos(
+    mut gizmos: Gizmos<Light2dGizmoConfigGroup>,
+    config_store: Res<GizmoConfigStore>,
+    images: Res<Assets<Image>>,
+    occluders: Query<(&LightOccluder2d, &GlobalTransform, &ViewVisibility)>,
+) {
+    let (_, config) = config_store.config::<Light2dGizmoConfigGroup>();
+
+    for (occluder, global_transform, view_visibility) in &occluders {
+        let active = occluder.enabled && occluder.opacity > 0.0 && view_visibility.get();
+        let color = if active {
+            config.occluder_color
+        } else {
+            config.inactive_occluder_color
+        };
+
+        let shapes = iter::once((&occluder.shape, occluder.offset)).chain(
+            occluder.compound_shapes.iter().map(|compound_shape| {
+                (
+                    &compound_shape.shape,
+                    occluder.offset + compound_shape.offset,
+                )
+            }),
+        );
+
+        for (shape, offset) in shapes {
+            draw_shape(&mut gizmos, shape, offset, global_transform, &images, color);
+        }
+    }
+}
+
+fn draw_point_light_gizmos(
+    mut gizmos: Gizmos<Light2dGizmoConfigGroup>,
+    config_store: Res<GizmoConfigStore>,
+    point_lights: Query<(
+        &PointLight2d,
+        &GlobalTransform,
+        &ViewVisibility,
+        Option<&LightModulation2d>,
+    )>,
+) {
+    // The number of rings the dot at a point light's center is filled with.
+    const DOT_RINGS: u32 = 4;
+
+    let (_, config) = config_store.config::<Light2dGizmoConfigGroup>();
+    if !config.draw_point_lights {
+        return;
+    }
+
+    for (point_light, global_transform, view_visibility, modulation) in &point_lights {
+        let active = point_light.enabled && view_visibility.get();
+        if !active && !config.draw_inactive_lights {
+            continue;
+        }
+        // Inactive lights are dimmed by as much as the configured inactive light color.
+        let color = if active {
+            point_light.color
+        } else {
+            point_light
+                .color
+                .with_alpha(point_light.color.alpha() * config.inactive_light_color.alpha())
+        };
+
+        let radius = point_light.radius * point_light.radius_scale(global_transform);
+        let ellipticity = point_light.ellipticity.abs().max(Vec2::splat(f32::EPSILON));
+        let rotation = (global_transform.rotation() * Vec3::X)
+            .xy()
+            .normalize_or(Vec2::X);
+        // Lights are drawn where they're rendered, including any offset from their modulation.
+        let offset = modulation.map_or(Vec2::ZERO, |modulation| modulation.offset);
+        let isometry = Isometry2d::new(
+            global_transform.translation().xy() + offset,
+            Rot2::from_sin_cos(rotation.y, rotation.x),
+        );
+        gizmos.ellipse_2d(isometry, ellipticity * radius, color);
+
+        // Gizmos can't be filled, so the dot is drawn as concentric rings.
+        let dot_radius = radius * 0.05;
+        for ring in 1..=DOT_RINGS {
+            gizmos.circle_2d(isometry, dot_radius * ring as f32 / DOT_RINGS as f32, color);
+        }
+    }
+}
+
+fn draw_spot_light_gizmos(
+    mut gizmos: Gizmos<Light2dGizmoConfigGroup>,
+    config_store: Res<GizmoConfigStore>,
+    spot_lights: Query<(
+        &SpotLight2d,
+        &GlobalTransform,
+        &ViewVisibility,
+        Option<&LightModulation2d>,
+    )>,
+) {
+    let (_, config) = config_store.config::<Light2dGizmoConfigGroup>();
+
+    for (spot_light, global_transform, view_visibility, modulation) in &spot_lights {
+        let Some(color) = light_color(config, spot_light.enabled && view_visibility.get()) else {
+            continue;
+        };
+        // The inner cone is drawn fainter, so it can be told apart from the outer cone.
+        let inner_color = color.with_alpha(color.alpha() * 0.5);
+
+        let radius_scale = spot_light.radius_scale(global_transform);
+        let radius = spot_light.radius * radius_scale;
+        let half_width = spot_light.source_width.max(0.0) * radius_scale * 0.5;
+        let center = global_transform.translation().xy()
+            + modulation.map_or(Vec2::ZERO, |modulation| modulation.offset);
+        let axis = spot_light.world_direction(global_transform);
+        let (_, inner_angle, outer_angle) = spot_light.normalized_angles();
+        let inner_half_angle = inner_angle.to_radians();
+        let outer_half_angle = outer_angle.to_radians();
+        let bar = axis.perp();
+
+        // Light is emitted from the nearest point on the source segment, so each side of the
+        // cone fans out from the end of the segment on that side.
+        for side in [-1.0, 1.0] {
+            let end = center + bar * half_width * side;
+            let outer_edge = Vec2::from_angle(outer_half_angle * side).rotate(axis);
+            let inner_edge = Vec2::from_angle(inner_half_angle * side).rotate(axis);
+            gizmos.line_2d(end, end + outer_edge * radius, color);
+            gizmos.line_2d(end, end + inner_edge * radius, inner_color);
+            draw_arc(
+                &mut gizmos,
+                end,
+                radius,
+                axis.to_angle(),
+                outer_half_angle * side,
+                color,
+            );
+        }
+
+        if half_width > 0.0 {
+            let start = center - bar * half_width;
+            let end = center + bar * half_width;
+            gizmos.line_2d(start, end, color);
+            gizmos.line_2d(start + axis * radius, end + axis * radius, color);
+        }
+    }
+}
+
+/// Returns the color a light is drawn in, or `None` if it shouldn't be drawn.
+fn light_color(config: &Light2dGizmoConfigGroup, active: bool) -> Option<Color> {
+    if active {
+        Some(config.light_color)
+    } else if config.draw_inactive_lights {
+        Some(config.inactive_light_color)
+    } else {
+        None
+    }
+}
+
+/// Draws an arc around `center`, from `start_angle` through `sweep` radians.
+fn draw_arc(
+    gizmos: &mut Gizmos<Light2dGizmoConfigGroup>,
+    center: Vec2,
+    radius: f32,
+    start_angle: f32,
+    sweep: f32,
+    color: Color,
+) {
+    const ARC_SEGMENTS: u32 = 16;
+
+    let points = (0..=ARC_SEGMENTS).map(|i| {
+        let angle = start_angle + sweep * i as f32 / ARC_SEGMENTS as f32;
+        center + Vec2::from_angle(angle) * radius
+    });
+    gizmos.linestrip_2d(points, color);
+}
+
+fn draw_shape(
+    gizmos: &mut Gizmos<Light2dGizmoConfigGroup>,
+    shape: &LightOccluder2dShape,
+    offset: Vec2,
+    global_transform: &GlobalTransform,
+    images: &Assets<Image>,
+    color: Color,
+) {
+    let to_world = |point: Vec2| {
+        global_transform
+            .transform_point((point + offset).extend(0.0))
+            .xy()
+    };
+    let rotation = (global_transform.rotation() * Vec3::X)
+        .xy()
+        .normalize_or(Vec2::X);
+    let isometry = Isometry2d::new(
+        to_world(Vec2::ZERO),
+        Rot2::from_sin_cos(rotation.y, rotation.x),
+    );
+    let scale = global_transform.scale().xy().abs();
+
+    match shape {
+        LightOccluder2dShape::Rectangle { half_size } => {
+            gizmos.rect_2d(isometry, *half_size * scale * 2.0, color);
+        }
+        LightOccluder2dShape::RoundedRectangle {
+            half_size,
+            corner_radius,
+        } => {
+            let half_size = *half_size * scale;
+            let corner_radius =
+                (corner_radius * scale.min_element()).clamp(0.0, half_size.min_element().max(0.0));
+            gizmos
+                .rounded_rect_2d(isometry, half_size * 2.0, color)
+                .corner_radius(corner_radius);
+        }
+        LightOccluder2dShape::Capsule {
+            half_length,
+            radius,
+        } => {
+            let start = to_world(Vec2::new(0.0, -half_length));
+            let end = to_world(Vec2::new(0.0, *half_length));
+            let direction = (end - start).normalize_or(Vec2::Y);
+            let capsule = Capsule2d::new(radius * scale.x, start.distance(end));
+            gizmos.primitive_2d(
+                &capsule,
+                Isometry2d::new(
+                    (start + end) * 0.5,
+                    Rot2::from_sin_cos(-direction.x, direction.y),
+                ),
+                color,
+            );
+        }
+        LightOccluder2dShape::Ellipse { half_size } => {
+            gizmos.ellipse_2d(isometry, *half_size * scale, color);
+        }
+        LightOccluder2dShape::Annulus {
+            inner_radius,
+            outer_radius,
+        } => {
+            for radius in [inner_radius, outer_radius] {
+                gizmos.circle_2d(isometry, radius.max(0.0) * scale.max_element(), color);
+            }
+        }
+        LightOccluder2dShape::Triangle { a, b, c } => {
+            draw_outline(gizmos, [*a, *b, *c].map(to_world), color);
+        }
+        LightOccluder2dShape::ConvexPolygon(vertices) | LightOccluder2dShape::Polygon(vertices) => {
+            draw_outline(gizmos, vertices.iter().copied().map(to_world), color);
+        }
+        LightOccluder2dShape::Segment { start, end } => {
+            gizmos.line_2d(to_world(*start), to_world(*end), color);
+        }
+        LightOccluder2dShape::AlphaMask { image, .. } => {
+            // The mask's silhouette isn't available outside of rendering, so its bounds are
+            // drawn instead.
+            if let Some(image) = images.get(image) {
+                gizmos.rect_2d(isometry, image.size().as_vec2() * scale, color);
+            }
+        }
+        LightOccluder2dShape::Sdf { world_size, .. } => {
+            // The distance field's surface isn't available outside of rendering either.
+            gizmos.rect_2d(isometry, world_size.abs() * scale, color);
+        }
+    }
+}
+
+/// Draws a closed outline through the given vertices.
+fn draw_outline(
+    gizmos: &mut Gizmos<Light2dGizmoConfigGroup>,
+    vertices: impl IntoIterator<Item = Vec2, IntoIter: Clone>,
+    color: Color,
+) {
+    let vertices = vertices.into_iter();
+    gizmos.linestrip_2d(vertices.clone().chain(vertices.take(1)), color);
+}
@@ -2,16 +2,64 @@
 #![doc = include_str!("../README.md")]
 #![expect(deprecated)]
 
+#[cfg(feature = "animation")]
+pub mod animation;
+pub mod bake;
+#[cfg(any(feature = "avian2d", feature = "rapier2d"))]
+pub mod collider;
+mod cpu_light;
+pub mod day_night;
+pub mod fade;
+pub mod flash;
+#[cfg(feature = "gizmos")]
+pub mod gizmos;
 pub mod light;
+pub mod lod;
+pub mod modulation;
 pub mod occluder;
 pub mod plugin;
+pub mod probe;
 mod render;
+pub mod target;
+#[cfg(test)]
+mod test_utils;
+pub mod trail;
+pub mod vision;
+pub mod zone;
 
 /// A module which exports commonly used dependencies.
 pub mod prelude {
+    pub use crate::bake::Light2dBaker;
+    pub use crate::day_night::{
+        DayNightCycle2d, DayNightCycle2dPlugin, DayNightKeyframe2d, DayNightSun2d, TimeOfDay,
+    };
+    pub use crate::fade::{FadingOut2d, LightFade2d, LightFadeCommandsExt};
+    pub use crate::flash::AmbientFlash2d;
     pub use crate::light::{
-        AmbientLight2d, Light2d, PointLight2d, PointLight2dBundle, SpotLight2d,
+        AmbientGradient, AmbientGradientSpace, AmbientLight2d, AmbientOcclusion2d, AreaLight2d,
+        BakedLightmap2d, BeamAnchor, BeamLight2d, CapsuleLight2d, DirectionalLight2d,
+        EmissiveSprite2d, Exposure2d, FalloffCurve, GlobalLight2d, Light2d, Light2dPreset,
+        LightBlend2d, LightIntensityUnit, LineLight2d, PointLight2d, PointLight2dBundle,
+        PolygonLight2d, PolylineLight2d, RingLight2d, ScreenLight2d, SpotLight2d, StaticLight2d,
+        VolumetricLightQuality, color_from_temperature, lerp_color, lerp_color_perceptual,
+    };
+    pub use crate::lod::{Light2dLodSettings, LightLod2d};
+    pub use crate::modulation::{
+        Light2dAnimationTime, LightFlicker2d, LightModulation2d, LightNoise2d, LightOscillator2d,
+        LightPulse2d, LightStrobe2d, PulseWaveform,
+    };
+    pub use crate::occluder::{
+        LightOccluder2d, LightOccluder2dBundle, LightOccluder2dCompoundShape, LightOccluder2dShape,
+        OccludeFromMesh2d, OccluderCulling, OccluderFrames, OccluderLightFilter, OcclusionLayers,
+        StaticLightOccluder2d, tilemap::TilemapOccluder,
     };
-    pub use crate::occluder::{LightOccluder2d, LightOccluder2dBundle, LightOccluder2dShape};
     pub use crate::plugin::Light2dPlugin;
+    pub use crate::probe::{LightProbe2d, SampledLight2d};
+    pub use crate::target::LightTarget2d;
+    pub use crate::trail::TrailLight2d;
+    pub use crate::vision::{VisionMask2d, VisionSource2d};
+    pub use crate::zone::{AmbientLightZone2d, AmbientLightZoneShape};
+
+    #[cfg(any(feature = "avian2d", feature = "rapier2d"))]
+    pub use crate::collider::OccluderFromCollider;
 }
@@ -1,16 +1,24 @@
 //! A module which contains lighting components.
 
+use core::ops::Range;
+
 use bevy::{
+    asset::Handle,
     camera::visibility,
     camera::visibility::{InheritedVisibility, ViewVisibility, Visibility, VisibilityClass},
-    color::Color,
-    ecs::{bundle::Bundle, component::Component},
-    prelude::{ReflectComponent, ReflectDefault},
+    color::{Color, Mix, Oklaba},
+    ecs::{bundle::Bundle, component::Component, resource::Resource},
+    image::Image,
+    math::{FloatExt, Rect, Vec2, Vec3, Vec3Swizzles},
+    platform::collections::HashMap,
+    prelude::{ReflectComponent, ReflectDefault, ReflectResource},
     reflect::Reflect,
-    render::sync_world::SyncToRenderWorld,
+    render::{extract_resource::ExtractResource, sync_world::SyncToRenderWorld},
     transform::components::{GlobalTransform, Transform},
 };
 
+use crate::modulation::LightNoise2d;
+
 /// A "marker" component to be used with a `Camera2d`.
 ///
 /// 2D lighting effects will only run for cameras that have this component.
@@ -18,6 +26,139 @@ use bevy::{
 pub struct Light2d {
     /// The ambight light to apply to the scene.
     pub ambient_light: AmbientLight2d,
+    /// The radius (in pixels) over which light from [`EmissiveSprite2d`]s spills onto its
+    /// surroundings, like bloom. A radius of `0` keeps emitted light within each sprite.
+    pub emissive_spill: u32,
+    /// The exposure of the scene's lighting, which brightens or darkens every light at once.
+    pub exposure: Exposure2d,
+    /// How the light of overlapping lights is combined, for every light in the camera's view.
+    ///
+    /// Lights don't have a blend mode of their own, so every light in a view is blended the same
+    /// way.
+    pub blend: LightBlend2d,
+    /// The brightest the scene's lighting can be, as a linear luminance.
+    ///
+    /// The accumulated light of each pixel, after exposure, is scaled down to this luminance,
+    /// keeping its hue, so overlapping bright lights can't blow out effects like bloom. `None`
+    /// leaves the lighting unclamped.
+    pub max_brightness: Option<f32>,
+    /// Darkens the ambient light near the surfaces of occluders, so that corners and crevices
+    /// between them don't look flat. `None` skips ambient occlusion entirely.
+    pub ambient_occlusion: Option<AmbientOcclusion2d>,
+}
+
+/// Contact ambient occlusion, which darkens a camera's ambient light near occluders.
+///
+/// Occlusion is found from the same distance field occluders cast shadows with, so it's darkest
+/// where several occluders meet, like the inside corner of a room or between stacked crates.
+/// Only the ambient light is darkened, including ambient light zones and the ambient map,
+/// while light from lights and baked lightmaps is left untouched. The insides of occluders
+/// aren't darkened, and every occluder contributes, whatever its occlusion or render layers.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+pub struct AmbientOcclusion2d {
+    /// The distance (in world units) from the surface of an occluder within which the ambient
+    /// light is darkened.
+    pub radius: f32,
+    /// How much the ambient light is darkened, from `0.0` for not at all to `1.0` for black in
+    /// the tightest corners.
+    pub strength: f32,
+}
+
+impl Default for AmbientOcclusion2d {
+    /// Returns an [`AmbientOcclusion2d`] which darkens the ambient light by up to half, within
+    /// 16 units of occluders.
+    fn default() -> Self {
+        Self {
+            radius: 16.0,
+            strength: 0.5,
+        }
+    }
+}
+
+/// How the light of overlapping lights is combined, on top of the ambient light.
+///
+/// The blend mode is chosen per camera, with [`Light2d::blend`], rather than per light, and
+/// applies to every dynamic light in the camera's view. Light baked into a [`BakedLightmap2d`]
+/// is always added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum LightBlend2d {
+    /// The light of overlapping lights is added together, so many overlapping lights quickly
+    /// brighten to white.
+    #[default]
+    Add,
+    /// Overlapping lights take the brightest of their light, for each color channel, so
+    /// overlapping lights are never brighter than the brightest of them.
+    Max,
+    /// Overlapping lights are combined like Photoshop's screen blend mode, brightening each other
+    /// less the brighter they already are, so they approach white without exceeding it.
+    Screen,
+}
+
+/// The exposure of a camera's lighting, in stops.
+///
+/// Each stop doubles the brightness of the scene's lighting, including its ambient light, so
+/// scenes can be balanced without changing the intensity of each light. Exposure is most useful
+/// with lights measured in [`LightIntensityUnit::Lumens`], whose intensities are often far from
+/// `1.0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Default)]
+pub struct Exposure2d {
+    /// The number of stops the lighting is brightened by. Negative stops darken the lighting,
+    /// and `0.0` leaves it unchanged.
+    pub stops: f32,
+}
+
+impl Exposure2d {
+    /// Returns the factor the scene's lighting is multiplied by.
+    pub fn multiplier(&self) -> f32 {
+        self.stops.exp2()
+    }
+}
+
+/// The unit a light's intensity is measured in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum LightIntensityUnit {
+    /// The intensity multiplies the light's attenuation directly.
+    #[default]
+    Arbitrary,
+    /// The intensity is the light's luminous flux, in lumens, which is spread across the area
+    /// the light covers.
+    ///
+    /// Lumens are converted with [`LightIntensityUnit::to_intensity`], so that lights of the
+    /// same flux give off the same total amount of light regardless of their radius.
+    Lumens,
+}
+
+impl LightIntensityUnit {
+    /// The luminous flux, in lumens, of a light with a radius of [`Self::REFERENCE_RADIUS`]
+    /// which has an intensity of `1.0`. This is roughly a 60W incandescent bulb.
+    pub const REFERENCE_LUMENS: f32 = 800.0;
+
+    /// The radius of a light of [`Self::REFERENCE_LUMENS`] which has an intensity of `1.0`.
+    pub const REFERENCE_RADIUS: f32 = 100.0;
+
+    /// Returns the intensity, in the light map pass's arbitrary units, of a light of
+    /// `intensity` in this unit and `radius`.
+    ///
+    /// Lumens are spread across the area of the light, which grows with the square of its
+    /// radius, so a light with twice the radius of another of the same flux is a quarter as
+    /// intense:
+    ///
+    /// `intensity = (lumens / REFERENCE_LUMENS) * (REFERENCE_RADIUS / radius)²`
+    pub fn to_intensity(self, intensity: f32, radius: f32) -> f32 {
+        match self {
+            Self::Arbitrary => intensity,
+            Self::Lumens => {
+                if radius <= 0.0 {
+                    return 0.0;
+                }
+                intensity / Self::REFERENCE_LUMENS * (Self::REFERENCE_RADIUS / radius).powi(2)
+            }
+        }
+    }
 }
 
 /// A light that provides illumination in all directions.
@@ -31,7 +172,21 @@ pub struct Light2d {
 ///
 /// For more information on the formula used, see the blog post
 /// [A better point light attenutation function](https://lisyarus.github.io/blog/posts/point-light-attenuation.html#section-the-solution)
-/// by [lisyarus](https://lisyarus.github.io/blog/).
+/// by [lisyarus](https://lisyarus.github.io/blog/). The light's `attenuation_exponent` replaces
+/// the exponent of `2.0` in the formula's numerator.
+///
+/// # Render layers
+///
+/// A light with `RenderLayers` only illuminates the views of cameras that share one of its
+/// first 32 layers, while a light without `RenderLayers` illuminates every view.
+///
+/// # Occlusion layers
+///
+/// A light with [`OcclusionLayers`] is only blocked by occluders that share one of its layers,
+/// while a light without them is blocked by every occluder. For example, a light on a layer
+/// that walls aren't on shines straight through them, like a ghost's vision.
+///
+/// [`OcclusionLayers`]: crate::occluder::OcclusionLayers
 #[derive(Component, Clone, Reflect)]
 #[reflect(Component, Default)]
 #[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
@@ -39,16 +194,118 @@ pub struct Light2d {
 pub struct PointLight2d {
     /// The light's color tint.
     pub color: Color,
+    /// The color of the light at its radius, like the deep orange rim of a white-hot fire.
+    ///
+    /// The light's color shifts from `color` at its center to `edge_color` at its radius,
+    /// following the light's falloff curve, so the color changes fastest where the light fades
+    /// fastest. `None` keeps the light a single color.
+    pub edge_color: Option<Color>,
     /// The intensity of the light. The light's attenutation is multiplied by this value.
     /// The higher the intensity, the brighter the light.
+    ///
+    /// A negative intensity subtracts light instead, darkening its surroundings, even below the
+    /// ambient light. The scene's lighting is never darker than black.
     pub intensity: f32,
+    /// The unit the light's intensity is measured in.
+    pub intensity_unit: LightIntensityUnit,
     /// The radius of the light. Illumination will only occur within the light's radius.
     pub radius: f32,
+    /// The radius of the light's hotspot, within which the light is at its full intensity.
+    /// Illumination only begins to deteriorate beyond this radius. Inner radii larger than the
+    /// light's radius are clamped to it.
+    pub inner_radius: f32,
     /// How quickly illumination from the light should deteriorate over distance.
     /// A higher falloff value will result in less illumination at the light's maximum radius.
     pub falloff: f32,
+    /// The shape of the light's attenuation over distance.
+    pub falloff_curve: FalloffCurve,
+    /// The exponent of the light's [`FalloffCurve::InverseSquare`] attenuation, which shapes how
+    /// quickly it fades towards its radius. Exponents above `2.0` concentrate light around the
+    /// center, for tight bulbs, while those below `2.0` spread it towards the radius, for haze.
+    /// Other falloff curves ignore it.
+    pub attenuation_exponent: f32,
+    /// The brightest the light's own light can be, as a linear luminance.
+    ///
+    /// The light's contribution to each pixel is scaled down to this luminance, keeping its
+    /// hue, before it's combined with other lights. `None` leaves the light unclamped.
+    pub max_brightness: Option<f32>,
+    /// Whether the light emits light.
+    ///
+    /// Unlike hiding the light's entity, disabling the light doesn't affect anything else on the
+    /// entity or its children, such as the sprite of a lamp's bulb. Disabled lights aren't
+    /// rendered, so they don't count towards the limit of lights in WebGL2.
+    pub enabled: bool,
     /// Whether the light should cast shadows.
     pub cast_shadows: bool,
+    /// How dark the light's shadows are, from `0.0`, where occluders don't block the light at
+    /// all, to `1.0`, where they block it entirely. Softer shadows suit secondary lights, such
+    /// as fill lights. This has no effect unless `cast_shadows` is `true`.
+    pub shadow_strength: f32,
+    /// How far (in world units) the edges of the light's shadows are blurred over.
+    ///
+    /// A softness of `0.0` casts hard shadows. Unlike the softness of an occluder, this only
+    /// affects the shadows of this light, so a moon can cast dreamy shadows while a lamp
+    /// beside it stays sharp. Soft shadows never let light through occluders. This has no
+    /// effect unless `cast_shadows` is `true`.
+    pub shadow_softness: f32,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a torch held by a
+    /// character, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+    /// An image that patterns the light, like a window frame or a disco ball.
+    ///
+    /// The light's color is multiplied by the cookie's color. At a `cookie_scale` of `1.0`,
+    /// the cookie covers the light's diameter, and no light is emitted outside of it. Lights
+    /// whose cookie hasn't loaded are drawn without it.
+    pub cookie: Option<Handle<Image>>,
+    /// The size of the cookie relative to the light's diameter.
+    pub cookie_scale: f32,
+    /// The angle (in degrees) the cookie is rotated by, in addition to the rotation of the
+    /// light's transform.
+    pub cookie_rotation: f32,
+    /// How quickly (in degrees per second) the cookie spins, like a rotating gobo.
+    pub cookie_rotation_speed: f32,
+    /// The offset of the cookie's image, as a fraction of its size.
+    ///
+    /// Offset cookies wrap around, so the image repeats across the light.
+    pub cookie_offset: Vec2,
+    /// How quickly (as a fraction of the cookie's size per second) the cookie's image scrolls,
+    /// like caustics across an underwater scene.
+    ///
+    /// The cookie's rotation and scrolling are driven by the [`Light2dAnimationTime`], so they
+    /// stop while it, or the app's virtual time, is paused.
+    ///
+    /// [`Light2dAnimationTime`]: crate::modulation::Light2dAnimationTime
+    pub cookie_scroll_speed: Vec2,
+    /// A texture which modulates the light across the area it lights, like the flickering glow
+    /// of a campfire.
+    pub modulation: Option<LightNoise2d>,
+    /// The radius of the light's source, which softens its shadows.
+    ///
+    /// Real lamps aren't points, so their shadows have a penumbra, which widens with the
+    /// distance from the occluder casting them. A source radius of `0.0` casts hard shadows.
+    /// This has no effect unless `cast_shadows` is `true`.
+    pub source_radius: f32,
+    /// How far the light reaches along its local x and y axes, relative to its radius.
+    ///
+    /// Squashing the light along one axis stretches its pool of light into an ellipse, which is
+    /// useful for isometric and top-down perspectives. The ellipse rotates with the light's
+    /// transform, and shadows are still cast from the light's center. An ellipticity of
+    /// `Vec2::ONE` is a circle.
+    pub ellipticity: Vec2,
+    /// The height (in world units) of the light above the 2D plane, which gives the light a
+    /// direction in 3D, such as for lighting normal maps.
+    pub height: f32,
+    /// Whether the light's height is read from the z translation of its transform, rather than
+    /// from `height`.
+    pub height_from_z: bool,
+    /// Whether the light's radius, inner radius, source radius and shadow softness are
+    /// multiplied by the scale of its transform, so that the light shrinks and grows with its
+    /// entity and its ancestors, like the glow of a shrinking fireball.
+    ///
+    /// The larger of the transform's x and y scales is used.
+    pub radius_from_scale: bool,
 }
 
 impl Default for PointLight2d {
@@ -56,21 +313,120 @@ impl Default for PointLight2d {
     fn default() -> Self {
         Self {
             color: Color::WHITE,
+            edge_color: None,
             intensity: 1.0,
+            intensity_unit: LightIntensityUnit::Arbitrary,
             radius: 0.5,
+            inner_radius: 0.0,
             falloff: 0.0,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            max_brightness: None,
+            enabled: true,
             cast_shadows: false,
+            shadow_strength: 1.0,
+            shadow_softness: 0.0,
+            ignore_self_occlusion: false,
+            cookie: None,
+            cookie_scale: 1.0,
+            cookie_rotation: 0.0,
+            cookie_rotation_speed: 0.0,
+            cookie_offset: Vec2::ZERO,
+            cookie_scroll_speed: Vec2::ZERO,
+            modulation: None,
+            source_radius: 0.0,
+            ellipticity: Vec2::ONE,
+            height: DEFAULT_LIGHT_HEIGHT,
+            height_from_z: false,
+            radius_from_scale: false,
         }
     }
 }
 
+impl PointLight2d {
+    /// Returns the light with the color of a black body at a temperature of `kelvin`.
+    ///
+    /// See [`color_from_temperature`] for more information.
+    pub fn with_temperature(mut self, kelvin: f32) -> Self {
+        self.color = color_from_temperature(kelvin);
+        self
+    }
+
+    /// Returns the height of the light above the 2D plane, given its entity's transform.
+    pub fn world_height(&self, global_transform: &GlobalTransform) -> f32 {
+        light_height(self.height, self.height_from_z, global_transform)
+    }
+
+    /// Returns the factor the light's radii are multiplied by, given its entity's transform.
+    pub fn radius_scale(&self, global_transform: &GlobalTransform) -> f32 {
+        radius_scale(self.radius_from_scale, global_transform)
+    }
+}
+
+/// The height above the 2D plane of lights that don't specify one.
+pub(crate) const DEFAULT_LIGHT_HEIGHT: f32 = 50.0;
+
+// Lights scaled to nothing, or by a negative scale, are shrunk to this factor instead, so
+// that they're never inverted or NaN.
+const MIN_RADIUS_SCALE: f32 = 1e-4;
+
+// Spot lights with an edge exponent of zero or less use this exponent instead, so that their
+// beam doesn't fill the whole circle.
+const MIN_EDGE_EXPONENT: f32 = 1e-4;
+
+fn radius_scale(radius_from_scale: bool, global_transform: &GlobalTransform) -> f32 {
+    if !radius_from_scale {
+        return 1.0;
+    }
+    let scale = global_transform.scale().xy().max_element();
+    // `max` also replaces NaN scales.
+    scale.max(MIN_RADIUS_SCALE)
+}
+
+fn light_height(height: f32, height_from_z: bool, global_transform: &GlobalTransform) -> f32 {
+    if height_from_z {
+        global_transform.translation().z
+    } else {
+        height
+    }
+}
+
+/// The shape of a light's attenuation, from full intensity at its center to nothing at its
+/// radius.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Default)]
+pub enum FalloffCurve {
+    /// A curve resembling the inverse square law, shaped by the light's `falloff`. See
+    /// [`PointLight2d`] for more information.
+    #[default]
+    InverseSquare,
+    /// Illumination decreases linearly with distance.
+    Linear,
+    /// Illumination eases out from the center and into the radius, giving a softer edge than a
+    /// linear curve.
+    Smoothstep,
+    /// Illumination decreases with the distance to the radius raised to the given exponent.
+    /// Exponents above `1.0` concentrate light around the center, while those below `1.0`
+    /// spread it towards the radius.
+    Exponent(f32),
+}
+
 /// A light that provides illumination in a beam towards a given direction.
 ///
 /// The parameters for spot lights function identically to point lights, but with a few additional parameters included.
 /// The direction parameter defines the angle (in degrees) that the light is pointing towards.
-/// The inner and outer angle parameters of the light are used to define the tapering of the light's beam.
+/// The inner and outer angle parameters of the light are used to define the tapering of the light's beam:
+/// they are half angles (in degrees) measured from the direction, so a light with an outer angle of
+/// `45.0` lights a 90 degree wide cone. The light is at full strength within the inner angle, and fades
+/// out towards the outer angle.
 /// The source width parameter defines the width of the segment from where the light begins to emit.
 ///
+/// Like a [`PointLight2d`], a spot light with `RenderLayers` only illuminates the views of
+/// cameras that share one of its layers, and a spot light with [`OcclusionLayers`] is only
+/// blocked by occluders that share one of its layers.
+///
+/// [`OcclusionLayers`]: crate::occluder::OcclusionLayers
+///
 #[derive(Component, Clone, Reflect)]
 #[reflect(Component, Default)]
 #[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
@@ -78,42 +434,784 @@ impl Default for PointLight2d {
 pub struct SpotLight2d {
     /// The light's color tint.
     pub color: Color,
+    /// The color of the light at its radius.
+    ///
+    /// Like a [`PointLight2d`]'s edge color, the light's color shifts from `color` near the
+    /// light to `edge_color` at its radius, following the light's falloff curve. `None` keeps
+    /// the light a single color.
+    pub edge_color: Option<Color>,
     /// The intensity of the light. The light's attenutation is multiplied by this value.
     /// The higher the intensity, the brighter the light.
+    ///
+    /// A negative intensity subtracts light instead, darkening its surroundings, even below the
+    /// ambient light. The scene's lighting is never darker than black.
     pub intensity: f32,
+    /// The unit the light's intensity is measured in.
+    pub intensity_unit: LightIntensityUnit,
     /// The radius of the light. Illumination will only occur within the light's radius.
     pub radius: f32,
+    /// The radius of the light's hotspot, within which the light is at its full intensity.
+    /// Illumination only begins to deteriorate beyond this radius. Inner radii larger than the
+    /// light's radius are clamped to it.
+    pub inner_radius: f32,
     /// How quickly illumination from the light should deteriorate over distance.
     /// A higher falloff value will result in less illumination at the light's maximum radius.
     pub falloff: f32,
+    /// The shape of the light's attenuation over distance.
+    pub falloff_curve: FalloffCurve,
+    /// The exponent of the light's [`FalloffCurve::InverseSquare`] attenuation, which shapes how
+    /// quickly it fades towards its radius. Exponents above `2.0` concentrate light around the
+    /// center, for tight bulbs, while those below `2.0` spread it towards the radius, for haze.
+    /// Other falloff curves ignore it.
+    pub attenuation_exponent: f32,
     /// The given angle direction (in degrees) of the light.
     pub direction: f32,
-    /// The inner angle of the light.
+    /// Whether `direction` is relative to the rotation of the light's transform, so that the
+    /// beam turns with the entity and its ancestors, like a flashlight held by a character.
+    ///
+    /// When `true`, a `direction` of `0.0` points along the entity's local x axis.
+    pub direction_from_transform: bool,
+    /// The half angle (in degrees) from the light's direction within which the light is at
+    /// full strength.
+    ///
+    /// This is clamped between `0.0` and `180.0`, and must not be larger than `outer_angle`.
     pub inner_angle: f32,
-    /// The outer angle of the light.
+    /// The half angle (in degrees) from the light's direction at which the light fades out
+    /// completely.
+    ///
+    /// This is clamped between `0.0` and `180.0`. An `outer_angle` smaller than the
+    /// `inner_angle` is swapped with it.
     pub outer_angle: f32,
+    /// The exponent the fade from the inner angle to the outer angle is raised to, which shapes
+    /// the edge of the beam. Exponents above `1.0` give the hard edge of a stage spotlight, while
+    /// those below `1.0` give the soft edge of a car headlight. Exponents of `0.0` or less are
+    /// clamped to a small positive value.
+    pub edge_exponent: f32,
     /// The width of the segment from where the light begins to emit.
     pub source_width: f32,
+    /// The brightest the light's own light can be, as a linear luminance.
+    ///
+    /// The light's contribution to each pixel is scaled down to this luminance, keeping its
+    /// hue, before it's combined with other lights. `None` leaves the light unclamped.
+    pub max_brightness: Option<f32>,
+    /// Whether the light emits light.
+    ///
+    /// Unlike hiding the light's entity, disabling the light doesn't affect anything else on the
+    /// entity or its children, such as the sprite of a lamp's bulb. Disabled lights aren't
+    /// rendered, so they don't count towards the limit of lights in WebGL2.
+    pub enabled: bool,
     /// Whether the light should cast shadows.
     pub cast_shadows: bool,
+    /// How dark the light's shadows are, from `0.0`, where occluders don't block the light at
+    /// all, to `1.0`, where they block it entirely. Softer shadows suit secondary lights, such
+    /// as fill lights. This has no effect unless `cast_shadows` is `true`.
+    pub shadow_strength: f32,
+    /// How far (in world units) the edges of the light's shadows are blurred over.
+    ///
+    /// A softness of `0.0` casts hard shadows. Unlike the softness of an occluder, this only
+    /// affects the shadows of this light, so a moon can cast dreamy shadows while a lamp
+    /// beside it stays sharp. Soft shadows never let light through occluders. This has no
+    /// effect unless `cast_shadows` is `true`.
+    pub shadow_softness: f32,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a torch held by a
+    /// character, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+    /// An image projected by the light, like a gobo in front of a stage light or a window
+    /// frame casting its pattern across a floor.
+    ///
+    /// The image is stretched across the light's cone, with its left and right edges at the
+    /// outer angle, and its top edge at the light. The light's color is multiplied by the
+    /// image's color. Lights whose projection texture hasn't loaded are drawn without it.
+    pub projection_texture: Option<Handle<Image>>,
+    /// A texture which modulates the light across the area it lights, like caustics cast by
+    /// light through water.
+    pub modulation: Option<LightNoise2d>,
+    /// Whether the light's beam glows, like a shaft of light through dust or fog.
+    ///
+    /// Light scattered by the beam is added on top of the scene rather than lighting it, so
+    /// the beam is visible even against black. Occluders carve streaks out of the beam where
+    /// they shadow it. The quality of the effect is controlled by [`VolumetricLightQuality`].
+    pub volumetric: bool,
+    /// The brightness of the light scattered by a volumetric beam, relative to the light's
+    /// intensity. This has no effect unless `volumetric` is `true`.
+    pub volumetric_intensity: f32,
+    /// The height (in world units) of the light above the 2D plane, which gives the light a
+    /// direction in 3D, such as for lighting normal maps.
+    pub height: f32,
+    /// Whether the light's height is read from the z translation of its transform, rather than
+    /// from `height`.
+    pub height_from_z: bool,
+    /// Whether the light's radius, inner radius, source width and shadow softness are
+    /// multiplied by the scale of its transform, so that the light shrinks and grows with its
+    /// entity and its ancestors.
+    ///
+    /// The larger of the transform's x and y scales is used.
+    pub radius_from_scale: bool,
 }
 
 impl Default for SpotLight2d {
     fn default() -> Self {
         Self {
             color: Color::WHITE,
+            edge_color: None,
             intensity: 1.0,
+            intensity_unit: LightIntensityUnit::Arbitrary,
             radius: 0.5,
+            inner_radius: 0.0,
             falloff: 0.0,
-            direction: -90.,
-            inner_angle: -180.,
-            outer_angle: -90.,
+            falloff_curve: FalloffCurve::InverseSquare,
+            attenuation_exponent: 2.0,
+            direction: 0.,
+            direction_from_transform: false,
+            inner_angle: 20.,
+            outer_angle: 40.,
+            edge_exponent: 1.0,
             source_width: 1.,
+            max_brightness: None,
+            enabled: true,
+            cast_shadows: false,
+            shadow_strength: 1.0,
+            shadow_softness: 0.0,
+            ignore_self_occlusion: false,
+            projection_texture: None,
+            modulation: None,
+            volumetric: false,
+            volumetric_intensity: 0.25,
+            height: DEFAULT_LIGHT_HEIGHT,
+            height_from_z: false,
+            radius_from_scale: false,
+        }
+    }
+}
+
+impl SpotLight2d {
+    /// Returns the world space direction of the light's beam, given its entity's transform.
+    pub fn world_direction(&self, global_transform: &GlobalTransform) -> Vec2 {
+        let direction = Vec2::from_angle(self.direction.to_radians());
+        if !self.direction_from_transform {
+            return direction;
+        }
+        let rotation = (global_transform.rotation() * Vec3::X)
+            .xy()
+            .normalize_or(Vec2::X);
+        rotation.rotate(direction)
+    }
+
+    /// Returns the light's direction wrapped between `-180.0` and `180.0`, and its inner and
+    /// outer angles clamped between `0.0` and `180.0`, with the inner angle no larger than the
+    /// outer angle.
+    ///
+    /// These are the values the light is rendered with.
+    pub fn normalized_angles(&self) -> (f32, f32, f32) {
+        let direction = (self.direction + 180.0).rem_euclid(360.0) - 180.0;
+        // `max` and `min` also replace NaN angles with `0.0`.
+        let inner_angle = self.inner_angle.max(0.0).min(180.0);
+        let outer_angle = self.outer_angle.max(0.0).min(180.0);
+        (
+            direction,
+            inner_angle.min(outer_angle),
+            inner_angle.max(outer_angle),
+        )
+    }
+
+    /// Returns the light's edge exponent, clamped to a small positive value.
+    ///
+    /// This is the value the light is rendered with.
+    pub fn normalized_edge_exponent(&self) -> f32 {
+        // `max` also replaces a NaN exponent.
+        self.edge_exponent.max(MIN_EDGE_EXPONENT)
+    }
+
+    /// Returns the height of the light above the 2D plane, given its entity's transform.
+    pub fn world_height(&self, global_transform: &GlobalTransform) -> f32 {
+        light_height(self.height, self.height_from_z, global_transform)
+    }
+
+    /// Returns the factor the light's radii are multiplied by, given its entity's transform.
+    pub fn radius_scale(&self, global_transform: &GlobalTransform) -> f32 {
+        radius_scale(self.radius_from_scale, global_transform)
+    }
+}
+
+/// Controls the quality of volumetric [`SpotLight2d`]s.
+#[derive(Resource, Clone, Debug, ExtractResource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct VolumetricLightQuality {
+    /// The number of samples taken along each fragment's ray towards a volumetric light.
+    ///
+    /// More samples give smoother streaks at a higher cost, which grows with the number of
+    /// volumetric lights on screen. `0` disables volumetric lighting.
+    pub samples: u32,
+}
+
+impl Default for VolumetricLightQuality {
+    /// Returns a [`VolumetricLightQuality`] of `16` samples.
+    fn default() -> Self {
+        Self { samples: 16 }
+    }
+}
+
+/// A light that emits uniformly along a segment, like a neon tube or a laser fence.
+///
+/// The segment is centered on the entity, and runs along its local x axis, so it's rotated and
+/// scaled with the entity's transform. Illumination is attenuated by the distance to the
+/// nearest point on the segment, in the same way as a [`PointLight2d`]. A line light with a
+/// length of `0.0` is equivalent to a point light.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<LineLight2d>)]
+pub struct LineLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The radius of the light. Illumination will only occur within this distance of the
+    /// light's segment.
+    pub radius: f32,
+    /// How quickly illumination from the light should deteriorate over distance.
+    /// A higher falloff value will result in less illumination at the light's maximum radius.
+    pub falloff: f32,
+    /// The length of the light's segment.
+    pub length: f32,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a torch held by a
+    /// character, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+}
+
+impl Default for LineLight2d {
+    /// Returns a white [`LineLight2d`] with a length of `1.0`.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 0.5,
+            falloff: 0.0,
+            length: 1.0,
+            cast_shadows: false,
+            ignore_self_occlusion: false,
+        }
+    }
+}
+
+/// A light that emits from a capsule, like a fluorescent tube or a lightsaber.
+///
+/// The capsule's core is a segment centered on the entity, running along its local x axis, so
+/// it's rotated and scaled with the entity's transform. Illumination is attenuated by the
+/// distance to the nearest point on the core, so the middle of the capsule is as bright as its
+/// ends, which are rounded. A capsule light with a `half_length` of `0.0` is equivalent to a
+/// [`PointLight2d`].
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<CapsuleLight2d>)]
+pub struct CapsuleLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The radius of the light. Illumination will only occur within this distance of the
+    /// capsule's core.
+    pub radius: f32,
+    /// How quickly illumination from the light should deteriorate over distance.
+    /// A higher falloff value will result in less illumination at the light's maximum radius.
+    pub falloff: f32,
+    /// Half of the length of the capsule's core.
+    pub half_length: f32,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a lightsaber held by
+    /// a character, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+}
+
+impl Default for CapsuleLight2d {
+    /// Returns a white [`CapsuleLight2d`] with a `half_length` of `0.5`.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 0.5,
+            falloff: 0.0,
+            half_length: 0.5,
+            cast_shadows: false,
+            ignore_self_occlusion: false,
+        }
+    }
+}
+
+/// A long, thin light of near-constant brightness along its length, like a laser beam.
+///
+/// The beam runs along the entity's local x axis, so it's rotated and scaled with the entity's
+/// transform, and its `length` can be changed every frame, such as to stop it at the first
+/// occluder it hits. Across the beam, illumination is attenuated by the distance to its core in
+/// the same way as a [`PointLight2d`], reaching nothing at half of its `thickness`.
+///
+/// An optional glow is drawn around the end of the beam, where it hits its target. Use
+/// [`BeamLight2d::endpoints`] to find the beam's ends in world space, such as for raycasts.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<BeamLight2d>)]
+pub struct BeamLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The length of the beam.
+    pub length: f32,
+    /// The thickness of the beam. Illumination will only occur within half of this distance of
+    /// the beam's core.
+    pub thickness: f32,
+    /// How quickly illumination from the beam should deteriorate across its thickness.
+    /// A higher falloff value will result in a sharper beam.
+    pub lateral_falloff: f32,
+    /// The radius of the glow at the end of the beam. A radius of `0.0` disables the glow.
+    pub impact_glow_radius: f32,
+    /// Where the beam is anchored to the entity.
+    pub anchor: BeamAnchor,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a laser fired by a
+    /// turret, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+}
+
+/// Where a [`BeamLight2d`] is anchored to its entity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum BeamAnchor {
+    /// The beam is centered on the entity.
+    #[default]
+    Center,
+    /// The beam starts at the entity, and extends along its local x axis.
+    Start,
+}
+
+impl BeamLight2d {
+    /// Returns the world space start and end of the beam, given its entity's transform.
+    pub fn endpoints(&self, global_transform: &GlobalTransform) -> (Vec2, Vec2) {
+        let length = self.length.max(0.0);
+        let (start, end) = match self.anchor {
+            BeamAnchor::Center => (-length * 0.5, length * 0.5),
+            BeamAnchor::Start => (0.0, length),
+        };
+        let to_world = |x: f32| {
+            global_transform
+                .transform_point(Vec3::new(x, 0.0, 0.0))
+                .xy()
+        };
+        (to_world(start), to_world(end))
+    }
+}
+
+impl Default for BeamLight2d {
+    /// Returns a white [`BeamLight2d`] with a length of `1.0`, centered on its entity.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            length: 1.0,
+            thickness: 1.0,
+            lateral_falloff: 0.0,
+            impact_glow_radius: 0.0,
+            anchor: BeamAnchor::Center,
+            cast_shadows: false,
+            ignore_self_occlusion: false,
+        }
+    }
+}
+
+/// A light that emits from the whole of a rectangle, like a glowing floor panel or a patch of
+/// sunlight from a window.
+///
+/// The rectangle is centered on the entity, and is rotated and scaled with the entity's
+/// transform. Points inside the rectangle receive the light's full intensity, while points
+/// outside of it are attenuated by their distance to its edge, in the same way as a
+/// [`PointLight2d`].
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<AreaLight2d>)]
+pub struct AreaLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// Half of the width and height of the light's rectangle.
+    pub half_size: Vec2,
+    /// The radius of the light. Illumination will only occur within this distance of the
+    /// light's rectangle.
+    pub radius: f32,
+    /// How quickly illumination from the light should deteriorate over distance.
+    /// A higher falloff value will result in less illumination at the light's maximum radius.
+    pub falloff: f32,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a torch held by a
+    /// character, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+}
+
+impl Default for AreaLight2d {
+    /// Returns a 1x1 white [`AreaLight2d`].
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            half_size: Vec2::splat(0.5),
+            radius: 0.5,
+            falloff: 0.0,
+            cast_shadows: false,
+            ignore_self_occlusion: false,
+        }
+    }
+}
+
+/// The maximum number of vertices supported by a [`PolygonLight2d`].
+pub const MAX_POLYGON_LIGHT_VERTICES: usize = 32;
+
+/// A light that emits from the whole of a polygon, like a lava pool or a patch of glowing water.
+///
+/// The polygon's vertices are in local space, so it's moved, rotated and scaled with the
+/// entity's transform. The polygon may be concave, but must not intersect itself. Points inside
+/// the polygon receive the light's full intensity, while points outside of it are attenuated by
+/// their distance to its edge, in the same way as a [`PointLight2d`].
+///
+/// At most [`MAX_POLYGON_LIGHT_VERTICES`] vertices are supported; any vertices beyond that
+/// limit are ignored. Polygons with fewer than three vertices don't emit light.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<PolygonLight2d>)]
+pub struct PolygonLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The vertices of the light's polygon, in local space.
+    pub vertices: Vec<Vec2>,
+    /// The radius of the light. Illumination will only occur within this distance of the
+    /// light's polygon.
+    pub radius: f32,
+    /// How quickly illumination from the light should deteriorate over distance.
+    /// A higher falloff value will result in less illumination at the light's maximum radius.
+    pub falloff: f32,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a torch held by a
+    /// character, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+}
+
+impl Default for PolygonLight2d {
+    /// Returns a white [`PolygonLight2d`] with no vertices.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            vertices: Vec::new(),
+            radius: 0.5,
+            falloff: 0.0,
+            cast_shadows: false,
+            ignore_self_occlusion: false,
+        }
+    }
+}
+
+/// A light that emits along a chain of segments, like a bolt of lightning or an electric arc.
+///
+/// The points are in local space, so the polyline is moved, rotated and scaled with the
+/// entity's transform. Illumination is attenuated by the distance to the nearest segment of
+/// the polyline, in the same way as a [`PointLight2d`]. The points can be replaced every frame,
+/// such as to regenerate a bolt, without any light flickering between segments.
+///
+/// At most [`MAX_POLYGON_LIGHT_VERTICES`] points are supported; any points beyond that limit
+/// are ignored. A polyline with a single point is equivalent to a point light, and a polyline
+/// without points doesn't emit light.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<PolylineLight2d>)]
+pub struct PolylineLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The points of the light's polyline, in local space.
+    pub points: Vec<Vec2>,
+    /// The radius of the light. Illumination will only occur within this distance of the
+    /// light's polyline.
+    pub radius: f32,
+    /// How quickly illumination from the light should deteriorate over distance.
+    /// A higher falloff value will result in less illumination at the light's maximum radius.
+    pub falloff: f32,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a spell cast by a
+    /// character, which would otherwise be shadowed by its caster.
+    pub ignore_self_occlusion: bool,
+}
+
+impl Default for PolylineLight2d {
+    /// Returns a white [`PolylineLight2d`] with no points.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            points: Vec::new(),
+            radius: 0.5,
+            falloff: 0.0,
+            cast_shadows: false,
+            ignore_self_occlusion: false,
+        }
+    }
+}
+
+/// A light fixed to the screen, rather than placed in the world, such as a glow around the
+/// cursor or a flash anchored to an element of the HUD.
+///
+/// The light is positioned within the viewport of each camera with a [`Light2d`], so it doesn't
+/// move as cameras pan or zoom, and its radius is measured in physical pixels, so it covers the
+/// same part of the screen regardless of the size of the window. Illumination is attenuated in
+/// the same way as a [`PointLight2d`]. Screen lights don't cast shadows, and count towards the
+/// limit of point lights in WebGL2.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Visibility)]
+pub struct ScreenLight2d {
+    /// The position of the light within the viewport, from `(0.0, 0.0)` at the top left to
+    /// `(1.0, 1.0)` at the bottom right.
+    pub anchor: Vec2,
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The radius of the light, in physical pixels. Illumination will only occur within this
+    /// distance of the light's anchor.
+    pub radius_px: f32,
+    /// How quickly illumination from the light should deteriorate over distance.
+    /// A higher falloff value will result in less illumination at the light's maximum radius.
+    pub falloff: f32,
+}
+
+impl Default for ScreenLight2d {
+    /// Returns a white [`ScreenLight2d`] in the center of the viewport, with a radius of `100`
+    /// pixels.
+    fn default() -> Self {
+        Self {
+            anchor: Vec2::splat(0.5),
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius_px: 100.0,
+            falloff: 0.0,
+        }
+    }
+}
+
+/// A light that emits from a ring around the entity, like a magic circle or a ring of fire.
+///
+/// Brightness peaks on the ring and fades to nothing at the edges of a band around it, so the
+/// center of the ring stays dark. The arc parameters restrict the light to a portion of the
+/// ring, such as for a radial progress indicator. A full arc with a small thickness looks like
+/// a glowing circle outline.
+///
+/// Arc angles are measured anticlockwise from the entity's local x axis, and are rotated with
+/// the entity's transform.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<RingLight2d>)]
+pub struct RingLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's attenutation is multiplied by this value.
+    /// The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The radius of the ring.
+    pub radius: f32,
+    /// The width of the band of illumination, centered on the ring.
+    pub thickness: f32,
+    /// How quickly illumination from the light should deteriorate over distance from the ring.
+    /// A higher falloff value will result in less illumination at the edges of the band.
+    pub falloff: f32,
+    /// The angle (in degrees) where the lit arc of the ring starts.
+    pub arc_start: f32,
+    /// The angle (in degrees) where the lit arc of the ring ends. This must not be less than
+    /// `arc_start`. Arcs covering `360.0` degrees or more light the whole ring.
+    pub arc_end: f32,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+    /// Whether the light ignores occluders on its own entity and its descendants.
+    ///
+    /// This is useful for lights carried by an occluding entity, such as a torch held by a
+    /// character, which would otherwise be shadowed by its carrier.
+    pub ignore_self_occlusion: bool,
+}
+
+impl Default for RingLight2d {
+    /// Returns a white [`RingLight2d`] lighting the whole ring.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 0.5,
+            thickness: 0.1,
+            falloff: 0.0,
+            arc_start: 0.0,
+            arc_end: 360.0,
             cast_shadows: false,
+            ignore_self_occlusion: false,
         }
     }
 }
 
+/// A light that illuminates the whole view uniformly from a fixed direction, like the sun or the
+/// moon.
+///
+/// Directional lights have no position or radius, so they don't attenuate over distance.
+/// Shadows cast by directional lights are parallel, and extend away from the light's direction
+/// across the view. Multiple directional lights are added together, and at most eight are drawn
+/// at once.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld)]
+pub struct DirectionalLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The illuminance of the light. The light's color is multiplied by this value.
+    pub illuminance: f32,
+    /// The angle (in degrees) of the direction the light shines towards.
+    pub direction: f32,
+    /// Whether the light should cast shadows.
+    pub cast_shadows: bool,
+}
+
+impl Default for DirectionalLight2d {
+    /// Returns a white [`DirectionalLight2d`] shining downwards.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            illuminance: 1.0,
+            direction: -90.0,
+            cast_shadows: false,
+        }
+    }
+}
+
+/// A light that illuminates the whole view uniformly, which is never blocked by occluders.
+///
+/// Unlike the [`AmbientLight2d`] of a camera, global lights are entities, so any number of them
+/// can be spawned, animated, and parented. This is useful for stylized fill lighting, such as
+/// crossfading between a light for each area of a level. Global lights are added to the ambient
+/// light, and multiple global lights are added together. Hidden global lights don't emit light.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(Visibility)]
+pub struct GlobalLight2d {
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light. The light's color is multiplied by this value.
+    pub intensity: f32,
+}
+
+impl Default for GlobalLight2d {
+    /// Returns a white [`GlobalLight2d`] with an intensity of `1.0`.
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Makes a [`Sprite`] emit light matching its own image, like a lava tile, a neon sign or a
+/// glowing mushroom.
+///
+/// The sprite's image, tinted by the sprite's color and `color_scale`, is rendered into an
+/// emissive buffer which is added to the light reaching each pixel, so the sprite stays bright
+/// even in the dark. Emitted light isn't blocked by occluders. By default, emitted light stays
+/// within the sprite; set [`Light2d::emissive_spill`] to let it spill over onto the sprite's
+/// surroundings. Hidden sprites don't emit light.
+///
+/// The sprite's image must be kept in the main world, and is downsampled into an atlas shared
+/// with light cookies, so very large images lose detail.
+///
+/// [`Sprite`]: bevy::sprite::Sprite
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(SyncToRenderWorld)]
+pub struct EmissiveSprite2d {
+    /// How brightly the sprite emits light. The sprite's colors are multiplied by this value.
+    pub intensity: f32,
+    /// A color the sprite's emitted light is multiplied by.
+    pub color_scale: Color,
+}
+
+impl Default for EmissiveSprite2d {
+    /// Returns an [`EmissiveSprite2d`] which emits the sprite's own colors.
+    fn default() -> Self {
+        Self {
+            intensity: 1.0,
+            color_scale: Color::WHITE,
+        }
+    }
+}
+
+/// Marks a light which never moves or changes, such as a torch mounted on a wall.
+///
+/// Static lights aren't drawn every frame. Instead, they're baked into a lightmap once with
+/// [`Light2dBaker`], which is drawn by cameras with a [`BakedLightmap2d`]. Bake the lightmap
+/// again when static lights or occluders change.
+///
+/// [`PointLight2d`], [`SpotLight2d`], [`LineLight2d`], [`CapsuleLight2d`], [`AreaLight2d`],
+/// [`PolygonLight2d`], [`PolylineLight2d`], [`RingLight2d`] and [`BeamLight2d`] can be baked.
+/// Other lights marked as static are still drawn every frame.
+///
+/// [`Light2dBaker`]: crate::bake::Light2dBaker
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct StaticLight2d;
+
+/// A lightmap of [`StaticLight2d`]s baked by [`Light2dBaker`], to be used with a `Camera2d`
+/// alongside [`Light2d`].
+///
+/// The lightmap is added to the camera's ambient light, before any other lights. Fragments
+/// outside of the lightmap's region receive no light from it.
+///
+/// [`Light2dBaker`]: crate::bake::Light2dBaker
+#[derive(Component, Clone, Default)]
+pub struct BakedLightmap2d {
+    /// The baked lightmap image.
+    pub image: Handle<Image>,
+    /// The world space region covered by the lightmap, which must match the region it was
+    /// baked with.
+    pub region: Rect,
+}
+
 /// A bundle of components for rendering a [`PointLight2d`] entity.
 #[derive(Bundle, Default)]
 #[deprecated(
@@ -148,6 +1246,19 @@ pub struct AmbientLight2d {
     /// The brightness of the ambient light. This value is multiplied against the linear RGB
     /// representation of the ambient light's color.
     pub brightness: f32,
+    /// A vertical gradient the ambient light's color follows instead of `color`, like the
+    /// light fading with depth underwater. `None` lights the scene with `color` alone.
+    pub gradient: Option<AmbientGradient>,
+    /// A texture the ambient light is multiplied by across `ambient_map_region`, for lighting
+    /// painted by hand, like a vignette around the edge of a level.
+    ///
+    /// The map multiplies the whole ambient term, including global lights and ambient light
+    /// zones. Fragments outside of the region, and every fragment while the image is loading,
+    /// receive the plain ambient light. The image is sampled with linear filtering, so it must
+    /// be kept in the main world, in a filterable format like the images loaded from a PNG.
+    pub ambient_map: Option<Handle<Image>>,
+    /// The world space region covered by the `ambient_map`.
+    pub ambient_map_region: Rect,
 }
 
 impl Default for AmbientLight2d {
@@ -157,6 +1268,568 @@ impl Default for AmbientLight2d {
         Self {
             color: Color::WHITE,
             brightness: 1.0,
+            gradient: None,
+            ambient_map: None,
+            ambient_map_region: Rect::default(),
+        }
+    }
+}
+
+/// A vertical gradient of ambient light, from the bottom of the screen or a region of the world
+/// to the top.
+///
+/// The colors are interpolated in linear RGB, and multiplied by the ambient light's
+/// brightness.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+pub struct AmbientGradient {
+    /// The ambient light's color at the top of the gradient.
+    pub top: Color,
+    /// The ambient light's color at the bottom of the gradient.
+    pub bottom: Color,
+    /// Whether the gradient spans the screen or a range of heights in the world.
+    pub space: AmbientGradientSpace,
+    /// The world space heights of the bottom and top of the gradient, when it's in
+    /// [`AmbientGradientSpace::World`]. The ambient light is `bottom` below the range, and `top`
+    /// above it.
+    pub world_range: Range<f32>,
+}
+
+impl Default for AmbientGradient {
+    /// Returns an [`AmbientGradient`] from black at the bottom of the screen to white at the top.
+    fn default() -> Self {
+        Self {
+            top: Color::WHITE,
+            bottom: Color::BLACK,
+            space: AmbientGradientSpace::Screen,
+            world_range: 0.0..100.0,
+        }
+    }
+}
+
+impl AmbientGradient {
+    /// Returns how far up the gradient a world space height is, between `0.0` at its bottom and
+    /// `1.0` at its top, when it's in [`AmbientGradientSpace::World`].
+    pub fn world_factor(&self, y: f32) -> f32 {
+        let height = self.world_range.end - self.world_range.start;
+        if height == 0.0 {
+            return if y < self.world_range.start { 0.0 } else { 1.0 };
+        }
+        ((y - self.world_range.start) / height).clamp(0.0, 1.0)
+    }
+}
+
+/// The space an [`AmbientGradient`] spans.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum AmbientGradientSpace {
+    /// The gradient spans the camera's view, from the bottom of the screen to the top, so it
+    /// stays put as the camera moves.
+    #[default]
+    Screen,
+    /// The gradient spans the `world_range` of heights in the world, so it scrolls with the
+    /// scene, like the surface of the water.
+    World,
+}
+
+/// Returns the color `t` of the way from `a` to `b`, interpolated in linear RGB, which matches
+/// how the colors of lights are blended by the animation system.
+pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    Color::LinearRgba(a.to_linear().mix(&b.to_linear(), t))
+}
+
+/// Returns the color `t` of the way from `a` to `b`, interpolated in Oklab, a perceptual color
+/// space whose blends look evenly spaced, such as between the warm light of dusk and the cool
+/// light of night.
+pub fn lerp_color_perceptual(a: Color, b: Color, t: f32) -> Color {
+    Color::Oklaba(Oklaba::from(a).mix(&Oklaba::from(b), t))
+}
+
+impl AmbientLight2d {
+    /// Returns the ambient light `t` of the way from `self` to `other`, where `t` is clamped
+    /// between `0.0` and `1.0`.
+    ///
+    /// The color is interpolated with [`lerp_color_perceptual`], and the brightness is
+    /// interpolated linearly. Gradients are interpolated in the same way, where an ambient light
+    /// without a gradient is treated as a gradient of its color, so gradients fade in and out
+    /// smoothly. The gradient's space is taken from `self`, unless only `other` has one.
+    /// The ambient map is taken from `self`.
+    pub fn lerp(&self, other: &AmbientLight2d, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            color: lerp_color_perceptual(self.color, other.color, t),
+            brightness: self.brightness.lerp(other.brightness, t),
+            gradient: lerp_gradient(self, other, t),
+            ambient_map: self.ambient_map.clone(),
+            ambient_map_region: self.ambient_map_region,
+        }
+    }
+}
+
+impl PointLight2d {
+    /// Returns the light `t` of the way from `self` to `other`, where `t` is clamped between
+    /// `0.0` and `1.0`.
+    ///
+    /// The color and edge color are interpolated with [`lerp_color_perceptual`], and the
+    /// intensity, radius, inner radius, falloff and attenuation exponent are interpolated
+    /// linearly. Every other field is taken from `self`.
+    pub fn lerp(&self, other: &PointLight2d, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            color: lerp_color_perceptual(self.color, other.color, t),
+            edge_color: lerp_edge_color(
+                (self.color, self.edge_color),
+                (other.color, other.edge_color),
+                t,
+            ),
+            intensity: self.intensity.lerp(other.intensity, t),
+            radius: self.radius.lerp(other.radius, t),
+            inner_radius: self.inner_radius.lerp(other.inner_radius, t),
+            falloff: self.falloff.lerp(other.falloff, t),
+            attenuation_exponent: self
+                .attenuation_exponent
+                .lerp(other.attenuation_exponent, t),
+            ..self.clone()
+        }
+    }
+}
+
+impl SpotLight2d {
+    /// Returns the light `t` of the way from `self` to `other`, where `t` is clamped between
+    /// `0.0` and `1.0`.
+    ///
+    /// The color and edge color are interpolated with [`lerp_color_perceptual`], and the
+    /// intensity, radius, inner radius, falloff, attenuation exponent, direction and angles are
+    /// interpolated linearly. Every other field is taken from `self`.
+    pub fn lerp(&self, other: &SpotLight2d, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            color: lerp_color_perceptual(self.color, other.color, t),
+            edge_color: lerp_edge_color(
+                (self.color, self.edge_color),
+                (other.color, other.edge_color),
+                t,
+            ),
+            intensity: self.intensity.lerp(other.intensity, t),
+            radius: self.radius.lerp(other.radius, t),
+            inner_radius: self.inner_radius.lerp(other.inner_radius, t),
+            falloff: self.falloff.lerp(other.falloff, t),
+            attenuation_exponent: self
+                .attenuation_exponent
+                .lerp(other.attenuation_exponent, t),
+            direction: self.direction.lerp(other.direction, t),
+            inner_angle: self.inner_angle.lerp(other.inner_angle, t),
+            outer_angle: self.outer_angle.lerp(other.outer_angle, t),
+            ..self.clone()
+        }
+    }
+}
+
+/// Interpolates the gradients of two ambient lights. An ambient light without a gradient is
+/// treated as a gradient of its color, spanning the other light's gradient.
+fn lerp_gradient(a: &AmbientLight2d, b: &AmbientLight2d, t: f32) -> Option<AmbientGradient> {
+    let (a, b) = match (&a.gradient, &b.gradient) {
+        (None, None) => return None,
+        (Some(a), Some(b)) => (a.clone(), b.clone()),
+        (Some(gradient), None) => (gradient.clone(), uniform_gradient(b.color, gradient)),
+        (None, Some(gradient)) => (uniform_gradient(a.color, gradient), gradient.clone()),
+    };
+    Some(AmbientGradient {
+        top: lerp_color_perceptual(a.top, b.top, t),
+        bottom: lerp_color_perceptual(a.bottom, b.bottom, t),
+        space: a.space,
+        world_range: a.world_range.start.lerp(b.world_range.start, t)
+            ..a.world_range.end.lerp(b.world_range.end, t),
+    })
+}
+
+/// Returns a gradient of a single color, spanning the same region as `gradient`.
+fn uniform_gradient(color: Color, gradient: &AmbientGradient) -> AmbientGradient {
+    AmbientGradient {
+        top: color,
+        bottom: color,
+        ..gradient.clone()
+    }
+}
+
+/// Interpolates the edge colors of two lights, each paired with its color. A light without an
+/// edge color is treated as having an edge color matching its color, so gradients fade in and
+/// out smoothly.
+fn lerp_edge_color(a: (Color, Option<Color>), b: (Color, Option<Color>), t: f32) -> Option<Color> {
+    if a.1.is_none() && b.1.is_none() {
+        return None;
+    }
+    Some(lerp_color_perceptual(
+        a.1.unwrap_or(a.0),
+        b.1.unwrap_or(b.0),
+        t,
+    ))
+}
+
+/// A lighting preset, such as for a time of day, which can be blended with another preset.
+///
+/// A preset holds an ambient light, and overrides for lights, keyed by name. It's up to you
+/// how names are matched to entities, such as with their `Name` component.
+#[derive(Clone, Default, Reflect)]
+#[reflect(Default)]
+pub struct Light2dPreset {
+    /// The ambient light of the preset.
+    pub ambient_light: AmbientLight2d,
+    /// The point lights of the preset, by name.
+    pub point_lights: HashMap<String, PointLight2d>,
+    /// The spot lights of the preset, by name.
+    pub spot_lights: HashMap<String, SpotLight2d>,
+}
+
+impl Light2dPreset {
+    /// Returns the preset `t` of the way from `a` to `b`, where `t` is clamped between `0.0`
+    /// and `1.0`.
+    ///
+    /// The ambient light, and lights named in both presets, are interpolated with their `lerp`
+    /// methods. Lights named in only one of the presets are kept as they are.
+    pub fn blend(a: &Light2dPreset, b: &Light2dPreset, t: f32) -> Self {
+        Self {
+            ambient_light: a.ambient_light.lerp(&b.ambient_light, t),
+            point_lights: blend_named(&a.point_lights, &b.point_lights, |a, b| a.lerp(b, t)),
+            spot_lights: blend_named(&a.spot_lights, &b.spot_lights, |a, b| a.lerp(b, t)),
         }
     }
 }
+
+fn blend_named<T: Clone>(
+    a: &HashMap<String, T>,
+    b: &HashMap<String, T>,
+    lerp: impl Fn(&T, &T) -> T,
+) -> HashMap<String, T> {
+    let mut blended: HashMap<String, T> = b.clone();
+    for (name, a_value) in a {
+        let value = match b.get(name) {
+            Some(b_value) => lerp(a_value, b_value),
+            None => a_value.clone(),
+        };
+        blended.insert(name.clone(), value);
+    }
+    blended
+}
+
+/// Returns the color of light emitted by a black body at a temperature of `kelvin`, such as
+/// `1900.0` for candlelight, `2700.0` for a warm household bulb, or `6500.0` for daylight.
+///
+/// Temperatures are clamped between `1000.0` and `12000.0` Kelvin. The color can be used by any
+/// light, including an [`AmbientLight2d`].
+///
+/// The color is approximated with the curves fitted by
+/// [Tanner Helland](https://tannerhelland.com/2012/09/18/convert-temperature-rgb-algorithm-code.html).
+pub fn color_from_temperature(kelvin: f32) -> Color {
+    let kelvin = if kelvin.is_nan() {
+        MIN_TEMPERATURE
+    } else {
+        kelvin.clamp(MIN_TEMPERATURE, MAX_TEMPERATURE)
+    };
+    let t = kelvin / 100.0;
+
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (t - 60.0).powf(-0.13320476)
+    };
+    let green = if t <= 66.0 {
+        99.4708 * t.ln() - 161.11957
+    } else {
+        288.12216 * (t - 60.0).powf(-0.075514846)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (t - 10.0).ln() - 305.04479
+    };
+
+    Color::srgb(
+        red.clamp(0.0, 255.0) / 255.0,
+        green.clamp(0.0, 255.0) / 255.0,
+        blue.clamp(0.0, 255.0) / 255.0,
+    )
+}
+
+const MIN_TEMPERATURE: f32 = 1000.0;
+const MAX_TEMPERATURE: f32 = 12000.0;
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::FRAC_PI_2;
+
+    use bevy::{
+        app::App, color::ColorToComponents, ecs::hierarchy::ChildOf, math::Quat,
+        transform::TransformPlugin,
+    };
+
+    use super::*;
+
+    #[test]
+    fn child_spot_light_follows_rotating_parent() {
+        let mut app = App::new();
+        app.add_plugins(TransformPlugin);
+
+        let parent = app
+            .world_mut()
+            .spawn(Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_2)))
+            .id();
+        let spot_light = SpotLight2d {
+            direction: 0.0,
+            direction_from_transform: true,
+            ..Default::default()
+        };
+        let light = app
+            .world_mut()
+            .spawn((spot_light.clone(), ChildOf(parent)))
+            .id();
+
+        let world_direction = |app: &App| {
+            spot_light.world_direction(app.world().get::<GlobalTransform>(light).unwrap())
+        };
+
+        app.update();
+        assert!(world_direction(&app).abs_diff_eq(Vec2::Y, 1e-5));
+
+        app.world_mut()
+            .get_mut::<Transform>(parent)
+            .unwrap()
+            .rotate_z(FRAC_PI_2);
+        app.update();
+        assert!(world_direction(&app).abs_diff_eq(Vec2::NEG_X, 1e-5));
+
+        // Lights that don't follow their transform ignore the parent's rotation.
+        let fixed = SpotLight2d {
+            direction_from_transform: false,
+            ..spot_light.clone()
+        };
+        let global_transform = app.world().get::<GlobalTransform>(light).unwrap();
+        assert!(
+            fixed
+                .world_direction(global_transform)
+                .abs_diff_eq(Vec2::X, 1e-5)
+        );
+    }
+
+    #[test]
+    fn daylight_temperature_is_white() {
+        let color = color_from_temperature(6500.0).to_srgba();
+
+        assert!(color.red > 0.97 && color.green > 0.97 && color.blue > 0.95);
+    }
+
+    #[test]
+    fn household_bulb_temperature_is_warm_orange() {
+        let color = color_from_temperature(2700.0).to_srgba();
+
+        assert_eq!(color.red, 1.0);
+        assert!(color.green > 0.5 && color.green < 0.8);
+        assert!(color.blue < 0.5);
+        assert!(color.red > color.green && color.green > color.blue);
+    }
+
+    #[test]
+    fn temperature_is_clamped() {
+        let coldest = color_from_temperature(MIN_TEMPERATURE);
+        let hottest = color_from_temperature(MAX_TEMPERATURE);
+
+        assert_eq!(color_from_temperature(0.0), coldest);
+        assert_eq!(color_from_temperature(-500.0), coldest);
+        assert_eq!(color_from_temperature(f32::NEG_INFINITY), coldest);
+        assert_eq!(color_from_temperature(f32::NAN), coldest);
+        assert_eq!(color_from_temperature(1.0e6), hottest);
+        assert_eq!(color_from_temperature(f32::INFINITY), hottest);
+
+        for kelvin in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 0.0, 1.0e6] {
+            let color = color_from_temperature(kelvin).to_srgba();
+            assert!(
+                color
+                    .to_f32_array()
+                    .iter()
+                    .all(|channel| channel.is_finite())
+            );
+        }
+    }
+
+    #[test]
+    fn reference_lumens_have_unit_intensity() {
+        assert_eq!(LightIntensityUnit::Lumens.to_intensity(800.0, 100.0), 1.0);
+    }
+
+    #[test]
+    fn lumens_scale_with_inverse_square_radius() {
+        let unit = LightIntensityUnit::Lumens;
+
+        assert_eq!(unit.to_intensity(800.0, 200.0), 0.25);
+        assert_eq!(unit.to_intensity(800.0, 50.0), 4.0);
+        assert_eq!(unit.to_intensity(1600.0, 100.0), 2.0);
+    }
+
+    #[test]
+    fn lumens_without_radius_have_no_intensity() {
+        assert_eq!(LightIntensityUnit::Lumens.to_intensity(800.0, 0.0), 0.0);
+        assert_eq!(LightIntensityUnit::Lumens.to_intensity(800.0, -10.0), 0.0);
+    }
+
+    #[test]
+    fn arbitrary_intensity_is_unchanged() {
+        assert_eq!(LightIntensityUnit::Arbitrary.to_intensity(3.0, 0.0), 3.0);
+        assert_eq!(LightIntensityUnit::Arbitrary.to_intensity(3.0, 100.0), 3.0);
+    }
+
+    fn assert_color_eq(a: Color, b: Color) {
+        let (a, b) = (a.to_linear().to_vec4(), b.to_linear().to_vec4());
+        assert!((a - b).abs().max_element() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn lerp_color_endpoints() {
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        let blue = Color::srgb(0.0, 0.0, 1.0);
+
+        assert_color_eq(lerp_color(red, blue, 0.0), red);
+        assert_color_eq(lerp_color(red, blue, 1.0), blue);
+        assert_color_eq(lerp_color_perceptual(red, blue, 0.0), red);
+        assert_color_eq(lerp_color_perceptual(red, blue, 1.0), blue);
+        assert_color_eq(lerp_color(red, blue, 0.5), Color::linear_rgb(0.5, 0.0, 0.5));
+    }
+
+    #[test]
+    fn lerp_clamps_t() {
+        let a = PointLight2d {
+            intensity: 1.0,
+            color: Color::WHITE,
+            ..Default::default()
+        };
+        let b = PointLight2d {
+            intensity: 3.0,
+            color: Color::BLACK,
+            ..Default::default()
+        };
+
+        let before = a.lerp(&b, -1.0);
+        assert_eq!(before.intensity, 1.0);
+        assert_color_eq(before.color, Color::WHITE);
+
+        let after = a.lerp(&b, 2.0);
+        assert_eq!(after.intensity, 3.0);
+        assert_color_eq(after.color, Color::BLACK);
+
+        let ambient_a = AmbientLight2d {
+            brightness: 0.0,
+            ..Default::default()
+        };
+        let ambient_b = AmbientLight2d {
+            brightness: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(ambient_a.lerp(&ambient_b, 5.0).brightness, 1.0);
+        assert_eq!(ambient_a.lerp(&ambient_b, -5.0).brightness, 0.0);
+    }
+
+    #[test]
+    fn blend_named_keeps_disjoint_keys() {
+        let a = HashMap::from_iter([("torch".to_string(), 1.0), ("shared".to_string(), 0.0)]);
+        let b = HashMap::from_iter([("lamp".to_string(), 2.0), ("shared".to_string(), 4.0)]);
+
+        let blended = blend_named(&a, &b, |a: &f32, b| a.lerp(*b, 0.5));
+
+        assert_eq!(blended.len(), 3);
+        assert_eq!(blended.get("torch"), Some(&1.0));
+        assert_eq!(blended.get("lamp"), Some(&2.0));
+        assert_eq!(blended.get("shared"), Some(&2.0));
+    }
+
+    #[test]
+    fn lerp_edge_color_combinations() {
+        let orange = Color::srgb(1.0, 0.5, 0.0);
+
+        assert_eq!(
+            lerp_edge_color((Color::WHITE, None), (Color::BLACK, None), 0.5),
+            None
+        );
+
+        // A light without an edge color uses its color as its edge color.
+        let fading_in = lerp_edge_color((Color::WHITE, None), (Color::BLACK, Some(orange)), 0.0);
+        assert_color_eq(fading_in.unwrap(), Color::WHITE);
+        let faded_in = lerp_edge_color((Color::WHITE, None), (Color::BLACK, Some(orange)), 1.0);
+        assert_color_eq(faded_in.unwrap(), orange);
+
+        let fading_out = lerp_edge_color((Color::WHITE, Some(orange)), (Color::BLACK, None), 1.0);
+        assert_color_eq(fading_out.unwrap(), Color::BLACK);
+
+        let both = lerp_edge_color(
+            (Color::WHITE, Some(orange)),
+            (Color::BLACK, Some(Color::WHITE)),
+            0.0,
+        );
+        assert_color_eq(both.unwrap(), orange);
+    }
+
+    #[test]
+    fn lerp_gradient_without_gradients_is_none() {
+        let a = AmbientLight2d::default();
+        let b = AmbientLight2d::default();
+
+        assert_eq!(lerp_gradient(&a, &b, 0.5), None);
+    }
+
+    #[test]
+    fn lerp_gradient_with_both_gradients() {
+        let a = AmbientLight2d {
+            gradient: Some(AmbientGradient {
+                world_range: 0.0..100.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let b = AmbientLight2d {
+            gradient: Some(AmbientGradient {
+                top: Color::BLACK,
+                bottom: Color::WHITE,
+                space: AmbientGradientSpace::World,
+                world_range: 100.0..300.0,
+            }),
+            ..Default::default()
+        };
+
+        let gradient = lerp_gradient(&a, &b, 1.0).unwrap();
+        assert_color_eq(gradient.top, Color::BLACK);
+        assert_color_eq(gradient.bottom, Color::WHITE);
+        assert_eq!(gradient.world_range, 100.0..300.0);
+        // The space is taken from `a`.
+        assert_eq!(gradient.space, AmbientGradientSpace::Screen);
+
+        let halfway = lerp_gradient(&a, &b, 0.5).unwrap();
+        assert_eq!(halfway.world_range, 50.0..200.0);
+    }
+
+    #[test]
+    fn lerp_gradient_fades_in_and_out() {
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        let plain = AmbientLight2d {
+            color: red,
+            ..Default::default()
+        };
+        let graded = AmbientLight2d {
+            gradient: Some(AmbientGradient {
+                space: AmbientGradientSpace::World,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        // A light without a gradient is a gradient of its color, in the other's space.
+        let fading_in = lerp_gradient(&plain, &graded, 0.0).unwrap();
+        assert_color_eq(fading_in.top, red);
+        assert_color_eq(fading_in.bottom, red);
+        assert_eq!(fading_in.space, AmbientGradientSpace::World);
+
+        let fading_out = lerp_gradient(&graded, &plain, 1.0).unwrap();
+        assert_color_eq(fading_out.top, red);
+        assert_color_eq(fading_out.bottom, red);
+        assert_eq!(fading_out.space, AmbientGradientSpace::World);
+    }
+}
@@ -0,0 +1,163 @@
+//! A module which contains the level of detail settings of lights, which skip the cost of lights
+//! that are too small on screen to notice.
+
+use bevy::{
+    camera::{Camera, visibility::ViewVisibility},
+    diagnostic::{DiagnosticPath, Diagnostics},
+    ecs::{
+        component::Component,
+        entity::{Entity, EntityHashSet},
+        query::{Has, With},
+        resource::Resource,
+        system::{Query, Res, ResMut},
+    },
+    prelude::{ReflectComponent, ReflectDefault, ReflectResource},
+    reflect::Reflect,
+    transform::components::GlobalTransform,
+};
+
+use crate::{
+    light::{Light2d, PointLight2d, SpotLight2d},
+    vision::VisionSource2d,
+};
+
+/// Controls when [`PointLight2d`]s and [`SpotLight2d`]s are demoted, based on how large they are
+/// on screen.
+///
+/// A light's size on screen is its radius, in physical pixels, in the view where it's largest,
+/// so the camera's zoom and viewport are taken into account. Far away lights, which are only a
+/// few pixels across, stop casting shadows, skipping the cost of raymarching them, and can be
+/// skipped entirely.
+///
+/// The number of lights demoted each frame is recorded in the
+/// [`Light2dLodSettings::DEMOTED_LIGHTS`] diagnostic. Add [`LightLod2d::Never`] to a light to
+/// never demote it.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Default)]
+pub struct Light2dLodSettings {
+    /// Lights with a radius on screen smaller than this (in physical pixels) don't cast
+    /// shadows.
+    pub shadow_radius: f32,
+    /// Lights with a radius on screen smaller than this (in physical pixels) aren't rendered at
+    /// all. `None` renders every light, however small.
+    ///
+    /// Lights that are vision sources are never skipped, so they still reveal a vision mask.
+    pub cull_radius: Option<f32>,
+}
+
+impl Light2dLodSettings {
+    /// The number of lights that were demoted by their level of detail in the last frame,
+    /// either losing their shadows or being skipped.
+    pub const DEMOTED_LIGHTS: DiagnosticPath =
+        DiagnosticPath::const_new("bevy_light_2d/demoted_lights");
+}
+
+impl Default for Light2dLodSettings {
+    /// Returns [`Light2dLodSettings`] which remove the shadows of lights with a radius of less
+    /// than 4 pixels on screen, and never skip lights.
+    fn default() -> Self {
+        Self {
+            shadow_radius: 4.0,
+            cull_radius: None,
+        }
+    }
+}
+
+/// Whether a [`PointLight2d`] or [`SpotLight2d`] can be demoted by [`Light2dLodSettings`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default)]
+pub enum LightLod2d {
+    /// The light is demoted when it's small on screen.
+    #[default]
+    Auto,
+    /// The light is never demoted, such as a light the player is meant to notice from afar.
+    Never,
+}
+
+/// The lights demoted by [`Light2dLodSettings`] this frame.
+#[derive(Resource, Default)]
+pub(crate) struct DemotedLights {
+    /// Lights which don't cast shadows.
+    pub(crate) shadowless: EntityHashSet,
+    /// Lights which aren't rendered.
+    pub(crate) culled: EntityHashSet,
+}
+
+pub(crate) fn update_light_lod(
+    settings: Res<Light2dLodSettings>,
+    mut demoted_lights: ResMut<DemotedLights>,
+    mut diagnostics: Diagnostics,
+    cameras: Query<(&Camera, &GlobalTransform), With<Light2d>>,
+    point_lights: Query<(
+        Entity,
+        &PointLight2d,
+        &GlobalTransform,
+        &ViewVisibility,
+        Option<&LightLod2d>,
+        Has<VisionSource2d>,
+    )>,
+    spot_lights: Query<(
+        Entity,
+        &SpotLight2d,
+        &GlobalTransform,
+        &ViewVisibility,
+        Option<&LightLod2d>,
+        Has<VisionSource2d>,
+    )>,
+) {
+    demoted_lights.shadowless.clear();
+    demoted_lights.culled.clear();
+
+    // Lights are shared between views, so they're only demoted when they're small in every
+    // view, which is when they're small in the most zoomed in view.
+    let pixels_per_unit = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .filter_map(|(camera, global_transform)| {
+            let viewport_width = camera.physical_viewport_size()?.x as f32;
+            let scale = global_transform.scale().x.abs().max(f32::EPSILON);
+            Some(camera.clip_from_view().x_axis.x.abs() * viewport_width * 0.5 / scale)
+        })
+        .reduce(f32::max);
+    let Some(pixels_per_unit) = pixels_per_unit else {
+        diagnostics.add_measurement(&Light2dLodSettings::DEMOTED_LIGHTS, || 0.0);
+        return;
+    };
+
+    let lights = point_lights
+        .iter()
+        .map(
+            |(entity, light, global_transform, visibility, lod, vision)| {
+                let radius = light.radius * light.radius_scale(global_transform);
+                let active = light.enabled && visibility.get();
+                (entity, radius, active, light.cast_shadows, lod, vision)
+            },
+        )
+        .chain(spot_lights.iter().map(
+            |(entity, light, global_transform, visibility, lod, vision)| {
+                let radius = light.radius * light.radius_scale(global_transform);
+                let active = light.enabled && visibility.get();
+                (entity, radius, active, light.cast_shadows, lod, vision)
+            },
+        ));
+
+    for (entity, radius, active, cast_shadows, lod, vision_source) in lights {
+        if !active || lod == Some(&LightLod2d::Never) {
+            continue;
+        }
+        let screen_radius = radius * pixels_per_unit;
+        if !vision_source
+            && settings
+                .cull_radius
+                .is_some_and(|cull_radius| screen_radius < cull_radius)
+        {
+            demoted_lights.culled.insert(entity);
+        } else if cast_shadows && screen_radius < settings.shadow_radius {
+            demoted_lights.shadowless.insert(entity);
+        }
+    }
+
+    diagnostics.add_measurement(&Light2dLodSettings::DEMOTED_LIGHTS, || {
+        (demoted_lights.shadowless.len() + demoted_lights.culled.len()) as f64
+    });
+}
@@ -0,0 +1,465 @@
+//! A module which contains components that animate lights, such as [`LightFlicker2d`],
+//! [`LightPulse2d`], [`LightStrobe2d`] and [`LightOscillator2d`]. Lights can also be faded in
+//! and out with a [`LightFade2d`](crate::fade::LightFade2d).
+//!
+//! Rather than overwriting a light's fields or transform, effects modify the light's
+//! [`LightModulation2d`], which is reset every frame and applied as the light is extracted for
+//! rendering. This means effects can be combined, user code can freely change a light's
+//! intensity, color and transform, and removing an effect restores the light as it was.
+//!
+//! Lights can also be varied across the area they light, rather than over time, with a
+//! [`LightNoise2d`].
+
+use core::f32::consts::TAU;
+
+use bevy::{
+    asset::Handle,
+    color::{ColorToComponents, LinearRgba},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        resource::Resource,
+        schedule::SystemSet,
+        system::{Query, Res, ResMut},
+    },
+    image::Image,
+    math::{FloatExt, Vec2, Vec3},
+    prelude::{ReflectComponent, ReflectDefault, ReflectResource},
+    reflect::Reflect,
+    render::extract_resource::ExtractResource,
+    time::Time,
+};
+
+use crate::light::color_from_temperature;
+
+/// Multiplies the intensity and color of a light on the same entity when it's rendered, and
+/// offsets its position.
+///
+/// Every kind of light is modulated. [`DirectionalLight2d`]s have no position, and
+/// [`ScreenLight2d`]s are anchored to the viewport, so they aren't offset.
+///
+/// The modulation is reset at the start of every frame, in [`LightModulationSystems::Reset`],
+/// and modified by each effect on the entity, in [`LightModulationSystems::Modulate`].
+///
+/// [`DirectionalLight2d`]: crate::light::DirectionalLight2d
+/// [`ScreenLight2d`]: crate::light::ScreenLight2d
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct LightModulation2d {
+    /// The factor the light's intensity is multiplied by.
+    pub intensity: f32,
+    /// The color the light's color is multiplied by.
+    pub color: LinearRgba,
+    /// The world space offset of the light from its entity's position.
+    ///
+    /// Only the rendered light is moved, so the entity's transform, and anything else that
+    /// depends on it, is left untouched.
+    pub offset: Vec2,
+}
+
+impl Default for LightModulation2d {
+    /// Returns a [`LightModulation2d`] which leaves the light unchanged.
+    fn default() -> Self {
+        Self {
+            intensity: 1.0,
+            color: LinearRgba::WHITE,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+/// A texture which varies a [`PointLight2d`] or [`SpotLight2d`] across the area it lights, like
+/// the churning glow of a campfire or the caustics of light through water.
+///
+/// The light's color is multiplied by the texture, which is tiled across the world and scrolled
+/// over time on the GPU, driven by [`Light2dAnimationTime`]. The texture is shared with light
+/// cookies, so it must be kept in the main world, and lights whose texture hasn't loaded are
+/// drawn without it.
+///
+/// [`PointLight2d`]: crate::light::PointLight2d
+/// [`SpotLight2d`]: crate::light::SpotLight2d
+#[derive(Clone, Debug, Reflect)]
+pub struct LightNoise2d {
+    /// The texture the light is multiplied by.
+    pub texture: Handle<Image>,
+    /// The size (in world units) of each tile of the texture.
+    pub scale: Vec2,
+    /// The speed (in world units per second) the texture scrolls at.
+    pub scroll: Vec2,
+}
+
+/// System sets for the systems which animate lights, which run in the `Update` schedule.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LightModulationSystems {
+    /// Resets every [`LightModulation2d`].
+    Reset,
+    /// Modifies [`LightModulation2d`]s by each effect.
+    Modulate,
+}
+
+/// The clock that light animations, such as [`LightPulse2d`], are driven by.
+///
+/// The clock advances with Bevy's `Time`, scaled by its `speed`, unless it's paused, such as
+/// while a game's pause menu is open. Animations freeze where they are while the clock is
+/// paused, and carry on from there when it's resumed.
+///
+/// Every built-in light animation follows this clock, including flickers, fades, trails,
+/// scrolling cookies and noise textures, so slowing it down puts them all in slow motion.
+#[derive(Resource, Clone, Debug, ExtractResource, Reflect)]
+#[reflect(Resource, Default)]
+pub struct Light2dAnimationTime {
+    /// Whether light animations are paused.
+    pub paused: bool,
+    /// How fast light animations run, relative to Bevy's `Time`. A speed of `0.5` runs them at
+    /// half speed, for slow motion. Negative speeds are treated as `0.0`.
+    pub speed: f32,
+    elapsed: f32,
+    delta: f32,
+}
+
+impl Default for Light2dAnimationTime {
+    /// Returns a [`Light2dAnimationTime`] which runs at the same speed as Bevy's `Time`.
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: 1.0,
+            elapsed: 0.0,
+            delta: 0.0,
+        }
+    }
+}
+
+impl Light2dAnimationTime {
+    /// Returns how long light animations have been running for, in seconds, excluding any time
+    /// they spent paused, and scaled by their speed.
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Returns how long light animations advanced by in the last frame, in seconds, which is
+    /// `0.0` while they're paused.
+    pub fn delta_secs(&self) -> f32 {
+        self.delta
+    }
+}
+
+/// Makes a light flicker, like a torch or a candle.
+///
+/// The light's intensity is perturbed with smooth noise, which is unique to each seed, so
+/// torches with different seeds don't flicker in lockstep. Flickers are driven by
+/// [`Light2dAnimationTime`], so they can be paused.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(LightModulation2d)]
+pub struct LightFlicker2d {
+    /// How far the light's intensity strays from its own, as a fraction of it. An amplitude of
+    /// `0.2` varies the intensity between 80% and 120%.
+    pub amplitude: f32,
+    /// How many times per second the flicker changes direction, on average.
+    pub frequency: f32,
+    /// The seed of the flicker's noise.
+    pub seed: u32,
+    /// How smooth the flicker is, from `0.0` for a jittery flicker to `1.0` for a slow,
+    /// rolling one.
+    pub smoothing: f32,
+    /// How much the light's color warms, towards the orange of candlelight, as it dims. A
+    /// warmth of `0.0` leaves the light's color unchanged.
+    pub warmth: f32,
+}
+
+impl Default for LightFlicker2d {
+    /// Returns a [`LightFlicker2d`] which varies a light's intensity by 20%, about 8 times a
+    /// second.
+    fn default() -> Self {
+        Self {
+            amplitude: 0.2,
+            frequency: 8.0,
+            seed: 0,
+            smoothing: 0.5,
+            warmth: 0.0,
+        }
+    }
+}
+
+// The color temperature, in Kelvin, that flickering lights warm towards as they dim.
+const FLICKER_WARM_TEMPERATURE: f32 = 1900.0;
+
+/// Makes a light's intensity pulse rhythmically, like an alarm or a heartbeat.
+///
+/// The light's intensity is multiplied by a factor which cycles between `min_intensity` and
+/// `max_intensity` every `period` seconds, following the pulse's waveform. Pulses are driven
+/// by [`Light2dAnimationTime`], so they can be paused.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(LightModulation2d)]
+pub struct LightPulse2d {
+    /// The factor the light's intensity is multiplied by at the lowest point of the pulse.
+    pub min_intensity: f32,
+    /// The factor the light's intensity is multiplied by at the highest point of the pulse.
+    pub max_intensity: f32,
+    /// How long each pulse lasts for, in seconds. Pulses with a period of `0.0` or less hold
+    /// the light at `max_intensity`.
+    pub period: f32,
+    /// The shape of each pulse.
+    pub waveform: PulseWaveform,
+    /// How far through its cycle the pulse starts, as a fraction of its period, so pulsing
+    /// lights can be staggered.
+    pub phase: f32,
+}
+
+impl Default for LightPulse2d {
+    /// Returns a [`LightPulse2d`] which smoothly varies a light's intensity between 50% and
+    /// 100%, once a second.
+    fn default() -> Self {
+        Self {
+            min_intensity: 0.5,
+            max_intensity: 1.0,
+            period: 1.0,
+            waveform: PulseWaveform::Sine,
+            phase: 0.0,
+        }
+    }
+}
+
+/// The shape of a [`LightPulse2d`]'s pulses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default)]
+pub enum PulseWaveform {
+    /// The intensity eases between its minimum and maximum, like a heartbeat aura.
+    #[default]
+    Sine,
+    /// The intensity rises and falls linearly.
+    Triangle,
+    /// The intensity switches between its minimum and maximum halfway through each pulse, like
+    /// an alarm.
+    Square,
+}
+
+impl PulseWaveform {
+    /// Returns the waveform's value, between `0.0` and `1.0`, at `t` periods into its cycle.
+    fn sample(self, t: f32) -> f32 {
+        let t = t.rem_euclid(1.0);
+        match self {
+            PulseWaveform::Sine => 0.5 - 0.5 * (t * TAU).cos(),
+            PulseWaveform::Triangle => 1.0 - (2.0 * t - 1.0).abs(),
+            PulseWaveform::Square => {
+                if t < 0.5 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// Switches a light on and off, like a police light or a broken fluorescent tube.
+///
+/// Rather than hiding the light's entity, the strobe switches the light off by zeroing its
+/// intensity, so anything else on the entity, such as its sprite, stays visible.
+///
+/// Each strobe starts at a different point in its cycle, so strobes with the same settings on
+/// different entities don't flash in lockstep, unless they share a `sync_group`. Strobes are
+/// driven by [`Light2dAnimationTime`], so they can be paused.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(LightModulation2d)]
+pub struct LightStrobe2d {
+    /// How long the light stays on for in each cycle, in seconds.
+    pub on_duration: f32,
+    /// How long the light stays off for in each cycle, in seconds.
+    pub off_duration: f32,
+    /// How much the time the light stays off for varies between cycles, as a fraction of
+    /// `off_duration`, for the stutter of a broken light. The time the light stays on for
+    /// makes up the difference, so the strobe keeps its rhythm.
+    pub jitter: f32,
+    /// The group of strobes this strobe flashes in sync with. Strobes with the same group and
+    /// settings flash together, while a strobe without a group flashes on its own.
+    pub sync_group: Option<u32>,
+    on: bool,
+}
+
+impl Default for LightStrobe2d {
+    /// Returns a [`LightStrobe2d`] which flashes a light on and off twice a second, without
+    /// any jitter.
+    fn default() -> Self {
+        Self {
+            on_duration: 0.25,
+            off_duration: 0.25,
+            jitter: 0.0,
+            sync_group: None,
+            on: true,
+        }
+    }
+}
+
+impl LightStrobe2d {
+    /// Returns whether the strobe currently has the light switched on.
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+}
+
+/// Makes a light sway around its entity's position, like a hanging lantern or a buoy.
+///
+/// The light is offset along each axis by a sine wave, without moving its entity, so the
+/// entity's transform and its children are left untouched. Oscillators are driven by
+/// [`Light2dAnimationTime`], so they can be paused.
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(LightModulation2d)]
+pub struct LightOscillator2d {
+    /// How far (in world units) the light sways from its entity's position along each axis.
+    pub amplitude: Vec2,
+    /// How many times per second the light sways back and forth along each axis.
+    pub frequency: Vec2,
+    /// How far through its cycle the sway along each axis starts, as a fraction of the cycle.
+    ///
+    /// Sways with the same frequency along both axes trace a line when their phases match,
+    /// and an ellipse when they're a quarter of a cycle apart.
+    pub phase: Vec2,
+}
+
+impl Default for LightOscillator2d {
+    /// Returns a [`LightOscillator2d`] which sways a light 4 units from side to side, every two
+    /// seconds.
+    fn default() -> Self {
+        Self {
+            amplitude: Vec2::new(4.0, 0.0),
+            frequency: Vec2::new(0.5, 0.5),
+            phase: Vec2::ZERO,
+        }
+    }
+}
+
+pub(crate) fn advance_light_animation_time(
+    time: Res<Time>,
+    mut animation_time: ResMut<Light2dAnimationTime>,
+) {
+    animation_time.delta = if animation_time.paused {
+        0.0
+    } else {
+        time.delta_secs() * animation_time.speed.max(0.0)
+    };
+    animation_time.elapsed += animation_time.delta;
+}
+
+pub(crate) fn reset_light_modulation(mut modulations: Query<&mut LightModulation2d>) {
+    for mut modulation in &mut modulations {
+        *modulation = LightModulation2d::default();
+    }
+}
+
+pub(crate) fn flicker_lights(
+    animation_time: Res<Light2dAnimationTime>,
+    mut lights: Query<(&LightFlicker2d, &mut LightModulation2d)>,
+) {
+    let elapsed = animation_time.elapsed_secs();
+    let warm_color = color_from_temperature(FLICKER_WARM_TEMPERATURE)
+        .to_linear()
+        .to_vec3();
+
+    for (flicker, mut modulation) in &mut lights {
+        // A second, faster octave of noise adds jitter, which smoothing fades out.
+        let t = elapsed * flicker.frequency.max(0.0);
+        let detail = 1.0 - flicker.smoothing.clamp(0.0, 1.0);
+        let noise = (value_noise(flicker.seed, t) + value_noise(!flicker.seed, t * 2.7) * detail)
+            / (1.0 + detail);
+
+        // Noise is mapped from 0..1 to -1..1, so the light flickers around its own intensity.
+        let offset = flicker.amplitude * (noise * 2.0 - 1.0);
+        modulation.intensity *= (1.0 + offset).max(0.0);
+
+        let dimming = (-offset).clamp(0.0, 1.0) * flicker.warmth.clamp(0.0, 1.0);
+        if dimming > 0.0 {
+            let tint = Vec3::ONE.lerp(warm_color, dimming);
+            modulation.color = LinearRgba::from_vec3(modulation.color.to_vec3() * tint);
+        }
+    }
+}
+
+pub(crate) fn pulse_lights(
+    animation_time: Res<Light2dAnimationTime>,
+    mut lights: Query<(&LightPulse2d, &mut LightModulation2d)>,
+) {
+    let elapsed = animation_time.elapsed_secs();
+
+    for (pulse, mut modulation) in &mut lights {
+        let value = if pulse.period > 0.0 {
+            pulse.waveform.sample(elapsed / pulse.period + pulse.phase)
+        } else {
+            1.0
+        };
+        modulation.intensity *= pulse.min_intensity.lerp(pulse.max_intensity, value);
+    }
+}
+
+pub(crate) fn strobe_lights(
+    animation_time: Res<Light2dAnimationTime>,
+    mut lights: Query<(Entity, &mut LightStrobe2d, &mut LightModulation2d)>,
+) {
+    let elapsed = animation_time.elapsed_secs();
+
+    for (entity, mut strobe, mut modulation) in &mut lights {
+        let on_duration = strobe.on_duration.max(0.0);
+        let off_duration = strobe.off_duration.max(0.0);
+        let period = on_duration + off_duration;
+        if period <= 0.0 {
+            continue;
+        }
+
+        // Strobes in a group share a seed, so they flash together. Every other strobe is
+        // seeded by its entity, which offsets its cycle from its neighbours.
+        let seed = match strobe.sync_group {
+            Some(sync_group) => sync_group.wrapping_mul(0x2c1b_3c6d) ^ 0x5bd1_e995,
+            None => entity.index(),
+        };
+        let t = elapsed + hash(seed, 0) * period;
+        let cycle = (t / period).floor();
+        let cycle_time = t - cycle * period;
+
+        let jitter = strobe.jitter.max(0.0) * (hash(seed, cycle as i32 + 1) * 2.0 - 1.0);
+        let off_time = (off_duration * (1.0 + jitter)).clamp(0.0, period);
+        let on = cycle_time < period - off_time;
+
+        if strobe.on != on {
+            strobe.on = on;
+        }
+        if !on {
+            modulation.intensity = 0.0;
+        }
+    }
+}
+
+pub(crate) fn oscillate_lights(
+    animation_time: Res<Light2dAnimationTime>,
+    mut lights: Query<(&LightOscillator2d, &mut LightModulation2d)>,
+) {
+    let elapsed = animation_time.elapsed_secs();
+
+    for (oscillator, mut modulation) in &mut lights {
+        let angle = (oscillator.frequency * elapsed + oscillator.phase) * TAU;
+        modulation.offset += oscillator.amplitude * Vec2::new(angle.x.sin(), angle.y.sin());
+    }
+}
+
+/// Returns smooth 1d value noise between `0.0` and `1.0`, which is unique to each seed.
+fn value_noise(seed: u32, t: f32) -> f32 {
+    let i = t.floor();
+    let f = t - i;
+    let s = f * f * (3.0 - 2.0 * f);
+    let a = hash(seed, i as i32);
+    let b = hash(seed, i as i32 + 1);
+    a + (b - a) * s
+}
+
+/// Returns a pseudo-random value between `0.0` and `1.0` for a lattice point of the noise.
+fn hash(seed: u32, i: i32) -> f32 {
+    let mut x = (i as u32).wrapping_mul(0x9e37_79b9) ^ seed.wrapping_mul(0x85eb_ca6b);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x as f32 / u32::MAX as f32
+}
@@ -0,0 +1,151 @@
+use bevy::{
+    asset::Assets,
+    camera::primitives::Aabb,
+    ecs::{
+        entity::Entity,
+        resource::Resource,
+        system::{Commands, Local, Query, Res},
+        world::Ref,
+    },
+    image::Image,
+    math::{Vec2, Vec3Swizzles},
+    prelude::ReflectResource,
+    reflect::Reflect,
+    transform::components::GlobalTransform,
+};
+
+use crate::light::{PointLight2d, SpotLight2d};
+
+use super::{LightOccluder2d, LightOccluder2dShape};
+
+/// Controls how far outside of a view [`LightOccluder2d`]s are kept, rather than culled.
+///
+/// Occluders are given an [`Aabb`] from their shapes, so that occluders outside of every view
+/// aren't rendered. An occluder outside of a view can still cast a shadow into it, so its
+/// bounds are padded by the radius of the largest light, plus this resource's `margin`.
+///
+/// Add [`NoFrustumCulling`] to an occluder to never cull it.
+///
+/// [`NoFrustumCulling`]: bevy::camera::visibility::NoFrustumCulling
+#[derive(Resource, Clone, Default, Reflect)]
+#[reflect(Resource, Default)]
+pub struct OccluderCulling {
+    /// An extra distance (in world units) that occluders' bounds are padded by.
+    pub margin: f32,
+}
+
+pub(crate) fn calculate_occluder_bounds(
+    mut commands: Commands,
+    mut last_padding: Local<f32>,
+    culling: Res<OccluderCulling>,
+    images: Res<Assets<Image>>,
+    point_lights: Query<&PointLight2d>,
+    spot_lights: Query<&SpotLight2d>,
+    occluders: Query<(
+        Entity,
+        Ref<LightOccluder2d>,
+        Ref<GlobalTransform>,
+        Option<&Aabb>,
+    )>,
+) {
+    let max_light_radius = point_lights
+        .iter()
+        .map(|light| light.radius)
+        .chain(
+            spot_lights
+                .iter()
+                .map(|light| light.radius + light.source_width * 0.5),
+        )
+        .fold(0.0, f32::max);
+    let padding = max_light_radius + culling.margin.max(0.0);
+
+    let padding_changed = padding != *last_padding;
+    *last_padding = padding;
+
+    for (entity, occluder, global_transform, aabb) in &occluders {
+        // Bounds are recalculated when the occluder's transform changes, so that the padding
+        // stays the same in world space as the occluder is scaled.
+        if !padding_changed
+            && !occluder.is_changed()
+            && !global_transform.is_changed()
+            && aabb.is_some()
+        {
+            continue;
+        }
+
+        let Some((min, max)) = local_bounds(&occluder, &images) else {
+            // Occluders without bounds yet (such as images that haven't loaded) are never
+            // culled.
+            commands.entity(entity).remove::<Aabb>();
+            continue;
+        };
+
+        // The padding and margin are in world space, while bounds are in local space.
+        let scale = global_transform.scale().xy().abs().max(Vec2::splat(1e-6));
+        let local_padding = Vec2::splat(padding + occluder.margin.max(0.0)) / scale;
+
+        commands.entity(entity).insert(Aabb::from_min_max(
+            (min - local_padding).extend(0.0),
+            (max + local_padding).extend(0.0),
+        ));
+    }
+}
+
+/// Returns the local space bounds of all of an occluder's shapes, as a minimum and maximum.
+fn local_bounds(occluder: &LightOccluder2d, images: &Assets<Image>) -> Option<(Vec2, Vec2)> {
+    let shapes = core::iter::once((&occluder.shape, occluder.offset)).chain(
+        occluder.compound_shapes.iter().map(|compound_shape| {
+            (
+                &compound_shape.shape,
+                occluder.offset + compound_shape.offset,
+            )
+        }),
+    );
+
+    let mut bounds: Option<(Vec2, Vec2)> = None;
+    for (shape, offset) in shapes {
+        let (min, max) = shape_bounds(shape, images)?;
+        let (min, max) = (min + offset, max + offset);
+
+        bounds = Some(match bounds {
+            Some((bounds_min, bounds_max)) => (bounds_min.min(min), bounds_max.max(max)),
+            None => (min, max),
+        });
+    }
+
+    bounds
+}
+
+fn shape_bounds(shape: &LightOccluder2dShape, images: &Assets<Image>) -> Option<(Vec2, Vec2)> {
+    let half_size = match shape {
+        LightOccluder2dShape::Rectangle { half_size }
+        | LightOccluder2dShape::RoundedRectangle { half_size, .. }
+        | LightOccluder2dShape::Ellipse { half_size } => half_size.abs(),
+        LightOccluder2dShape::Capsule {
+            half_length,
+            radius,
+        } => Vec2::new(radius.abs(), half_length.abs() + radius.abs()),
+        LightOccluder2dShape::Annulus {
+            inner_radius,
+            outer_radius,
+        } => Vec2::splat(inner_radius.max(*outer_radius).max(0.0)),
+        LightOccluder2dShape::AlphaMask { image, .. } => images.get(image)?.size().as_vec2() * 0.5,
+        LightOccluder2dShape::Sdf { world_size, .. } => world_size.abs() * 0.5,
+        LightOccluder2dShape::Triangle { a, b, c } => return point_bounds([*a, *b, *c]),
+        LightOccluder2dShape::Segment { start, end } => return point_bounds([*start, *end]),
+        LightOccluder2dShape::ConvexPolygon(vertices) | LightOccluder2dShape::Polygon(vertices) => {
+            return point_bounds(vertices.iter().copied());
+        }
+    };
+
+    Some((-half_size, half_size))
+}
+
+fn point_bounds(points: impl IntoIterator<Item = Vec2>) -> Option<(Vec2, Vec2)> {
+    points.into_iter().fold(None, |bounds, point| {
+        Some(match bounds {
+            Some((min, max)) => (Vec2::min(min, point), Vec2::max(max, point)),
+            None => (point, point),
+        })
+    })
+}
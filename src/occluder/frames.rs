@@ -0,0 +1,82 @@
+use bevy::{
+    ecs::{
+        change_detection::DetectChangesMut,
+        component::Component,
+        query::{Changed, Or},
+        system::Query,
+    },
+    sprite::Sprite,
+};
+
+use super::{LightOccluder2d, LightOccluder2dShape};
+
+/// Swaps an entity's [`LightOccluder2d`] shape to match the frame of its [`Sprite`]'s
+/// [`TextureAtlas`], for animated sprites whose silhouette changes between frames.
+///
+/// Each frame's shape is used when the atlas index is that frame's index. Indices past the
+/// last frame use the last frame's shape, and sprites without a texture atlas use the first.
+/// The occluder's shape is only replaced when the atlas index changes. Other occluder
+/// properties, such as its opacity, are left untouched.
+///
+/// [`TextureAtlas`]: bevy::image::TextureAtlas
+#[derive(Component, Clone, Default)]
+#[require(LightOccluder2d)]
+pub struct OccluderFrames {
+    frames: Vec<LightOccluder2dShape>,
+    // The frame whose shape was last applied to the occluder.
+    applied: Option<usize>,
+}
+
+impl OccluderFrames {
+    /// Returns [`OccluderFrames`] with a shape for each frame of the texture atlas.
+    pub fn new(frames: impl IntoIterator<Item = LightOccluder2dShape>) -> Self {
+        Self {
+            frames: frames.into_iter().collect(),
+            applied: None,
+        }
+    }
+
+    /// The shape of each frame.
+    pub fn frames(&self) -> &[LightOccluder2dShape] {
+        &self.frames
+    }
+
+    /// Replaces the shape of each frame, which is applied to the occluder even if the atlas
+    /// index hasn't changed.
+    pub fn set_frames(&mut self, frames: impl IntoIterator<Item = LightOccluder2dShape>) {
+        self.frames = frames.into_iter().collect();
+        self.applied = None;
+    }
+
+    /// Returns the frame shown for an atlas index, clamped to the last frame.
+    fn frame(&self, atlas_index: usize) -> Option<usize> {
+        self.frames
+            .len()
+            .checked_sub(1)
+            .map(|last| atlas_index.min(last))
+    }
+}
+
+pub(crate) fn update_occluder_frames(
+    mut occluders: Query<
+        (&Sprite, &mut OccluderFrames, &mut LightOccluder2d),
+        Or<(Changed<Sprite>, Changed<OccluderFrames>)>,
+    >,
+) {
+    for (sprite, mut occluder_frames, mut occluder) in &mut occluders {
+        let atlas_index = sprite.texture_atlas.as_ref().map_or(0, |atlas| atlas.index);
+
+        // Sprites change for many reasons other than animation, such as their color, so the
+        // shape is only replaced when the frame changes.
+        let frame = occluder_frames.frame(atlas_index);
+        if frame == occluder_frames.applied {
+            continue;
+        }
+
+        let occluder_frames = occluder_frames.bypass_change_detection();
+        occluder_frames.applied = frame;
+        if let Some(frame) = frame {
+            occluder.shape = occluder_frames.frames[frame].clone();
+        }
+    }
+}
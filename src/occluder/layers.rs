@@ -0,0 +1,109 @@
+use bevy::{
+    ecs::{component::Component, entity::Entity},
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+};
+
+/// Describes which lights an occluder blocks, similar to Bevy's `RenderLayers`.
+///
+/// Occluders only cast shadows from lights whose layers intersect their own. Add this
+/// component to both a [`LightOccluder2d`] and a light, such as a [`PointLight2d`] or
+/// [`SpotLight2d`]. Entities without this component are on every layer, so by default every
+/// occluder blocks every light.
+///
+/// There are 32 layers, numbered `0` to `31`.
+///
+/// [`LightOccluder2d`]: crate::occluder::LightOccluder2d
+/// [`PointLight2d`]: crate::light::PointLight2d
+/// [`SpotLight2d`]: crate::light::SpotLight2d
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Default)]
+pub struct OcclusionLayers(pub u32);
+
+impl OcclusionLayers {
+    /// The number of available layers.
+    pub const TOTAL_LAYERS: usize = u32::BITS as usize;
+
+    /// Every layer.
+    pub const ALL: Self = Self(u32::MAX);
+
+    /// No layers. Occluders without any layers don't block any lights.
+    pub const NONE: Self = Self(0);
+
+    /// Returns [`OcclusionLayers`] containing only the given layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is not less than [`OcclusionLayers::TOTAL_LAYERS`].
+    pub const fn layer(layer: usize) -> Self {
+        Self::NONE.with(layer)
+    }
+
+    /// Returns these layers with the given layer added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is not less than [`OcclusionLayers::TOTAL_LAYERS`].
+    #[must_use]
+    pub const fn with(self, layer: usize) -> Self {
+        assert!(
+            layer < Self::TOTAL_LAYERS,
+            "occlusion layer is out of range"
+        );
+        Self(self.0 | (1 << layer))
+    }
+
+    /// Returns these layers with the given layer removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `layer` is not less than [`OcclusionLayers::TOTAL_LAYERS`].
+    #[must_use]
+    pub const fn without(self, layer: usize) -> Self {
+        assert!(
+            layer < Self::TOTAL_LAYERS,
+            "occlusion layer is out of range"
+        );
+        Self(self.0 & !(1 << layer))
+    }
+
+    /// Returns `true` if these layers share at least one layer with `other`.
+    pub const fn intersects(&self, other: &Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl Default for OcclusionLayers {
+    /// Returns [`OcclusionLayers::ALL`], so that occluders block every light.
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Restricts a [`LightOccluder2d`] to only block the listed lights.
+///
+/// This is useful for precise cases which [`OcclusionLayers`] are too broad for, such as a
+/// shield which only blocks the player's flashlight. Lights must pass both this filter and the
+/// occluder's [`OcclusionLayers`] to be blocked. Lights which have been despawned are ignored.
+///
+/// [`LightOccluder2d`]: crate::occluder::LightOccluder2d
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct OccluderLightFilter {
+    /// The light entities the occluder blocks. An empty list blocks no lights.
+    pub blocks_only: Vec<Entity>,
+}
+
+impl OccluderLightFilter {
+    /// Returns an [`OccluderLightFilter`] which only blocks the given lights.
+    pub fn blocks_only(lights: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            blocks_only: lights.into_iter().collect(),
+        }
+    }
+
+    /// Returns `true` if the occluder blocks the given light.
+    pub fn blocks(&self, light: Entity) -> bool {
+        self.blocks_only.contains(&light)
+    }
+}
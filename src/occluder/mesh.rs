@@ -0,0 +1,269 @@
+use bevy::{
+    asset::{AssetEvent, Assets},
+    ecs::{
+        component::Component,
+        message::MessageReader,
+        query::With,
+        system::{Query, Res},
+        world::Ref,
+    },
+    log::warn_once,
+    math::Vec2,
+    mesh::{Mesh, Mesh2d},
+    platform::collections::{HashMap, HashSet},
+    render::render_resource::PrimitiveTopology,
+};
+use smallvec::SmallVec;
+
+use super::{
+    LightOccluder2d, LightOccluder2dCompoundShape, LightOccluder2dShape, triangulation::signed_area,
+};
+
+/// A marker component which keeps an entity's [`LightOccluder2d`] shapes in sync with its
+/// [`Mesh2d`].
+///
+/// The occluder's shapes are regenerated with [`LightOccluder2d::from_mesh`] whenever the mesh
+/// handle or mesh asset changes. Other occluder properties, such as its opacity, are left
+/// untouched.
+#[derive(Component, Default)]
+#[require(LightOccluder2d)]
+pub struct OccludeFromMesh2d;
+
+impl LightOccluder2d {
+    /// Returns a [`LightOccluder2d`] matching the 2d outline of a mesh's triangles, or `None` if
+    /// the mesh has no triangles.
+    ///
+    /// Each separate island of the mesh becomes its own polygon shape. Islands with holes can't
+    /// be represented by a single polygon, so are made up of their triangles instead. Only
+    /// meshes with a [`PrimitiveTopology::TriangleList`] topology are supported.
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        let mut shapes = mesh_shapes(mesh).into_iter();
+
+        Some(Self {
+            shape: shapes.next()?,
+            compound_shapes: shapes
+                .map(|shape| LightOccluder2dCompoundShape {
+                    shape,
+                    offset: Vec2::ZERO,
+                })
+                .collect(),
+            ..Self::default()
+        })
+    }
+}
+
+pub(crate) fn sync_mesh_occluders(
+    mut mesh_events: MessageReader<AssetEvent<Mesh>>,
+    meshes: Res<Assets<Mesh>>,
+    mut occluders: Query<(Ref<Mesh2d>, &mut LightOccluder2d), With<OccludeFromMesh2d>>,
+) {
+    let changed_meshes: HashSet<_> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (mesh_2d, mut occluder) in &mut occluders {
+        if !mesh_2d.is_changed() && !changed_meshes.contains(&mesh_2d.0.id()) {
+            continue;
+        }
+
+        // Meshes that haven't loaded yet will be picked up by their asset event.
+        let Some(mesh) = meshes.get(&mesh_2d.0) else {
+            continue;
+        };
+
+        let mesh_occluder = LightOccluder2d::from_mesh(mesh).unwrap_or_default();
+        occluder.shape = mesh_occluder.shape;
+        occluder.compound_shapes = mesh_occluder.compound_shapes;
+    }
+}
+
+/// Splits a mesh into islands of connected triangles, returning the shapes which make up
+/// each island.
+fn mesh_shapes(mesh: &Mesh) -> Vec<LightOccluder2dShape> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        warn_once!(
+            "Only meshes with a triangle list topology can be used as occluders, but a mesh \
+            has a {:?} topology.",
+            mesh.primitive_topology()
+        );
+        return Vec::new();
+    }
+
+    let Some(positions) = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(|positions| positions.as_float3())
+    else {
+        warn_once!(
+            "A mesh used as an occluder has no positions in the main world. Make sure the \
+            mesh's asset usage includes the main world."
+        );
+        return Vec::new();
+    };
+
+    // Meshes often duplicate vertices with the same position (for example, to give faces
+    // different normals), so vertices are welded by position to find shared edges.
+    let mut welded: HashMap<[u32; 2], usize> = HashMap::default();
+    let mut vertices = Vec::new();
+    let vertex_ids: Vec<usize> = positions
+        .iter()
+        .map(|&[x, y, _]| {
+            *welded.entry([x.to_bits(), y.to_bits()]).or_insert_with(|| {
+                vertices.push(Vec2::new(x, y));
+                vertices.len() - 1
+            })
+        })
+        .collect();
+
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    // Triangles are wound counter-clockwise, so that boundary edges run counter-clockwise
+    // around the outside of an island, and clockwise around its holes.
+    let triangles: Vec<[usize; 3]> = indices
+        .chunks_exact(3)
+        .filter_map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| vertex_ids.get(triangle[i]).copied());
+            let [a, b, c] = [a?, b?, c?];
+            let area = (vertices[b] - vertices[a]).perp_dot(vertices[c] - vertices[a]);
+
+            if area > 0.0 {
+                Some([a, b, c])
+            } else if area < 0.0 {
+                Some([a, c, b])
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let islands = connected_islands(&triangles, vertices.len());
+    let mut shapes = Vec::new();
+
+    for island in islands {
+        let loops = boundary_loops(&island, &vertices);
+        let outer_loops = loops
+            .iter()
+            .filter(|boundary| signed_area(boundary) > 0.0)
+            .count();
+
+        if let ([boundary], 1) = (loops.as_slice(), outer_loops) {
+            shapes.push(LightOccluder2dShape::Polygon(boundary.clone()));
+            continue;
+        }
+
+        shapes.extend(
+            island
+                .iter()
+                .map(|&[a, b, c]| LightOccluder2dShape::Triangle {
+                    a: vertices[a],
+                    b: vertices[b],
+                    c: vertices[c],
+                }),
+        );
+    }
+
+    shapes
+}
+
+/// Groups triangles into islands, where triangles sharing a vertex belong to the same island.
+fn connected_islands(triangles: &[[usize; 3]], vertex_count: usize) -> Vec<Vec<[usize; 3]>> {
+    let mut parents: Vec<usize> = (0..vertex_count).collect();
+
+    for &[a, b, c] in triangles {
+        for other in [b, c] {
+            let (root_a, root_other) = (find_root(&mut parents, a), find_root(&mut parents, other));
+            parents[root_other] = root_a;
+        }
+    }
+
+    let mut islands: HashMap<usize, Vec<[usize; 3]>> = HashMap::default();
+    let mut order = Vec::new();
+    for &triangle in triangles {
+        let root = find_root(&mut parents, triangle[0]);
+        islands
+            .entry(root)
+            .or_insert_with(|| {
+                order.push(root);
+                Vec::new()
+            })
+            .push(triangle);
+    }
+
+    order
+        .into_iter()
+        .filter_map(|root| islands.remove(&root))
+        .collect()
+}
+
+/// Finds the root of a vertex's island, flattening the path to it along the way.
+fn find_root(parents: &mut [usize], mut vertex: usize) -> usize {
+    while parents[vertex] != vertex {
+        parents[vertex] = parents[parents[vertex]];
+        vertex = parents[vertex];
+    }
+    vertex
+}
+
+/// Returns the closed loops of edges which aren't shared between two triangles, with
+/// collinear vertices removed.
+fn boundary_loops(triangles: &[[usize; 3]], vertices: &[Vec2]) -> Vec<Vec<Vec2>> {
+    let edges: HashSet<(usize, usize)> = triangles
+        .iter()
+        .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+        .collect();
+
+    let mut next: HashMap<usize, SmallVec<[usize; 1]>> = HashMap::default();
+    for &(a, b) in &edges {
+        if !edges.contains(&(b, a)) {
+            next.entry(a).or_default().push(b);
+        }
+    }
+
+    let mut loops = Vec::new();
+    while let Some(&start) = next.keys().next() {
+        let mut boundary = Vec::new();
+        let mut current = start;
+
+        while let Some(targets) = next.get_mut(&current) {
+            let Some(target) = targets.pop() else {
+                break;
+            };
+            if targets.is_empty() {
+                next.remove(&current);
+            }
+
+            boundary.push(vertices[current]);
+            current = target;
+            if current == start {
+                break;
+            }
+        }
+
+        let boundary = remove_collinear(boundary);
+        if boundary.len() >= 3 {
+            loops.push(boundary);
+        }
+    }
+
+    loops
+}
+
+fn remove_collinear(boundary: Vec<Vec2>) -> Vec<Vec2> {
+    let len = boundary.len();
+    (0..len)
+        .filter(|&i| {
+            let previous = boundary[(i + len - 1) % len];
+            let next = boundary[(i + 1) % len];
+            (boundary[i] - previous).perp_dot(next - boundary[i]) != 0.0
+        })
+        .map(|i| boundary[i])
+        .collect()
+}
@@ -0,0 +1,316 @@
+//! A module which contains occluder components.
+
+use bevy::{
+    asset::Handle,
+    camera::visibility::{self, InheritedVisibility, ViewVisibility, Visibility, VisibilityClass},
+    color::Color,
+    ecs::{bundle::Bundle, component::Component},
+    image::Image,
+    math::Vec2,
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+    render::sync_world::SyncToRenderWorld,
+    transform::components::{GlobalTransform, Transform},
+};
+use smallvec::SmallVec;
+
+mod bounds;
+mod frames;
+mod layers;
+mod mesh;
+mod primitive;
+pub mod tilemap;
+pub(crate) mod triangulation;
+
+pub use bounds::OccluderCulling;
+pub(crate) use bounds::calculate_occluder_bounds;
+pub use frames::OccluderFrames;
+pub(crate) use frames::update_occluder_frames;
+pub use layers::{OccluderLightFilter, OcclusionLayers};
+pub use mesh::OccludeFromMesh2d;
+pub(crate) use mesh::sync_mesh_occluders;
+pub(crate) use tilemap::update_tilemap_occluders;
+
+/// The maximum number of vertices supported by [`LightOccluder2dShape::ConvexPolygon`].
+pub const MAX_CONVEX_POLYGON_VERTICES: usize = 16;
+
+/// A light occluder that prevents light passing through it, casting shadows.
+///
+/// This is commonly used as a component within [`LightOcluder2dBundle`].
+///
+/// An occluder with `RenderLayers` only casts shadows in the views of cameras that share one of
+/// its first 32 layers, while an occluder without `RenderLayers` casts shadows in every view.
+#[derive(Component)]
+#[require(SyncToRenderWorld, Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<LightOccluder2d>)]
+pub struct LightOccluder2d {
+    /// The shape of the light occluder.
+    pub shape: LightOccluder2dShape,
+    /// Additional shapes which make up the light occluder, such as the canopy of a tree
+    /// whose trunk is the main `shape`.
+    ///
+    /// Every shape shares the occluder's transform and visibility.
+    pub compound_shapes: SmallVec<[LightOccluder2dCompoundShape; 1]>,
+    /// The offset of the occluder's shapes from the entity's origin, in local space.
+    ///
+    /// The offset is rotated and scaled with the entity's transform, which places an occluder
+    /// away from a sprite's anchor without spawning a child entity, such as a tree's trunk
+    /// above the base of its sprite.
+    pub offset: Vec2,
+    /// How much light the occluder blocks, between `0.0` and `1.0`.
+    ///
+    /// An opacity of `1.0` blocks all light, while lower values let some light through, which
+    /// is useful for materials like frosted glass or foliage. An opacity of `0.0` blocks no
+    /// light at all.
+    pub opacity: f32,
+    /// The color of light that passes through the occluder, like a pane of stained glass.
+    ///
+    /// A red tint lets red light through, while blocking the rest, scaled by the occluder's
+    /// opacity. Light passing through multiple tinted occluders is filtered by each of them.
+    pub tint: Option<Color>,
+    /// Whether the occluder only blocks light arriving from its front side.
+    ///
+    /// One-sided occluders let light from behind them pass through, which is useful for walls
+    /// that should block light from outside a room, while letting light from inside spill out.
+    pub one_sided: bool,
+    /// The local space direction that a one-sided occluder faces. Light arriving from this
+    /// direction is blocked.
+    ///
+    /// The direction is rotated with the occluder's transform. This has no effect unless
+    /// `one_sided` is `true`.
+    pub facing: Vec2,
+    /// Whether the occluder blocks light.
+    ///
+    /// Unlike hiding the occluder's entity, disabling the occluder doesn't affect anything
+    /// else on the entity, such as its sprite.
+    pub enabled: bool,
+    /// How soft the edges of the occluder's shadows are.
+    ///
+    /// Light passing within this distance (in world units) of the occluder is partially
+    /// blocked, widening the shadow's penumbra. A softness of `0.0` results in hard shadows.
+    pub softness: f32,
+    /// How tall the occluder is relative to the lights, for pseudo-3D shadows.
+    ///
+    /// An occluder with a height casts a shadow `height` times as long as its distance from the
+    /// light, so shadows lengthen as lights move away and as the height grows. A height of
+    /// `0.0` casts shadows of unlimited length.
+    pub height: f32,
+    /// The furthest distance (in world units) from the occluder that its shadows reach.
+    ///
+    /// Shadows fade out as they approach this distance, leaving light beyond it unaffected.
+    /// `None` casts shadows of unlimited length.
+    pub max_shadow_distance: Option<f32>,
+    /// The color of the occluder's shadows, like an ice wall casting a bluish shadow.
+    ///
+    /// Rather than being removed, light blocked by the occluder is modulated by this color.
+    /// `None` casts black shadows.
+    pub shadow_color: Option<Color>,
+    /// Whether only the occluder's border blocks light, like a glass dome or force field.
+    ///
+    /// Light passes freely through the interior of hollow occluders, so lights inside them
+    /// illuminate the inside normally.
+    pub hollow: bool,
+    /// The thickness (in world units) of a hollow occluder's border, centered on the edge of
+    /// its shape.
+    pub border_thickness: f32,
+    /// The distance (in world units) the occluder's shape is inflated by, or shrunk by if
+    /// negative.
+    ///
+    /// Occluders sized exactly to their sprites can leak light through the seams between
+    /// them, which a small positive margin closes.
+    pub margin: f32,
+    /// The fraction of light absorbed per world unit travelled through the occluder, from
+    /// `0.0` to `1.0`, like a fog bank or deep water.
+    ///
+    /// Unlike opacity, which is applied once as light enters the occluder, absorption lets
+    /// more light through thin sections of the occluder than thick ones. Set the opacity to
+    /// `0.0` for occluders that only absorb light.
+    pub absorption: f32,
+}
+
+impl Default for LightOccluder2d {
+    /// Returns a fully opaque [`LightOccluder2d`] with an empty rectangle shape.
+    fn default() -> Self {
+        Self {
+            shape: LightOccluder2dShape::default(),
+            compound_shapes: SmallVec::new(),
+            offset: Vec2::ZERO,
+            opacity: 1.0,
+            tint: None,
+            one_sided: false,
+            facing: Vec2::Y,
+            enabled: true,
+            softness: 0.0,
+            height: 0.0,
+            max_shadow_distance: None,
+            shadow_color: None,
+            hollow: false,
+            border_thickness: 1.0,
+            margin: 0.0,
+            absorption: 0.0,
+        }
+    }
+}
+
+/// An additional shape of a compound [`LightOccluder2d`].
+#[derive(Clone, Default)]
+pub struct LightOccluder2dCompoundShape {
+    /// The shape.
+    pub shape: LightOccluder2dShape,
+    /// The offset of the shape from the light occluder's origin, in local space. This is in
+    /// addition to the occluder's `offset`.
+    pub offset: Vec2,
+}
+
+/// Marks a [`LightOccluder2d`] which never moves, such as a wall or a tile.
+///
+/// Static occluders are uploaded to a separate buffer on the GPU, which is only rebuilt when
+/// a static occluder is added, changed, removed, or comes into or out of view. Every other
+/// occluder is uploaded every frame, so marking the occluders that don't move greatly reduces
+/// the cost of scenes with many occluders.
+///
+/// Moving a static occluder still works, but rebuilds the static buffer, so a warning is
+/// logged. Remove this component from occluders that move.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+#[require(LightOccluder2d)]
+pub struct StaticLightOccluder2d;
+
+/// Shape data for a light occluder.
+#[derive(Clone)]
+pub enum LightOccluder2dShape {
+    /// A rectangular light occluder.
+    Rectangle {
+        /// Half of the width and height of the rectangle.
+        half_size: Vec2,
+    },
+    /// A rectangular light occluder with rounded corners.
+    ///
+    /// The corner radius is clamped between `0.0` and the smallest component of `half_size`.
+    /// A corner radius of `0.0` is identical to [`LightOccluder2dShape::Rectangle`].
+    RoundedRectangle {
+        /// Half of the width and height of the rectangle.
+        half_size: Vec2,
+        /// The radius of the rectangle's corners.
+        corner_radius: f32,
+    },
+    /// A capsule light occluder, aligned with the local y axis.
+    ///
+    /// A capsule with a `half_length` of `0.0` is a circle.
+    Capsule {
+        /// Half of the length of the capsule's inner segment.
+        half_length: f32,
+        /// The radius of the capsule.
+        radius: f32,
+    },
+    /// An elliptical light occluder.
+    ///
+    /// An ellipse with equal radii is a circle.
+    Ellipse {
+        /// The horizontal and vertical radii of the ellipse.
+        half_size: Vec2,
+    },
+    /// A ring shaped light occluder, which only occludes between its inner and outer radius.
+    ///
+    /// Lights inside the ring are occluded by the ring, rather than by the interior.
+    Annulus {
+        /// The radius of the ring's open interior. This must not be larger than `outer_radius`.
+        inner_radius: f32,
+        /// The outer radius of the ring.
+        outer_radius: f32,
+    },
+    /// A triangular light occluder, defined by its vertices in local space.
+    ///
+    /// Degenerate triangles, where all three vertices are collinear, are treated as a
+    /// [`LightOccluder2dShape::Segment`].
+    Triangle {
+        /// The first vertex of the triangle.
+        a: Vec2,
+        /// The second vertex of the triangle.
+        b: Vec2,
+        /// The third vertex of the triangle.
+        c: Vec2,
+    },
+    /// A convex polygon light occluder, defined by its vertices in local space.
+    ///
+    /// At most [`MAX_CONVEX_POLYGON_VERTICES`] vertices are supported; any vertices beyond
+    /// that limit are ignored. A polygon with two vertices is treated as a
+    /// [`LightOccluder2dShape::Segment`].
+    ConvexPolygon(Vec<Vec2>),
+    /// A line segment light occluder, such as a thin wall or fence, defined by its endpoints in
+    /// local space.
+    ///
+    /// Segments occlude light from both sides.
+    Segment {
+        /// The start of the segment.
+        start: Vec2,
+        /// The end of the segment.
+        end: Vec2,
+    },
+    /// A simple polygon light occluder, which may be concave, defined by its vertices in
+    /// local space.
+    ///
+    /// The polygon is decomposed into triangles when first extracted, and again whenever the
+    /// occluder changes. Self-intersecting polygons can't be decomposed, and will instead
+    /// occlude using the polygon's bounding box.
+    Polygon(Vec<Vec2>),
+    /// A light occluder matching the silhouette of an image, such as a sprite's texture.
+    ///
+    /// Pixels with an alpha of at least `threshold` occlude light. The occluder covers the
+    /// image's size in pixels, centered on the occluder's origin, like a sprite without a
+    /// custom size.
+    ///
+    /// The image must be kept in the main world, and is baked into a distance field the first
+    /// time it's used with each threshold. Images that haven't loaded yet don't occlude light.
+    AlphaMask {
+        /// The image whose alpha channel is the occluder's silhouette.
+        image: Handle<Image>,
+        /// The alpha, between `0.0` and `1.0`, at or above which pixels occlude light.
+        threshold: f32,
+    },
+    /// A light occluder defined by a signed distance field texture, such as one authored
+    /// offline for complex static geometry like an entire cave system.
+    ///
+    /// The image's red channel stores the signed distance to the nearest surface, in the same
+    /// units as `world_size`, and negative inside. The occluder covers `world_size`, centered on
+    /// the occluder's origin. Use a floating point texture format so that distances aren't
+    /// clamped.
+    ///
+    /// Only a single distance field image can be used at a time. Occluders whose image hasn't
+    /// loaded yet don't occlude light.
+    Sdf {
+        /// The signed distance field texture, with distances in its red channel.
+        image: Handle<Image>,
+        /// The width and height of the region the texture covers, in world units.
+        world_size: Vec2,
+    },
+}
+
+impl Default for LightOccluder2dShape {
+    fn default() -> Self {
+        Self::Rectangle {
+            half_size: Vec2::splat(0.0),
+        }
+    }
+}
+
+/// A bundle of components for rendering a [`LightOccluder2d`] entity.
+#[derive(Bundle, Default)]
+#[deprecated(
+    since = "0.5.0",
+    note = "Use the `LightOccluder2d` component instead. Inserting `LightOccluder2d` will also insert the other components required automatically."
+)]
+pub struct LightOccluder2dBundle {
+    /// Specifies the rendering properties of the light occluder
+    pub light_occluder: LightOccluder2d,
+    /// The local transform of the light occluder, relative to its parent.
+    pub transform: Transform,
+    /// The absolute transform of the light occluder. This should generally not be written to directly.
+    pub global_transform: GlobalTransform,
+    /// User indication of whether an entity is visible.
+    pub visibility: Visibility,
+    /// Inherited visibility of an entity.
+    pub inherited_visibility: InheritedVisibility,
+    /// Algorithmically-computed indication of whether an entity is visible and should be extracted for rendering.
+    pub view_visibility: ViewVisibility,
+}
@@ -0,0 +1,208 @@
+use bevy::math::{
+    Vec2,
+    primitives::{
+        Annulus, Capsule2d, Circle, Ellipse, Polygon, Primitive2d, Rectangle, RegularPolygon,
+        Segment2d, Triangle2d,
+    },
+};
+
+use super::{LightOccluder2d, LightOccluder2dShape};
+
+impl LightOccluder2d {
+    /// Returns a fully opaque [`LightOccluder2d`] with the shape of a 2d primitive.
+    ///
+    /// See the [`From`] implementations of [`LightOccluder2dShape`] for the supported
+    /// primitives.
+    pub fn from_primitive(primitive: impl Primitive2d + Into<LightOccluder2dShape>) -> Self {
+        Self {
+            shape: primitive.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<Rectangle> for LightOccluder2dShape {
+    fn from(rectangle: Rectangle) -> Self {
+        Self::Rectangle {
+            half_size: rectangle.half_size,
+        }
+    }
+}
+
+impl From<Circle> for LightOccluder2dShape {
+    /// Circles are ellipses with equal radii.
+    fn from(circle: Circle) -> Self {
+        Self::Ellipse {
+            half_size: Vec2::splat(circle.radius),
+        }
+    }
+}
+
+impl From<Ellipse> for LightOccluder2dShape {
+    fn from(ellipse: Ellipse) -> Self {
+        Self::Ellipse {
+            half_size: ellipse.half_size,
+        }
+    }
+}
+
+impl From<Capsule2d> for LightOccluder2dShape {
+    fn from(capsule: Capsule2d) -> Self {
+        Self::Capsule {
+            half_length: capsule.half_length,
+            radius: capsule.radius,
+        }
+    }
+}
+
+impl From<Annulus> for LightOccluder2dShape {
+    fn from(annulus: Annulus) -> Self {
+        Self::Annulus {
+            inner_radius: annulus.inner_circle.radius,
+            outer_radius: annulus.outer_circle.radius,
+        }
+    }
+}
+
+impl From<Triangle2d> for LightOccluder2dShape {
+    fn from(triangle: Triangle2d) -> Self {
+        let [a, b, c] = triangle.vertices;
+        Self::Triangle { a, b, c }
+    }
+}
+
+impl From<Segment2d> for LightOccluder2dShape {
+    /// Segments are given a small thickness, so the occluder is slightly wider than the
+    /// segment.
+    fn from(segment: Segment2d) -> Self {
+        let [start, end] = segment.vertices;
+        Self::Segment { start, end }
+    }
+}
+
+impl From<RegularPolygon> for LightOccluder2dShape {
+    /// Regular polygons with more than [`MAX_CONVEX_POLYGON_VERTICES`] sides are truncated
+    /// when extracted, so should be approximated with an [`Ellipse`] instead.
+    ///
+    /// [`MAX_CONVEX_POLYGON_VERTICES`]: super::MAX_CONVEX_POLYGON_VERTICES
+    fn from(polygon: RegularPolygon) -> Self {
+        Self::ConvexPolygon(polygon.vertices(0.0).into_iter().collect())
+    }
+}
+
+impl From<Polygon> for LightOccluder2dShape {
+    fn from(polygon: Polygon) -> Self {
+        Self::Polygon(polygon.vertices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_primitive_is_opaque() {
+        let occluder = LightOccluder2d::from_primitive(Circle::new(3.0));
+
+        assert_eq!(occluder.opacity, 1.0);
+        assert!(matches!(
+            occluder.shape,
+            LightOccluder2dShape::Ellipse { half_size } if half_size == Vec2::splat(3.0)
+        ));
+    }
+
+    #[test]
+    fn from_rectangle() {
+        assert!(matches!(
+            LightOccluder2dShape::from(Rectangle::new(4.0, 2.0)),
+            LightOccluder2dShape::Rectangle { half_size } if half_size == Vec2::new(2.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn from_circle() {
+        assert!(matches!(
+            LightOccluder2dShape::from(Circle::new(5.0)),
+            LightOccluder2dShape::Ellipse { half_size } if half_size == Vec2::splat(5.0)
+        ));
+    }
+
+    #[test]
+    fn from_ellipse() {
+        assert!(matches!(
+            LightOccluder2dShape::from(Ellipse::new(3.0, 1.0)),
+            LightOccluder2dShape::Ellipse { half_size } if half_size == Vec2::new(3.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn from_capsule() {
+        assert!(matches!(
+            LightOccluder2dShape::from(Capsule2d::new(1.0, 6.0)),
+            LightOccluder2dShape::Capsule {
+                half_length,
+                radius,
+            } if half_length == 3.0 && radius == 1.0
+        ));
+    }
+
+    #[test]
+    fn from_annulus() {
+        assert!(matches!(
+            LightOccluder2dShape::from(Annulus::new(2.0, 5.0)),
+            LightOccluder2dShape::Annulus {
+                inner_radius,
+                outer_radius,
+            } if inner_radius == 2.0 && outer_radius == 5.0
+        ));
+    }
+
+    #[test]
+    fn from_triangle() {
+        let (a, b, c) = (Vec2::ZERO, Vec2::X, Vec2::Y);
+
+        assert!(matches!(
+            LightOccluder2dShape::from(Triangle2d::new(a, b, c)),
+            LightOccluder2dShape::Triangle { a: ta, b: tb, c: tc }
+                if (ta, tb, tc) == (a, b, c)
+        ));
+    }
+
+    #[test]
+    fn from_segment() {
+        let (start, end) = (Vec2::new(-1.0, 0.0), Vec2::new(2.0, 3.0));
+
+        assert!(matches!(
+            LightOccluder2dShape::from(Segment2d::new(start, end)),
+            LightOccluder2dShape::Segment { start: s, end: e } if (s, e) == (start, end)
+        ));
+    }
+
+    #[test]
+    fn from_regular_polygon() {
+        let polygon = RegularPolygon::new(2.0, 6);
+
+        let LightOccluder2dShape::ConvexPolygon(vertices) = LightOccluder2dShape::from(polygon)
+        else {
+            panic!("regular polygons should be convex polygon occluders");
+        };
+        assert_eq!(vertices.len(), 6);
+        assert!(
+            vertices
+                .iter()
+                .all(|vertex| (vertex.length() - 2.0).abs() < 1e-5)
+        );
+    }
+
+    #[test]
+    fn from_polygon() {
+        let vertices = [Vec2::ZERO, Vec2::X, Vec2::ONE, Vec2::new(0.5, 0.5), Vec2::Y];
+
+        let LightOccluder2dShape::Polygon(polygon) =
+            LightOccluder2dShape::from(Polygon::new(vertices))
+        else {
+            panic!("polygons should be polygon occluders");
+        };
+        assert_eq!(polygon, vertices);
+    }
+}
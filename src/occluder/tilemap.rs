@@ -0,0 +1,323 @@
+//! A module which contains occluders for tilemaps.
+//!
+//! Spawning an occluder per solid tile quickly adds up to thousands of occluders. A
+//! [`TilemapOccluder`] instead merges neighbouring solid tiles into as few rectangular
+//! occluders as it can, and only rebuilds the occluders around tiles that change.
+
+use bevy::{
+    camera::visibility::Visibility,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        hierarchy::{ChildOf, Children},
+        query::Changed,
+        system::{Commands, Query},
+    },
+    math::{URect, UVec2, Vec2},
+    transform::components::Transform,
+};
+
+use super::{LightOccluder2d, LightOccluder2dShape};
+
+/// A grid of solid tiles, whose occluders are spawned as children of its entity.
+///
+/// Tile `(0, 0)` has its bottom left corner at the entity's origin, with x increasing to the
+/// right and y increasing upwards. Solid tiles are merged into rectangular [`LightOccluder2d`]s
+/// with [`merge_tiles`]. When tiles are changed with [`TilemapOccluder::set`], only the
+/// occluders overlapping the changed tiles are respawned.
+#[derive(Component)]
+#[require(Transform, Visibility)]
+pub struct TilemapOccluder {
+    size: UVec2,
+    tile_size: Vec2,
+    solid: Vec<bool>,
+    // The tiles which have changed since the occluders were last spawned.
+    dirty: Option<URect>,
+}
+
+impl TilemapOccluder {
+    /// Returns an empty [`TilemapOccluder`] with the given size in tiles, and tile size in
+    /// world units.
+    pub fn new(size: UVec2, tile_size: Vec2) -> Self {
+        Self {
+            size,
+            tile_size,
+            solid: vec![false; (size.x * size.y) as usize],
+            dirty: Some(URect::from_corners(UVec2::ZERO, size)),
+        }
+    }
+
+    /// Returns a [`TilemapOccluder`] from rows of tiles, where `true` is solid.
+    ///
+    /// The first row is at the bottom of the tilemap. Rows shorter than the longest row are
+    /// padded with empty tiles.
+    pub fn from_rows<R: AsRef<[bool]>>(rows: &[R], tile_size: Vec2) -> Self {
+        let width = rows.iter().map(|row| row.as_ref().len()).max().unwrap_or(0);
+        let mut tilemap = Self::new(UVec2::new(width as u32, rows.len() as u32), tile_size);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &solid) in row.as_ref().iter().enumerate() {
+                tilemap.set(UVec2::new(x as u32, y as u32), solid);
+            }
+        }
+
+        tilemap
+    }
+
+    /// The size of the tilemap, in tiles.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// The size of each tile, in world units.
+    pub fn tile_size(&self) -> Vec2 {
+        self.tile_size
+    }
+
+    /// Returns whether the tile at `position` is solid. Tiles outside of the tilemap are
+    /// empty.
+    pub fn is_solid(&self, position: UVec2) -> bool {
+        position.cmplt(self.size).all() && self.solid[self.index(position)]
+    }
+
+    /// Sets whether the tile at `position` is solid, marking its occluders to be respawned if
+    /// it changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `position` is outside of the tilemap.
+    pub fn set(&mut self, position: UVec2, solid: bool) {
+        assert!(
+            position.cmplt(self.size).all(),
+            "tile position {position} is outside of the tilemap"
+        );
+
+        let index = self.index(position);
+        if self.solid[index] == solid {
+            return;
+        }
+        self.solid[index] = solid;
+
+        let tile = URect::from_corners(position, position + 1);
+        self.dirty = Some(self.dirty.map_or(tile, |dirty| dirty.union(tile)));
+    }
+
+    fn index(&self, position: UVec2) -> usize {
+        (position.y * self.size.x + position.x) as usize
+    }
+}
+
+/// A rectangular occluder spawned by a [`TilemapOccluder`], covering a range of its tiles.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TileOccluder {
+    /// The tiles covered by the occluder. The maximum corner is exclusive.
+    pub tiles: URect,
+}
+
+/// Merges solid tiles into rectangles using greedy meshing, returning the tiles covered by
+/// each rectangle. The maximum corner of each rectangle is exclusive.
+///
+/// Rows are scanned from the bottom, and each rectangle is grown as wide as it can, and then
+/// as tall as it can. Every solid tile is covered by exactly one rectangle.
+pub fn merge_tiles(size: UVec2, is_solid: impl Fn(UVec2) -> bool) -> Vec<URect> {
+    let mut covered = vec![false; (size.x * size.y) as usize];
+    let index = |position: UVec2| (position.y * size.x + position.x) as usize;
+    let is_free =
+        |covered: &[bool], position: UVec2| is_solid(position) && !covered[index(position)];
+
+    let mut rects = Vec::new();
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let min = UVec2::new(x, y);
+            if !is_free(&covered, min) {
+                continue;
+            }
+
+            let mut max = min + 1;
+            while max.x < size.x && is_free(&covered, UVec2::new(max.x, y)) {
+                max.x += 1;
+            }
+            while max.y < size.y && (min.x..max.x).all(|x| is_free(&covered, UVec2::new(x, max.y)))
+            {
+                max.y += 1;
+            }
+
+            for y in min.y..max.y {
+                for x in min.x..max.x {
+                    covered[index(UVec2::new(x, y))] = true;
+                }
+            }
+
+            rects.push(URect::from_corners(min, max));
+        }
+    }
+
+    rects
+}
+
+pub(crate) fn update_tilemap_occluders(
+    mut commands: Commands,
+    mut tilemaps: Query<
+        (Entity, &mut TilemapOccluder, Option<&Children>),
+        Changed<TilemapOccluder>,
+    >,
+    tile_occluders: Query<&TileOccluder>,
+) {
+    for (entity, mut tilemap, children) in &mut tilemaps {
+        let Some(dirty) = tilemap.bypass_change_detection().dirty.take() else {
+            continue;
+        };
+
+        // Occluders overlapping the changed tiles are respawned. Their tiles need to be
+        // covered again, along with the changed tiles.
+        let mut respawn = vec![false; tilemap.solid.len()];
+        let mut mark = |tiles: URect| {
+            for y in tiles.min.y..tiles.max.y.min(tilemap.size.y) {
+                for x in tiles.min.x..tiles.max.x.min(tilemap.size.x) {
+                    respawn[tilemap.index(UVec2::new(x, y))] = true;
+                }
+            }
+        };
+        mark(dirty);
+
+        for child in children.into_iter().flatten() {
+            let Ok(tile_occluder) = tile_occluders.get(*child) else {
+                continue;
+            };
+
+            if overlaps(tile_occluder.tiles, dirty) {
+                mark(tile_occluder.tiles);
+                commands.entity(*child).despawn();
+            }
+        }
+
+        let tile_size = tilemap.tile_size;
+        for tiles in merge_tiles(tilemap.size, |position| {
+            respawn[tilemap.index(position)] && tilemap.is_solid(position)
+        }) {
+            let half_size = tiles.size().as_vec2() * tile_size * 0.5;
+            let center = tiles.min.as_vec2() * tile_size + half_size;
+
+            commands.spawn((
+                TileOccluder { tiles },
+                LightOccluder2d {
+                    shape: LightOccluder2dShape::Rectangle { half_size },
+                    ..LightOccluder2d::default()
+                },
+                Transform::from_translation(center.extend(0.0)),
+                ChildOf(entity),
+            ));
+        }
+    }
+}
+
+/// Returns whether two tile ranges, with exclusive maximum corners, share any tiles.
+fn overlaps(a: URect, b: URect) -> bool {
+    a.min.cmplt(b.max).all() && b.min.cmplt(a.max).all()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::app::{App, Update};
+
+    use super::*;
+
+    /// Asserts that every solid tile is covered by exactly one rectangle, and that every
+    /// rectangle only covers solid tiles.
+    fn assert_covers_once(tilemap: &TilemapOccluder, rects: &[URect]) {
+        for y in 0..tilemap.size.y {
+            for x in 0..tilemap.size.x {
+                let position = UVec2::new(x, y);
+                let covering = rects
+                    .iter()
+                    .filter(|rect| overlaps(**rect, URect::from_corners(position, position + 1)))
+                    .count();
+                let expected = usize::from(tilemap.is_solid(position));
+                assert_eq!(
+                    covering, expected,
+                    "tile {position} is covered {covering} times"
+                );
+            }
+        }
+    }
+
+    fn tile_occluders(app: &mut App) -> Vec<(Entity, URect)> {
+        app.world_mut()
+            .query::<(Entity, &TileOccluder)>()
+            .iter(app.world())
+            .map(|(entity, tile_occluder)| (entity, tile_occluder.tiles))
+            .collect()
+    }
+
+    #[test]
+    fn merge_tiles_merges_rectangles() {
+        let tilemap = TilemapOccluder::from_rows(
+            &[
+                [true, true, false, true],
+                [true, true, false, true],
+                [false, false, false, true],
+            ],
+            Vec2::ONE,
+        );
+
+        let rects = merge_tiles(tilemap.size, |position| tilemap.is_solid(position));
+
+        assert_eq!(rects, [URect::new(0, 0, 2, 2), URect::new(3, 0, 4, 3),]);
+        assert_covers_once(&tilemap, &rects);
+    }
+
+    #[test]
+    fn merge_tiles_covers_each_tile_once() {
+        let tilemap = TilemapOccluder::from_rows(
+            &[
+                [true, true, true, false, true],
+                [true, false, true, true, true],
+                [true, true, true, false, false],
+                [false, true, false, true, true],
+            ],
+            Vec2::ONE,
+        );
+
+        let rects = merge_tiles(tilemap.size, |position| tilemap.is_solid(position));
+
+        assert_covers_once(&tilemap, &rects);
+    }
+
+    #[test]
+    fn changed_tiles_only_respawn_overlapping_occluders() {
+        let mut app = App::new();
+        app.add_systems(Update, update_tilemap_occluders);
+
+        let tilemap = app
+            .world_mut()
+            .spawn(TilemapOccluder::from_rows(
+                &[[true, true, false, false, true, true]],
+                Vec2::splat(16.0),
+            ))
+            .id();
+        app.update();
+
+        let occluders = tile_occluders(&mut app);
+        assert_eq!(occluders.len(), 2);
+        let (left, _) = *occluders
+            .iter()
+            .find(|(_, tiles)| tiles.min.x == 0)
+            .unwrap();
+
+        // Removing a tile from the right occluder, and adding a tile beside the left one.
+        let mut tilemap_occluder = app.world_mut().get_mut::<TilemapOccluder>(tilemap).unwrap();
+        tilemap_occluder.set(UVec2::new(5, 0), false);
+        tilemap_occluder.set(UVec2::new(2, 0), true);
+        app.update();
+
+        let occluders = tile_occluders(&mut app);
+        let rects: Vec<URect> = occluders.iter().map(|(_, tiles)| *tiles).collect();
+        assert_covers_once(app.world().get::<TilemapOccluder>(tilemap).unwrap(), &rects);
+        assert!(
+            occluders
+                .iter()
+                .any(|(entity, tiles)| *entity == left && *tiles == URect::new(0, 0, 2, 1))
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::math::Vec2;
+
+/// Triangulates a simple polygon using ear clipping.
+///
+/// The polygon may be concave and use either winding order. Returns a flat list of triangle
+/// vertices (three per triangle), or `None` if the polygon is degenerate or self-intersecting.
+pub(crate) fn triangulate(vertices: &[Vec2]) -> Option<Vec<Vec2>> {
+    if vertices.len() < 3 || is_self_intersecting(vertices) {
+        return None;
+    }
+
+    let area = signed_area(vertices);
+    if area.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    // Work with counter-clockwise winding so that convex corners have a positive cross product.
+    let mut remaining: Vec<usize> = if area > 0.0 {
+        (0..vertices.len()).collect()
+    } else {
+        (0..vertices.len()).rev().collect()
+    };
+
+    let mut triangles = Vec::with_capacity((vertices.len() - 2) * 3);
+
+    while remaining.len() > 3 {
+        let ear = (0..remaining.len()).find(|&i| is_ear(vertices, &remaining, i))?;
+
+        let len = remaining.len();
+        triangles.extend([
+            vertices[remaining[(ear + len - 1) % len]],
+            vertices[remaining[ear]],
+            vertices[remaining[(ear + 1) % len]],
+        ]);
+        remaining.remove(ear);
+    }
+
+    triangles.extend(remaining.iter().map(|&i| vertices[i]));
+
+    Some(triangles)
+}
+
+fn is_ear(vertices: &[Vec2], remaining: &[usize], i: usize) -> bool {
+    let len = remaining.len();
+    let a = vertices[remaining[(i + len - 1) % len]];
+    let b = vertices[remaining[i]];
+    let c = vertices[remaining[(i + 1) % len]];
+
+    // Reflex (or collinear) corners can't be ears.
+    if (b - a).perp_dot(c - b) <= 0.0 {
+        return false;
+    }
+
+    remaining
+        .iter()
+        .map(|&index| vertices[index])
+        .filter(|&p| p != a && p != b && p != c)
+        .all(|p| !point_in_triangle(p, a, b, c))
+}
+
+fn point_in_triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    (b - a).perp_dot(p - a) >= 0.0
+        && (c - b).perp_dot(p - b) >= 0.0
+        && (a - c).perp_dot(p - c) >= 0.0
+}
+
+pub(crate) fn signed_area(vertices: &[Vec2]) -> f32 {
+    let len = vertices.len();
+    (0..len)
+        .map(|i| vertices[i].perp_dot(vertices[(i + 1) % len]))
+        .sum::<f32>()
+        * 0.5
+}
+
+fn is_self_intersecting(vertices: &[Vec2]) -> bool {
+    let len = vertices.len();
+
+    for i in 0..len {
+        for j in (i + 1)..len {
+            // Adjacent edges always share a vertex.
+            if j == i + 1 || (i == 0 && j == len - 1) {
+                continue;
+            }
+
+            if segments_intersect(
+                vertices[i],
+                vertices[(i + 1) % len],
+                vertices[j],
+                vertices[(j + 1) % len],
+            ) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+fn segments_intersect(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let d1 = (b - a).perp_dot(c - a);
+    let d2 = (b - a).perp_dot(d - a);
+    let d3 = (d - c).perp_dot(a - c);
+    let d4 = (d - c).perp_dot(b - c);
+
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
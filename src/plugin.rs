@@ -2,40 +2,87 @@
 
 use bevy::{
     asset::load_internal_asset,
+    camera::visibility::VisibilitySystems,
     core_pipeline::core_2d::graph::{Core2d, Node2d},
+    diagnostic::{Diagnostic, RegisterDiagnostic},
     prelude::*,
     render::{
-        Render, RenderApp, RenderSet, extract_component::UniformComponentPlugin,
-        gpu_component_array_buffer::GpuComponentArrayBufferPlugin, render_graph::RenderGraphExt,
-        render_graph::ViewNodeRunner, render_resource::SpecializedRenderPipelines,
+        Render, RenderApp, RenderSet,
+        extract_component::UniformComponentPlugin,
+        extract_resource::ExtractResourcePlugin,
+        gpu_component_array_buffer::GpuComponentArrayBufferPlugin,
+        render_graph::RenderGraphExt,
+        render_graph::ViewNodeRunner,
+        render_resource::{GpuArrayBuffer, SpecializedRenderPipelines},
+        renderer::RenderDevice,
         view::prepare_view_targets,
     },
 };
 
 use crate::{
-    light::{AmbientLight2d, PointLight2d, SpotLight2d},
+    fade::{FadingOut2d, LightFade2d, fade_lights},
+    flash::{AmbientFlash2d, update_ambient_flashes},
+    light::{
+        AmbientGradient, AmbientGradientSpace, AmbientLight2d, AreaLight2d, BeamLight2d,
+        CapsuleLight2d, DirectionalLight2d, EmissiveSprite2d, Exposure2d, FalloffCurve,
+        GlobalLight2d, LightIntensityUnit, LineLight2d, PointLight2d, PolygonLight2d,
+        PolylineLight2d, RingLight2d, ScreenLight2d, SpotLight2d, StaticLight2d,
+        VolumetricLightQuality,
+    },
+    lod::{DemotedLights, Light2dLodSettings, LightLod2d, update_light_lod},
+    modulation::{
+        Light2dAnimationTime, LightFlicker2d, LightModulation2d, LightModulationSystems,
+        LightOscillator2d, LightPulse2d, LightStrobe2d, PulseWaveform,
+        advance_light_animation_time, flicker_lights, oscillate_lights, pulse_lights,
+        reset_light_modulation, strobe_lights,
+    },
+    occluder::{
+        OccluderCulling, OccluderLightFilter, OcclusionLayers, StaticLightOccluder2d,
+        calculate_occluder_bounds, sync_mesh_occluders, update_occluder_frames,
+        update_tilemap_occluders,
+    },
+    probe::{LightProbe2d, SampledLight2d, sample_light_probes},
     render::{
         TYPES_SHADER, VIEW_TRANSFORMATIONS_SHADER,
+        emissive::{
+            EMISSIVE_SHADER, EmissiveNode, EmissivePass, EmissivePipeline,
+            EmissiveSpriteMetaBuffer, prepare_emissive_sprite_count, prepare_emissive_texture,
+        },
         empty_buffer::{EmptyBuffer, prepare_empty_buffer},
         extract::{
-            ExtractedAmbientLight2d, ExtractedLightOccluder2d, ExtractedPointLight2d,
-            ExtractedSpotLight2d, extract_ambient_lights, extract_light_occluders,
-            extract_point_lights, extract_spot_lights,
+            ExtractedAmbientLight2d, ExtractedEmissiveSprite2d, ExtractedLightExtras2d,
+            ExtractedLightOccluder2d, ExtractedLineLight2d, ExtractedOccluders,
+            ExtractedPointLight2d, ExtractedPolygonLight2d, ExtractedRingLight2d,
+            ExtractedSpotLight2d, IncompleteOccluders, PolygonOccluderCache, clear_light_extras,
+            clear_occluder_exclusions, extract_ambient_light_zones, extract_ambient_lights,
+            extract_area_lights, extract_beam_lights, extract_capsule_lights,
+            extract_directional_lights, extract_emissive_sprites, extract_light_occluders,
+            extract_line_lights, extract_point_lights, extract_polygon_lights,
+            extract_polyline_lights, extract_ring_lights, extract_screen_lights,
+            extract_spot_lights, extract_trail_lights, reclaim_light_cookie_atlas,
         },
         light_map::{
-            LIGHT_MAP_SHADER, LightMapNode, LightMapPass, LightMapPipeline, PointLightMetaBuffer,
-            SpotLightMetaBuffer, prepare_light_map_texture, prepare_point_light_count,
-            prepare_spot_light_count,
+            AmbientLightZoneBuffer, LIGHT_MAP_SHADER, LightCookieAtlas, LightExtrasBuffer,
+            LightMapMetaBuffer, LightMapNode, LightMapPass, LightMapPipeline,
+            OccluderExclusionBuffer, PolygonLightVertexBuffer, prepare_light_cookie_atlas,
+            prepare_light_extras, prepare_light_map_meta, prepare_light_map_pipelines,
+            prepare_light_map_texture,
         },
         lighting::{
             LIGHTING_SHADER, LightingNode, LightingPass, LightingPipeline,
             prepare_lighting_pipelines,
         },
         sdf::{
-            OccluderMetaBuffer, SDF_SHADER, SdfNode, SdfPass, SdfPipeline, prepare_occluder_meta,
-            prepare_sdf_texture,
+            OccluderMaskAtlas, OccluderMetaBuffer, OccluderSdfTexture, OccluderVertexBuffer,
+            SDF_SHADER, SdfNode, SdfPass, SdfPipeline, StaticOccluderBuffer,
+            prepare_occluder_mask_atlas, prepare_occluder_sdf_texture, prepare_occluder_vertices,
+            prepare_occluders, prepare_sdf_texture, prepare_static_occluders,
         },
     },
+    target::{LightTarget2d, aim_lights_at_targets},
+    trail::{TrailLight2d, update_light_trails},
+    vision::VisionSource2d,
+    zone::{AmbientLightZone2d, AmbientLightZoneShape, calculate_ambient_zone_bounds},
 };
 
 /// A plugin that provides 2d lighting for an app.
@@ -63,16 +110,124 @@ impl Plugin for Light2dPlugin {
             "render/light_map/light_map.wgsl",
             Shader::from_wgsl
         );
+        load_internal_asset!(
+            app,
+            EMISSIVE_SHADER,
+            "render/emissive/emissive.wgsl",
+            Shader::from_wgsl
+        );
 
         app.add_plugins((
             UniformComponentPlugin::<ExtractedAmbientLight2d>::default(),
             GpuComponentArrayBufferPlugin::<ExtractedPointLight2d>::default(),
-            GpuComponentArrayBufferPlugin::<ExtractedLightOccluder2d>::default(),
             GpuComponentArrayBufferPlugin::<ExtractedSpotLight2d>::default(),
+            GpuComponentArrayBufferPlugin::<ExtractedLineLight2d>::default(),
+            GpuComponentArrayBufferPlugin::<ExtractedRingLight2d>::default(),
+            GpuComponentArrayBufferPlugin::<ExtractedPolygonLight2d>::default(),
+            GpuComponentArrayBufferPlugin::<ExtractedEmissiveSprite2d>::default(),
+            ExtractResourcePlugin::<VolumetricLightQuality>::default(),
+            ExtractResourcePlugin::<Light2dAnimationTime>::default(),
         ))
         .register_type::<AmbientLight2d>()
+        .register_type::<AmbientGradient>()
+        .register_type::<AmbientGradientSpace>()
+        .register_type::<AmbientLightZone2d>()
+        .register_type::<AmbientLightZoneShape>()
         .register_type::<PointLight2d>()
-        .register_type::<SpotLight2d>();
+        .register_type::<FalloffCurve>()
+        .register_type::<LightIntensityUnit>()
+        .register_type::<Exposure2d>()
+        .register_type::<SpotLight2d>()
+        .register_type::<LineLight2d>()
+        .register_type::<CapsuleLight2d>()
+        .register_type::<BeamLight2d>()
+        .register_type::<AreaLight2d>()
+        .register_type::<RingLight2d>()
+        .register_type::<PolygonLight2d>()
+        .register_type::<PolylineLight2d>()
+        .register_type::<ScreenLight2d>()
+        .register_type::<TrailLight2d>()
+        .register_type::<LightTarget2d>()
+        .register_type::<Light2dLodSettings>()
+        .register_type::<LightLod2d>()
+        .register_type::<LightModulation2d>()
+        .register_type::<LightFlicker2d>()
+        .register_type::<LightPulse2d>()
+        .register_type::<PulseWaveform>()
+        .register_type::<LightStrobe2d>()
+        .register_type::<LightOscillator2d>()
+        .register_type::<Light2dAnimationTime>()
+        .register_type::<LightFade2d>()
+        .register_type::<FadingOut2d>()
+        .register_type::<AmbientFlash2d>()
+        .register_type::<DirectionalLight2d>()
+        .register_type::<GlobalLight2d>()
+        .register_type::<EmissiveSprite2d>()
+        .register_type::<StaticLight2d>()
+        .register_type::<LightProbe2d>()
+        .register_type::<SampledLight2d>()
+        .register_type::<VisionSource2d>()
+        .register_type::<OcclusionLayers>()
+        .register_type::<OccluderLightFilter>()
+        .register_type::<StaticLightOccluder2d>()
+        .register_type::<OccluderCulling>()
+        .register_type::<VolumetricLightQuality>()
+        .init_resource::<OccluderCulling>()
+        .init_resource::<VolumetricLightQuality>()
+        .init_resource::<Light2dAnimationTime>()
+        .init_resource::<Light2dLodSettings>()
+        .init_resource::<DemotedLights>()
+        .register_diagnostic(Diagnostic::new(Light2dLodSettings::DEMOTED_LIGHTS))
+        .configure_sets(
+            Update,
+            LightModulationSystems::Reset.before(LightModulationSystems::Modulate),
+        )
+        .add_systems(
+            Update,
+            (
+                (advance_light_animation_time, reset_light_modulation)
+                    .in_set(LightModulationSystems::Reset),
+                (
+                    flicker_lights,
+                    pulse_lights,
+                    strobe_lights,
+                    oscillate_lights,
+                    fade_lights,
+                )
+                    .in_set(LightModulationSystems::Modulate),
+                aim_lights_at_targets.after(LightModulationSystems::Reset),
+                update_ambient_flashes.after(LightModulationSystems::Reset),
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                sync_mesh_occluders.before(VisibilitySystems::CalculateBounds),
+                update_tilemap_occluders.before(TransformSystems::Propagate),
+                update_occluder_frames.before(VisibilitySystems::CalculateBounds),
+                calculate_occluder_bounds.in_set(VisibilitySystems::CalculateBounds),
+                calculate_ambient_zone_bounds.in_set(VisibilitySystems::CalculateBounds),
+                sample_light_probes
+                    .after(TransformSystems::Propagate)
+                    .after(VisibilitySystems::VisibilityPropagate),
+                update_light_trails.after(TransformSystems::Propagate),
+                update_light_lod.after(VisibilitySystems::CheckVisibility),
+            ),
+        );
+
+        #[cfg(feature = "avian2d")]
+        app.add_systems(
+            PostUpdate,
+            crate::collider::sync_avian_collider_occluders
+                .before(VisibilitySystems::CalculateBounds),
+        );
+
+        #[cfg(feature = "rapier2d")]
+        app.add_systems(
+            PostUpdate,
+            crate::collider::sync_rapier_collider_occluders
+                .before(VisibilitySystems::CalculateBounds),
+        );
 
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
@@ -80,26 +235,80 @@ impl Plugin for Light2dPlugin {
 
         render_app
             .init_resource::<SpecializedRenderPipelines<LightingPipeline>>()
-            .init_resource::<PointLightMetaBuffer>()
-            .init_resource::<SpotLightMetaBuffer>()
+            .init_resource::<SpecializedRenderPipelines<LightMapPipeline>>()
+            .init_resource::<LightMapMetaBuffer>()
+            .init_resource::<LightExtrasBuffer>()
+            .init_resource::<PolygonLightVertexBuffer>()
+            .init_resource::<AmbientLightZoneBuffer>()
+            .init_resource::<EmissiveSpriteMetaBuffer>()
             .init_resource::<OccluderMetaBuffer>()
+            .init_resource::<OccluderVertexBuffer>()
+            .init_resource::<ExtractedOccluders>()
+            .init_resource::<PolygonOccluderCache>()
+            .init_resource::<IncompleteOccluders>()
+            .init_resource::<OccluderMaskAtlas>()
+            .init_resource::<LightCookieAtlas>()
+            .init_resource::<OccluderSdfTexture>()
+            .init_resource::<OccluderExclusionBuffer>()
             .init_resource::<EmptyBuffer>()
             .add_systems(
                 ExtractSchedule,
                 (
-                    extract_point_lights,
+                    clear_occluder_exclusions,
+                    clear_light_extras,
+                    reclaim_light_cookie_atlas,
+                    extract_point_lights
+                        .after(clear_occluder_exclusions)
+                        .after(clear_light_extras)
+                        .after(reclaim_light_cookie_atlas),
+                    extract_screen_lights,
                     extract_light_occluders,
                     extract_ambient_lights,
-                    extract_spot_lights,
+                    extract_ambient_light_zones,
+                    extract_spot_lights
+                        .after(clear_occluder_exclusions)
+                        .after(clear_light_extras)
+                        .after(reclaim_light_cookie_atlas),
+                    extract_line_lights.after(clear_occluder_exclusions),
+                    extract_capsule_lights.after(clear_occluder_exclusions),
+                    extract_beam_lights.after(clear_occluder_exclusions),
+                    extract_area_lights
+                        .after(clear_occluder_exclusions)
+                        .after(extract_polygon_lights),
+                    extract_ring_lights.after(clear_occluder_exclusions),
+                    extract_polygon_lights.after(clear_occluder_exclusions),
+                    extract_polyline_lights
+                        .after(clear_occluder_exclusions)
+                        .after(extract_polygon_lights),
+                    extract_trail_lights,
+                    extract_directional_lights.after(clear_occluder_exclusions),
+                    extract_emissive_sprites.after(reclaim_light_cookie_atlas),
                 ),
             )
             .add_systems(
                 Render,
                 (
                     prepare_lighting_pipelines.in_set(RenderSet::Prepare),
-                    prepare_point_light_count.in_set(RenderSet::Prepare),
-                    prepare_spot_light_count.in_set(RenderSet::Prepare),
-                    prepare_occluder_meta.in_set(RenderSet::Prepare),
+                    prepare_light_map_pipelines
+                        .after(prepare_occluders)
+                        .in_set(RenderSet::Prepare),
+                    prepare_light_extras.in_set(RenderSet::Prepare),
+                    prepare_light_map_meta
+                        .after(prepare_occluders)
+                        .in_set(RenderSet::Prepare),
+                    prepare_emissive_sprite_count.in_set(RenderSet::Prepare),
+                    prepare_occluder_sdf_texture.in_set(RenderSet::Prepare),
+                    prepare_static_occluders
+                        .after(prepare_occluder_sdf_texture)
+                        .in_set(RenderSet::Prepare),
+                    prepare_occluders
+                        .after(prepare_static_occluders)
+                        .in_set(RenderSet::Prepare),
+                    prepare_occluder_vertices
+                        .after(prepare_occluders)
+                        .in_set(RenderSet::Prepare),
+                    prepare_occluder_mask_atlas.in_set(RenderSet::Prepare),
+                    prepare_light_cookie_atlas.in_set(RenderSet::Prepare),
                     prepare_empty_buffer.in_set(RenderSet::Prepare),
                     prepare_sdf_texture
                         .after(prepare_view_targets)
@@ -107,17 +316,22 @@ impl Plugin for Light2dPlugin {
                     prepare_light_map_texture
                         .after(prepare_view_targets)
                         .in_set(RenderSet::ManageViews),
+                    prepare_emissive_texture
+                        .after(prepare_view_targets)
+                        .in_set(RenderSet::ManageViews),
                 ),
             )
             .add_render_graph_node::<ViewNodeRunner<LightingNode>>(Core2d, LightingPass)
             .add_render_graph_node::<ViewNodeRunner<SdfNode>>(Core2d, SdfPass)
             .add_render_graph_node::<ViewNodeRunner<LightMapNode>>(Core2d, LightMapPass)
+            .add_render_graph_node::<ViewNodeRunner<EmissiveNode>>(Core2d, EmissivePass)
             .add_render_graph_edges(
                 Core2d,
                 (
                     Node2d::EndMainPass,
                     SdfPass,
                     LightMapPass,
+                    EmissivePass,
                     LightingPass,
                     Node2d::StartMainPassPostProcessing,
                 ),
@@ -129,9 +343,20 @@ impl Plugin for Light2dPlugin {
             return;
         };
 
+        let render_device = render_app.world().resource::<RenderDevice>();
+        let occluder_buffer = GpuArrayBuffer::<ExtractedLightOccluder2d>::new(render_device);
+        let light_extras_buffer = GpuArrayBuffer::<ExtractedLightExtras2d>::new(render_device);
+        let static_occluder_buffer = StaticOccluderBuffer {
+            buffer: GpuArrayBuffer::new(render_device),
+        };
+
         render_app
+            .insert_resource(occluder_buffer)
+            .insert_resource(light_extras_buffer)
+            .insert_resource(static_occluder_buffer)
             .init_resource::<LightingPipeline>()
             .init_resource::<SdfPipeline>()
-            .init_resource::<LightMapPipeline>();
+            .init_resource::<LightMapPipeline>()
+            .init_resource::<EmissivePipeline>();
     }
 }
@@ -0,0 +1,370 @@
+//! A module which contains [`LightProbe2d`], which samples lighting on the CPU.
+
+use bevy::{
+    asset::Assets,
+    camera::{Camera, visibility::InheritedVisibility},
+    color::{Color, ColorToComponents, LinearRgba},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::With,
+        system::{Query, Res},
+    },
+    image::Image,
+    math::{Vec3, Vec3Swizzles},
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::{
+    cpu_light::{CpuLight, CpuOccluder, blend_light, clamp_brightness},
+    flash::AmbientFlashes2d,
+    light::{
+        AmbientGradientSpace, AreaLight2d, CapsuleLight2d, GlobalLight2d, Light2d, LightBlend2d,
+        LineLight2d, PointLight2d, SpotLight2d,
+    },
+    modulation::LightModulation2d,
+    occluder::{LightOccluder2d, OcclusionLayers},
+    zone::AmbientLightZone2d,
+};
+
+/// Samples the light at an entity's position every frame, writing it to its
+/// [`SampledLight2d`].
+///
+/// This is useful for tinting entities which aren't drawn with the lighting pass, such as
+/// characters with a custom material, to match their surroundings, without reading back the
+/// light map from the GPU.
+///
+/// Probes evaluate the ambient light (including its gradient and flashes), exposure and maximum
+/// brightness of the first active camera with a [`Light2d`], every [`AmbientLightZone2d`] and
+/// [`GlobalLight2d`], and every visible [`PointLight2d`], [`SpotLight2d`], [`LineLight2d`],
+/// [`CapsuleLight2d`] and [`AreaLight2d`], using the same attenuation as the light map pass.
+/// The [`LightModulation2d`] of each light is applied, so flickering lights flicker on the
+/// probe too.
+/// Shadows are hard, and follow the outlines of occluders as described by [`Light2dBaker`].
+/// Other lights, light cookies, noise textures, ambient maps, ambient occlusion and emissive
+/// sprites aren't sampled.
+///
+/// [`Light2dBaker`]: crate::bake::Light2dBaker
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform, SampledLight2d)]
+pub struct LightProbe2d;
+
+/// The light sampled by a [`LightProbe2d`].
+///
+/// The color can be multiplied with an entity's own color to light it, like the lighting pass
+/// does for the rest of the scene.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default)]
+pub struct SampledLight2d {
+    /// The light reaching the probe, which may be brighter than white.
+    pub color: LinearRgba,
+}
+
+impl Default for SampledLight2d {
+    /// Returns a [`SampledLight2d`] of white light, which leaves colors unchanged until the
+    /// probe is first sampled.
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::WHITE,
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn sample_light_probes(
+    mut probes: Query<(&GlobalTransform, &mut SampledLight2d), With<LightProbe2d>>,
+    cameras: Query<(
+        &Camera,
+        &GlobalTransform,
+        &Light2d,
+        Option<&AmbientFlashes2d>,
+    )>,
+    global_lights: Query<(&GlobalLight2d, &InheritedVisibility)>,
+    ambient_light_zones: Query<(
+        Entity,
+        &AmbientLightZone2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+    )>,
+    point_lights: Query<(
+        &PointLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+        Option<&LightModulation2d>,
+    )>,
+    spot_lights: Query<(
+        &SpotLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+        Option<&LightModulation2d>,
+    )>,
+    line_lights: Query<(
+        &LineLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+        Option<&LightModulation2d>,
+    )>,
+    capsule_lights: Query<(
+        &CapsuleLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+        Option<&LightModulation2d>,
+    )>,
+    area_lights: Query<(
+        &AreaLight2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+        Option<&LightModulation2d>,
+    )>,
+    occluders: Query<(
+        &LightOccluder2d,
+        &GlobalTransform,
+        &InheritedVisibility,
+        Option<&OcclusionLayers>,
+    )>,
+    images: Res<Assets<Image>>,
+) {
+    if probes.is_empty() {
+        return;
+    }
+
+    let camera = cameras.iter().find(|(camera, ..)| camera.is_active);
+    let camera_light_2d = camera.map(|(_, _, light_2d, _)| light_2d);
+    let ambient_flash = camera
+        .and_then(|(_, _, _, flashes)| flashes)
+        .map_or(Vec3::ZERO, AmbientFlashes2d::color);
+    let ambient_gradient =
+        camera_light_2d.and_then(|light_2d| light_2d.ambient_light.gradient.as_ref());
+    let (ambient_bottom, ambient_top) =
+        camera_light_2d.map_or((Vec3::ZERO, Vec3::ZERO), |light_2d| {
+            let ambient_light = &light_2d.ambient_light;
+            let ambient_color = |color: Color| {
+                (color.to_linear().to_vec3() * ambient_light.brightness).max(ambient_flash)
+            };
+            match &ambient_light.gradient {
+                Some(gradient) => (ambient_color(gradient.bottom), ambient_color(gradient.top)),
+                None => (
+                    ambient_color(ambient_light.color),
+                    ambient_color(ambient_light.color),
+                ),
+            }
+        });
+    let exposure = camera_light_2d.map_or(1.0, |light_2d| light_2d.exposure.multiplier());
+    let blend = camera_light_2d.map_or(LightBlend2d::Add, |light_2d| light_2d.blend);
+    let max_brightness = camera_light_2d.and_then(|light_2d| light_2d.max_brightness);
+    // Global lights are never occluded, so they're folded into the ambient light, as they are
+    // for each view.
+    let global_light = global_lights
+        .iter()
+        .filter(|(_, visibility)| visibility.get())
+        .fold(Vec3::ZERO, |color, (global_light, _)| {
+            color + global_light.color.to_linear().to_vec3() * global_light.intensity
+        });
+
+    // Zones are layered in the same order as the light map pass.
+    let mut zones: Vec<_> = ambient_light_zones
+        .iter()
+        .filter(|(_, _, _, visibility)| visibility.get())
+        .collect();
+    zones.sort_by_key(|(entity, zone, ..)| (zone.priority, *entity));
+
+    let lights: Vec<CpuLight> = point_lights
+        .iter()
+        .filter(|(light, _, visibility, ..)| visibility.get() && light.enabled)
+        .map(
+            |(light, global_transform, _, occlusion_layers, modulation)| {
+                CpuLight::point(light, global_transform, occlusion_layers, modulation)
+            },
+        )
+        .chain(
+            spot_lights
+                .iter()
+                .filter(|(light, _, visibility, ..)| visibility.get() && light.enabled)
+                .map(
+                    |(light, global_transform, _, occlusion_layers, modulation)| {
+                        CpuLight::spot(light, global_transform, occlusion_layers, modulation)
+                    },
+                ),
+        )
+        .chain(
+            line_lights
+                .iter()
+                .filter(|(_, _, visibility, ..)| visibility.get())
+                .map(
+                    |(light, global_transform, _, occlusion_layers, modulation)| {
+                        CpuLight::line(light, global_transform, occlusion_layers, modulation)
+                    },
+                ),
+        )
+        .chain(
+            capsule_lights
+                .iter()
+                .filter(|(_, _, visibility, ..)| visibility.get())
+                .map(
+                    |(light, global_transform, _, occlusion_layers, modulation)| {
+                        CpuLight::capsule(light, global_transform, occlusion_layers, modulation)
+                    },
+                ),
+        )
+        .chain(
+            area_lights
+                .iter()
+                .filter(|(_, _, visibility, ..)| visibility.get())
+                .map(
+                    |(light, global_transform, _, occlusion_layers, modulation)| {
+                        CpuLight::area(light, global_transform, occlusion_layers, modulation)
+                    },
+                ),
+        )
+        .collect();
+
+    let occluders: Vec<CpuOccluder> = occluders
+        .iter()
+        .filter(|(_, _, visibility, _)| visibility.get())
+        .filter_map(|(occluder, global_transform, _, occlusion_layers)| {
+            CpuOccluder::new(occluder, global_transform, occlusion_layers, &images)
+        })
+        .collect();
+
+    for (global_transform, mut sampled_light) in &mut probes {
+        let pos = global_transform.translation().xy();
+        // Probes follow the ambient light's gradient at their position in the world, or on the
+        // camera's screen.
+        let gradient_factor = ambient_gradient.map_or(0.0, |gradient| match gradient.space {
+            AmbientGradientSpace::World => gradient.world_factor(pos.y),
+            AmbientGradientSpace::Screen => camera
+                .and_then(|(camera, camera_transform, ..)| {
+                    let viewport_pos = camera
+                        .world_to_viewport(camera_transform, pos.extend(0.0))
+                        .ok()?;
+                    let viewport_height = camera.logical_viewport_size()?.y;
+                    Some(1.0 - viewport_pos.y / viewport_height)
+                })
+                .unwrap_or(0.5)
+                .clamp(0.0, 1.0),
+        });
+        let ambient_light = zones.iter().fold(
+            ambient_bottom.lerp(ambient_top, gradient_factor) + global_light,
+            |color, (_, zone, zone_transform, _)| {
+                let zone_color = zone.color.to_linear().to_vec3() * zone.brightness + global_light;
+                color.lerp(zone_color, zone.weight(zone_transform, pos))
+            },
+        );
+        let color = lights.iter().fold(ambient_light, |color, light| {
+            blend_light(blend, color, light.illuminate(pos, &occluders))
+        });
+
+        // Negative lights never make the probe darker than black, matching the light map pass.
+        sampled_light.color = LinearRgba::from_vec3(clamp_brightness(
+            color.max(Vec3::ZERO) * exposure,
+            max_brightness,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Update},
+        utils::default,
+    };
+
+    use super::*;
+    use crate::{
+        light::{AmbientLight2d, Exposure2d},
+        test_utils::luminance,
+    };
+
+    /// Samples a probe lit by a point light, 10 units away, through a camera with the given
+    /// [`Light2d`].
+    fn sample(light_2d: Light2d, point_light: PointLight2d) -> Vec3 {
+        let mut app = App::new();
+        app.init_resource::<Assets<Image>>()
+            .add_systems(Update, sample_light_probes);
+
+        app.world_mut()
+            .spawn((Camera::default(), GlobalTransform::default(), light_2d));
+        app.world_mut().spawn((
+            point_light,
+            GlobalTransform::default(),
+            InheritedVisibility::VISIBLE,
+        ));
+        let probe = app
+            .world_mut()
+            .spawn((LightProbe2d, GlobalTransform::from_xyz(10.0, 0.0, 0.0)))
+            .id();
+
+        app.update();
+
+        app.world()
+            .get::<SampledLight2d>(probe)
+            .unwrap()
+            .color
+            .to_vec3()
+    }
+
+    fn dim_ambient_light(exposure: Exposure2d, max_brightness: Option<f32>) -> Light2d {
+        Light2d {
+            ambient_light: AmbientLight2d {
+                brightness: 0.1,
+                ..default()
+            },
+            exposure,
+            max_brightness,
+            ..default()
+        }
+    }
+
+    fn point_light(intensity: f32, max_brightness: Option<f32>) -> PointLight2d {
+        PointLight2d {
+            intensity,
+            radius: 100.0,
+            max_brightness,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn light_max_brightness_clamps_only_that_light() {
+        let sampled = sample(
+            dim_ambient_light(Exposure2d::default(), None),
+            point_light(10.0, Some(0.5)),
+        );
+
+        // The ambient light is added after the point light is clamped.
+        assert!((luminance(sampled) - 0.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn camera_max_brightness_clamps_exposed_light() {
+        let sampled = sample(
+            dim_ambient_light(Exposure2d { stops: 2.0 }, Some(0.25)),
+            point_light(10.0, None),
+        );
+
+        assert!((luminance(sampled) - 0.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn exposure_stop_doubles_sampled_light() {
+        let unexposed = sample(
+            dim_ambient_light(Exposure2d { stops: 0.0 }, None),
+            point_light(0.5, None),
+        );
+        let exposed = sample(
+            dim_ambient_light(Exposure2d { stops: 1.0 }, None),
+            point_light(0.5, None),
+        );
+
+        assert!(unexposed.min_element() > 0.1);
+        assert!((exposed - unexposed * 2.0).abs().max_element() < 1e-5);
+    }
+}
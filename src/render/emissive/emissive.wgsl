@@ -0,0 +1,54 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_render::view::View
+#import bevy_light_2d::types::{EmissiveSprite2d, EmissiveSpriteMeta}
+#import bevy_light_2d::view_transformations::{frag_coord_to_ndc, ndc_to_world};
+
+// We're currently only using a single uniform binding for emissive sprites in
+// WebGL2, which is limited to 4kb in BatchedUniformBuffer, so we need to
+// ensure our emissive sprites can fit in 4kb.
+const MAX_EMISSIVE_SPRITES: u32 = 64u;
+
+@group(0) @binding(0)
+var<uniform> view: View;
+
+// WebGL2 does not support storage buffers, so we fall back to a fixed length
+// array in a uniform buffer.
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 6
+    @group(0) @binding(1)
+    var<storage> emissive_sprites: array<EmissiveSprite2d>;
+#else
+    @group(0) @binding(1)
+    var<uniform> emissive_sprites: array<EmissiveSprite2d, MAX_EMISSIVE_SPRITES>;
+#endif
+
+@group(0) @binding(2)
+var<uniform> emissive_sprite_meta: EmissiveSpriteMeta;
+
+@group(0) @binding(3)
+var light_cookie_atlas: texture_2d<f32>;
+
+@group(0) @binding(4)
+var sprite_sampler: sampler;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pos = ndc_to_world(frag_coord_to_ndc(in.position.xy));
+
+    var emissive_color = vec3(0.0);
+
+    for (var i = 0u; i < emissive_sprite_meta.count; i++) {
+        let sprite = emissive_sprites[i];
+        let d = pos - sprite.center;
+        let local_pos = vec2(dot(sprite.world_to_local_x, d), dot(sprite.world_to_local_y, d));
+
+        if all(abs(local_pos) <= vec2(0.5)) {
+            // Image rows go downwards, while local space y goes upwards.
+            let uv = vec2(local_pos.x, -local_pos.y) + 0.5;
+            let atlas_uv = mix(sprite.uv_rect.xy, sprite.uv_rect.zw, uv);
+            let texel = textureSampleLevel(light_cookie_atlas, sprite_sampler, atlas_uv, 0.0);
+            emissive_color += sprite.color.rgb * texel.rgb * texel.a;
+        }
+    }
+
+    return vec4(emissive_color, 1.0);
+}
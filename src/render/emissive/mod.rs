@@ -0,0 +1,54 @@
+mod node;
+mod pipeline;
+mod prepare;
+
+use bevy::{
+    asset::{Handle, weak_handle},
+    ecs::{component::Component, resource::Resource},
+    math::Vec3,
+    render::{
+        render_graph::RenderLabel,
+        render_resource::{ShaderType, UniformBuffer},
+        texture::CachedTexture,
+    },
+    shader::Shader,
+};
+
+pub use node::EmissiveNode;
+pub use pipeline::EmissivePipeline;
+pub use prepare::{prepare_emissive_sprite_count, prepare_emissive_texture};
+
+pub const EMISSIVE_SHADER: Handle<Shader> = weak_handle!("8f1323d0-d616-42e0-ae54-4712184be5c9");
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct EmissivePass;
+
+/// The light emitted by [`EmissiveSprite2d`]s, which is added to the light map by the lighting
+/// pass.
+///
+/// [`EmissiveSprite2d`]: crate::light::EmissiveSprite2d
+#[derive(Component)]
+pub struct EmissiveTexture {
+    pub emissive: CachedTexture,
+}
+
+#[derive(Resource, Default)]
+pub struct EmissiveSpriteMetaBuffer {
+    pub buffer: UniformBuffer<EmissiveSpriteMeta>,
+}
+
+#[derive(Default, ShaderType)]
+pub struct EmissiveSpriteMeta {
+    pub count: u32,
+    // WebGL2 structs must be 16 byte aligned.
+    _padding: Vec3,
+}
+
+impl EmissiveSpriteMeta {
+    pub fn new(count: u32) -> Self {
+        Self {
+            count,
+            _padding: Vec3::ZERO,
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::ecs::system::lifetimeless::Read;
+use bevy::prelude::*;
+use bevy::render::render_graph::ViewNode;
+
+use bevy::render::render_resource::{
+    BindGroupEntries, GpuArrayBuffer, Operations, PipelineCache, RenderPassColorAttachment,
+    RenderPassDescriptor,
+};
+use bevy::render::renderer::RenderDevice;
+use bevy::render::view::{ViewUniformOffset, ViewUniforms};
+use smallvec::{SmallVec, smallvec};
+
+use crate::render::empty_buffer::EmptyBuffer;
+use crate::render::extract::ExtractedEmissiveSprite2d;
+use crate::render::light_map::LightCookieAtlas;
+
+use super::{EmissivePipeline, EmissiveSpriteMetaBuffer, EmissiveTexture};
+
+const EMISSIVE_PASS: &str = "emissive_pass";
+const EMISSIVE_BIND_GROUP: &str = "emissive_bind_group";
+
+#[derive(Default)]
+pub struct EmissiveNode;
+
+impl ViewNode for EmissiveNode {
+    type ViewQuery = (Read<ViewUniformOffset>, Read<EmissiveTexture>);
+
+    fn run<'w>(
+        &self,
+        _graph: &mut bevy::render::render_graph::RenderGraphContext,
+        render_context: &mut bevy::render::renderer::RenderContext<'w>,
+        (view_offset, emissive_texture): bevy::ecs::query::QueryItem<'w, 'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), bevy::render::render_graph::NodeRunError> {
+        let emissive_pipeline = world.resource::<EmissivePipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let (
+            Some(pipeline),
+            Some(view_uniform_binding),
+            Some(emissive_sprite_binding),
+            Some(emissive_sprite_count_binding),
+            Some(light_cookie_atlas),
+        ) = (
+            pipeline_cache.get_render_pipeline(emissive_pipeline.pipeline_id),
+            world.resource::<ViewUniforms>().uniforms.binding(),
+            world
+                .resource::<GpuArrayBuffer<ExtractedEmissiveSprite2d>>()
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
+            world
+                .resource::<EmissiveSpriteMetaBuffer>()
+                .buffer
+                .binding(),
+            world.resource::<LightCookieAtlas>().texture_view.as_ref(),
+        )
+        else {
+            return Ok(());
+        };
+
+        let bind_group = render_context.render_device().create_bind_group(
+            EMISSIVE_BIND_GROUP,
+            &emissive_pipeline.layout,
+            &BindGroupEntries::sequential((
+                view_uniform_binding,
+                emissive_sprite_binding,
+                emissive_sprite_count_binding,
+                light_cookie_atlas,
+                &emissive_pipeline.sprite_sampler,
+            )),
+        );
+
+        let mut emissive_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some(EMISSIVE_PASS),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &emissive_texture.emissive.default_view,
+                resolve_target: None,
+                ops: Operations::default(),
+                depth_slice: None,
+            })],
+            ..default()
+        });
+
+        let mut dynamic_offsets: SmallVec<[u32; 2]> = smallvec![view_offset.offset];
+
+        // Storage buffers aren't available in WebGL2. We fall back to a
+        // dynamic uniform buffer, and therefore need to provide the offset.
+        if world
+            .resource::<RenderDevice>()
+            .limits()
+            .max_storage_buffers_per_shader_stage
+            == 0
+        {
+            dynamic_offsets.push(0); // emissive sprites array
+        }
+
+        emissive_pass.set_render_pipeline(pipeline);
+        emissive_pass.set_bind_group(0, &bind_group, &dynamic_offsets);
+        emissive_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
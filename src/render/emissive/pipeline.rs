@@ -0,0 +1,84 @@
+use bevy::core_pipeline::FullscreenShader;
+use bevy::prelude::*;
+use bevy::render::render_resource::binding_types::{sampler, texture_2d, uniform_buffer};
+use bevy::render::render_resource::{
+    AddressMode, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState,
+    ColorWrites, FilterMode, FragmentState, GpuArrayBuffer, MultisampleState, PipelineCache,
+    PrimitiveState, RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor,
+    ShaderStages, TextureFormat, TextureSampleType,
+};
+use bevy::render::renderer::RenderDevice;
+use bevy::render::view::ViewUniform;
+
+use crate::render::extract::ExtractedEmissiveSprite2d;
+
+use super::{EMISSIVE_SHADER, EmissiveSpriteMeta};
+
+const EMISSIVE_PIPELINE: &str = "emissive_pipeline";
+const EMISSIVE_BIND_GROUP_LAYOUT: &str = "emissive_bind_group_layout";
+
+#[derive(Resource)]
+pub struct EmissivePipeline {
+    pub layout: BindGroupLayout,
+    pub pipeline_id: CachedRenderPipelineId,
+    pub sprite_sampler: Sampler,
+}
+
+impl FromWorld for EmissivePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let fullscreen_shader = world.resource::<FullscreenShader>();
+
+        let layout = render_device.create_bind_group_layout(
+            EMISSIVE_BIND_GROUP_LAYOUT,
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    uniform_buffer::<ViewUniform>(true),
+                    GpuArrayBuffer::<ExtractedEmissiveSprite2d>::binding_layout(render_device),
+                    uniform_buffer::<EmissiveSpriteMeta>(false),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                ),
+            ),
+        );
+
+        // Sprites are sampled with linear filtering, as they're stored downsampled in the
+        // light cookie atlas.
+        let sprite_sampler = render_device.create_sampler(&SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+
+        let pipeline_id = pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
+            label: Some(EMISSIVE_PIPELINE.into()),
+            layout: vec![layout.clone()],
+            vertex: fullscreen_shader.to_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: EMISSIVE_SHADER,
+                shader_defs: vec![],
+                entry_point: Some("fragment".into()),
+                targets: vec![Some(ColorTargetState {
+                    format: TextureFormat::Rgba16Float,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        });
+
+        Self {
+            layout,
+            pipeline_id,
+            sprite_sampler,
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use bevy::{
+    ecs::{
+        entity::Entity,
+        system::{Commands, Query, Res, ResMut},
+    },
+    render::{
+        render_resource::{TextureDescriptor, TextureDimension, TextureFormat, TextureUsages},
+        renderer::{RenderDevice, RenderQueue},
+        texture::TextureCache,
+        view::ViewTarget,
+    },
+};
+
+use crate::render::extract::ExtractedEmissiveSprite2d;
+
+use super::{EmissiveSpriteMeta, EmissiveSpriteMetaBuffer, EmissiveTexture};
+
+const EMISSIVE_TEXTURE: &str = "emissive_texture";
+
+pub fn prepare_emissive_texture(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    mut texture_cache: ResMut<TextureCache>,
+    view_targets: Query<(Entity, &ViewTarget)>,
+) {
+    for (entity, view_target) in &view_targets {
+        let emissive_texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some(EMISSIVE_TEXTURE),
+                size: view_target.main_texture().size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba16Float,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+
+        commands.entity(entity).insert(EmissiveTexture {
+            emissive: emissive_texture,
+        });
+    }
+}
+
+pub fn prepare_emissive_sprite_count(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    emissive_sprites: Query<&ExtractedEmissiveSprite2d>,
+    mut emissive_sprite_count: ResMut<EmissiveSpriteMetaBuffer>,
+) {
+    let meta = EmissiveSpriteMeta::new(emissive_sprites.iter().len() as u32);
+    emissive_sprite_count.buffer.set(meta);
+    emissive_sprite_count
+        .buffer
+        .write_buffer(&render_device, &render_queue);
+}
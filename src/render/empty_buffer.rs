@@ -27,11 +27,11 @@ impl EmptyBuffer {
         // GpuArrayBuffer uniform buffers are 4096 bytes in size when using WebGl2.
         //
         // On platforms that support dynamic storage buffers, we just need something big
-        // enough to "hold" one item.
+        // enough to "hold" one item of our largest type.
         let size = if render_device.limits().max_storage_buffers_per_shader_stage == 0 {
             4096
         } else {
-            64
+            512
         };
         if self.buffer.is_none() {
             self.buffer = Some(render_device.create_buffer(&BufferDescriptor {
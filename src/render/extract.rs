@@ -1,27 +1,107 @@
+use core::{f32::consts::PI, iter};
+use std::collections::BTreeMap;
+
 use bevy::{
+    camera::visibility::RenderLayers,
+    color::ColorToComponents,
+    platform::collections::{HashMap, HashSet},
     prelude::*,
-    render::{Extract, render_resource::ShaderType, sync_world::RenderEntity},
+    render::{
+        Extract,
+        render_resource::ShaderType,
+        sync_world::{RenderEntity, TemporaryRenderEntity},
+    },
+    sprite::Anchor,
 };
 
+use smallvec::SmallVec;
+
 use crate::{
-    light::{Light2d, PointLight2d, SpotLight2d},
-    occluder::{LightOccluder2d, LightOccluder2dShape},
+    flash::AmbientFlashes2d,
+    light::{
+        AmbientGradientSpace, AmbientOcclusion2d, AreaLight2d, BakedLightmap2d, BeamLight2d,
+        CapsuleLight2d, DEFAULT_LIGHT_HEIGHT, DirectionalLight2d, EmissiveSprite2d, FalloffCurve,
+        GlobalLight2d, Light2d, LightBlend2d, LineLight2d, MAX_POLYGON_LIGHT_VERTICES,
+        PointLight2d, PolygonLight2d, PolylineLight2d, RingLight2d, ScreenLight2d, SpotLight2d,
+        StaticLight2d,
+    },
+    lod::DemotedLights,
+    modulation::{Light2dAnimationTime, LightModulation2d, LightNoise2d},
+    occluder::{
+        LightOccluder2d, LightOccluder2dShape, MAX_CONVEX_POLYGON_VERTICES, OccluderLightFilter,
+        OcclusionLayers, StaticLightOccluder2d, triangulation::triangulate,
+    },
+    render::{
+        light_map::{
+            AmbientLightZoneBuffer, LightCookieAtlas, LightExtrasBuffer, MAX_AMBIENT_LIGHT_ZONES,
+            MAX_OCCLUDER_EXCLUSIONS, MAX_POLYGON_LIGHT_BUFFER_VERTICES, OccluderExclusionBuffer,
+            PolygonLightVertexBuffer,
+        },
+        sdf::OccluderMaskAtlas,
+    },
+    trail::{TrailLight2d, TrailPoints2d},
+    vision::{VisionMask2d, VisionSource2d},
+    zone::{AmbientLightZone2d, AmbientLightZoneShape},
 };
 
+/// The [`ExtractedPointLight2d::flags`] and [`ExtractedSpotLight2d::flags`] of lights that cast
+/// shadows.
+pub const LIGHT_FLAG_CAST_SHADOWS: u32 = 1 << 0;
+/// The flags of lights that are vision sources.
+pub const LIGHT_FLAG_VISION: u32 = 1 << 1;
+/// The flags of screen lights, whose position is in viewport coordinates and radius is in
+/// physical pixels.
+pub const LIGHT_FLAG_SCREEN_SPACE: u32 = 1 << 2;
+/// The flags of spot lights which project a texture, found at the `cookie_rect` of their extras.
+pub const LIGHT_FLAG_PROJECTION: u32 = 1 << 3;
+/// The light's `FalloffCurve` is stored in the flags above this bit.
+pub const LIGHT_FLAG_FALLOFF_CURVE_SHIFT: u32 = 8;
+
+/// The index of the extras of lights that don't use any.
+pub const NO_LIGHT_EXTRAS: u32 = u32::MAX;
+
 #[derive(Component, Default, Clone, ShaderType)]
 pub struct ExtractedPointLight2d {
     pub transform: Vec2,
     pub radius: f32,
+    // The light's height above the 2D plane, which is its z component in 3D.
+    pub height: f32,
     pub color: LinearRgba,
     pub intensity: f32,
     pub falloff: f32,
-    pub cast_shadows: u32,
+    // The light's local x axis, divided by its ellipticity along that axis.
+    pub ellipse_axis: Vec2,
+    // The light's ellipticity along its x axis, divided by its ellipticity along its y axis.
+    pub ellipse_aspect: f32,
+    // The light's `LIGHT_FLAG_*` bits and falloff curve.
+    pub flags: u32,
+    pub occlusion_layers: u32,
+    // Range of the occluder owners this light ignores within the occluder exclusion buffer.
+    pub exclusion_offset: u32,
+    pub exclusion_count: u32,
+    // The exponent of the light's falloff curve, or its attenuation exponent if it's an
+    // inverse square curve.
+    pub falloff_exponent: f32,
+    // The radius within which the light is at its full intensity.
+    pub inner_radius: f32,
+    // The radius of the light's source, which softens its shadows.
+    pub source_radius: f32,
+    // The views the light illuminates, as a bitmask of render layers.
+    pub render_layers: u32,
+    // How much of the light its shadows block, between zero and one.
+    pub shadow_strength: f32,
+    // The distance the edges of the light's shadows are blurred over.
+    pub shadow_softness: f32,
+    // The index of the light's extras within the light extras buffer, or `NO_LIGHT_EXTRAS`.
+    pub extras: u32,
 }
 
 #[derive(Component, Default, Clone, ShaderType)]
 pub struct ExtractedSpotLight2d {
     pub center: Vec2,
     pub radius: f32,
+    // The light's height above the 2D plane, which is its z component in 3D.
+    pub height: f32,
     pub color: LinearRgba,
     pub intensity: f32,
     pub falloff: f32,
@@ -29,123 +109,2438 @@ pub struct ExtractedSpotLight2d {
     pub inner_angle: f32,
     pub outer_angle: f32,
     pub source_width: f32,
-    pub cast_shadows: u32,
+    // The light's `LIGHT_FLAG_*` bits and falloff curve.
+    pub flags: u32,
+    pub occlusion_layers: u32,
+    // Range of the occluder owners this light ignores within the occluder exclusion buffer.
+    pub exclusion_offset: u32,
+    pub exclusion_count: u32,
+    // The brightness of the light scattered by the light's beam, or zero if it isn't volumetric.
+    pub volumetric_intensity: f32,
+    // The exponent of the light's falloff curve, or its attenuation exponent if it's an
+    // inverse square curve.
+    pub falloff_exponent: f32,
+    // The radius within which the light is at its full intensity.
+    pub inner_radius: f32,
+    // The views the light illuminates, as a bitmask of render layers.
+    pub render_layers: u32,
+    // How much of the light its shadows block, between zero and one.
+    pub shadow_strength: f32,
+    // The distance the edges of the light's shadows are blurred over.
+    pub shadow_softness: f32,
+    // The exponent the light's fade between its inner and outer angles is raised to.
+    pub edge_exponent: f32,
+    // The index of the light's extras within the light extras buffer, or `NO_LIGHT_EXTRAS`.
+    pub extras: u32,
+}
+
+/// The data only some point and spot lights use, which is kept in a separate buffer so that
+/// more lights fit in a uniform buffer on WebGL2. Lights without a cookie, projection texture,
+/// noise texture, edge color or brightness limit have no extras.
+#[derive(Clone, Copy, ShaderType)]
+pub struct ExtractedLightExtras2d {
+    // The texture coordinates of a point light's cookie, or a spot light's projection texture,
+    // within the light cookie atlas.
+    pub cookie_rect: Vec4,
+    // The noise texture's texture coordinates within the light cookie atlas, or zero if the
+    // light has no noise texture.
+    pub noise_rect: Vec4,
+    // The light's color at its radius, with an alpha of zero if the light is a single color.
+    pub edge_color: Vec4,
+    // The cookie's rotation, as the cosine and sine of its angle.
+    pub cookie_rotation: Vec2,
+    // The offset of the cookie's image, as a fraction of its size, or zero if it isn't offset.
+    pub cookie_offset: Vec2,
+    // The world space size of each tile of the noise texture, and the speed it scrolls at, in
+    // world units per second.
+    pub noise_scale: Vec2,
+    pub noise_scroll: Vec2,
+    // The world space radius a point light's cookie covers, or zero if the light has no cookie.
+    pub cookie_radius: f32,
+    // The largest linear luminance of the light's contribution.
+    pub max_brightness: f32,
+}
+
+impl Default for ExtractedLightExtras2d {
+    fn default() -> Self {
+        Self {
+            cookie_rect: Vec4::ZERO,
+            noise_rect: Vec4::ZERO,
+            edge_color: Vec4::ZERO,
+            cookie_rotation: Vec2::X,
+            cookie_offset: Vec2::ZERO,
+            noise_scale: Vec2::ONE,
+            noise_scroll: Vec2::ZERO,
+            cookie_radius: 0.0,
+            max_brightness: f32::MAX,
+        }
+    }
+}
+
+impl ExtractedLightExtras2d {
+    /// Whether the extras change how the light is drawn, so that lights which don't use them
+    /// don't take up space in the light extras buffer.
+    fn is_used(&self) -> bool {
+        self.cookie_rect != Vec4::ZERO
+            || self.noise_rect != Vec4::ZERO
+            || self.edge_color.w != 0.0
+            || self.max_brightness < f32::MAX
+    }
 }
 
 pub fn extract_spot_lights(
     mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    mut light_extras_buffer: ResMut<LightExtrasBuffer>,
+    mut light_cookie_atlas: ResMut<LightCookieAtlas>,
+    images: Extract<Res<Assets<Image>>>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    demoted_lights: Extract<Res<DemotedLights>>,
     q: Extract<
         Query<(
+            Entity,
             &RenderEntity,
             &SpotLight2d,
             &GlobalTransform,
             &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Has<VisionSource2d>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
         )>,
     >,
 ) {
-    for (render_entity, spot_light, global_transform, view_visibility) in &q {
-        if !view_visibility.get() {
+    for (
+        entity,
+        render_entity,
+        spot_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        vision_source,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &q
+    {
+        // Extracted lights are retained on their render entities, so lights which are hidden,
+        // disabled or culled by their level of detail have their extracted light removed,
+        // rather than being drawn as they were. Static lights are drawn by the baked lightmap
+        // instead.
+        if !view_visibility.get()
+            || !spot_light.enabled
+            || demoted_lights.culled.contains(&entity)
+            || is_static
+        {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedSpotLight2d>();
             continue;
         }
-        let direction_radians = spot_light.direction.to_radians();
-        let inner_radians = spot_light.inner_angle.to_radians();
-        let outer_radians = spot_light.outer_angle.to_radians();
-        let spotlight_direction = Vec2::from_angle(direction_radians);
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            spot_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        let (_, inner_angle, outer_angle) = spot_light.normalized_angles();
+        if inner_angle != spot_light.inner_angle || outer_angle != spot_light.outer_angle {
+            warn_once!(
+                "A spot light has an inner angle of {} and an outer angle of {}, which were \
+                adjusted to {} and {}. Spot light angles are half angles between 0 and 180 \
+                degrees, and the inner angle must not be larger than the outer angle.",
+                spot_light.inner_angle,
+                spot_light.outer_angle,
+                inner_angle,
+                outer_angle
+            );
+        }
+        let inner_radians = inner_angle.to_radians();
+        let outer_radians = outer_angle.to_radians();
+        let spotlight_direction = spot_light.world_direction(global_transform);
+        // Lights whose projection texture isn't available are drawn without it.
+        let projection_rect = spot_light
+            .projection_texture
+            .as_ref()
+            .and_then(|texture| light_cookie_atlas.get_or_insert(texture.id(), &images));
+        let (noise_rect, noise_scale, noise_scroll) = noise_params(
+            spot_light.modulation.as_ref(),
+            &mut light_cookie_atlas,
+            &images,
+        );
+        let (falloff_curve, falloff_exponent) =
+            falloff_curve_params(spot_light.falloff_curve, spot_light.attenuation_exponent);
+        // Lights whose shadows have no strength skip raymarching entirely.
+        let shadow_strength = spot_light.shadow_strength.clamp(0.0, 1.0);
+        let radius_scale = spot_light.radius_scale(global_transform);
+        let radius = spot_light.radius * radius_scale;
+        let modulation = modulation.copied().unwrap_or_default();
+        let mut flags = falloff_curve << LIGHT_FLAG_FALLOFF_CURVE_SHIFT;
+        if spot_light.cast_shadows
+            && shadow_strength > 0.0
+            && !demoted_lights.shadowless.contains(&entity)
+        {
+            flags |= LIGHT_FLAG_CAST_SHADOWS;
+        }
+        if vision_source {
+            flags |= LIGHT_FLAG_VISION;
+        }
+        if projection_rect.is_some() {
+            flags |= LIGHT_FLAG_PROJECTION;
+        }
+        let extras = extract_light_extras(
+            ExtractedLightExtras2d {
+                cookie_rect: projection_rect.unwrap_or_default(),
+                noise_rect: noise_rect.unwrap_or_default(),
+                edge_color: edge_color_params(spot_light.edge_color, modulation.color),
+                noise_scale,
+                noise_scroll,
+                max_brightness: spot_light.max_brightness.unwrap_or(f32::MAX),
+                ..default()
+            },
+            &mut light_extras_buffer,
+        );
         commands
             .entity(render_entity.id())
             .insert(ExtractedSpotLight2d {
-                center: global_transform.translation().xy(),
-                radius: spot_light.radius,
-                color: spot_light.color.to_linear(),
-                intensity: spot_light.intensity,
+                center: global_transform.translation().xy() + modulation.offset,
+                radius,
+                height: spot_light.world_height(global_transform),
+                color: spot_light.color.to_linear() * modulation.color,
+                intensity: spot_light
+                    .intensity_unit
+                    .to_intensity(spot_light.intensity, radius)
+                    * modulation.intensity,
                 falloff: spot_light.falloff,
                 direction: spotlight_direction,
                 inner_angle: inner_radians,
                 outer_angle: outer_radians,
-                source_width: spot_light.source_width,
-                cast_shadows: if spot_light.cast_shadows { 1 } else { 0 },
+                source_width: spot_light.source_width * radius_scale,
+                flags,
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                volumetric_intensity: if spot_light.volumetric {
+                    spot_light.volumetric_intensity.max(0.0)
+                } else {
+                    0.0
+                },
+                falloff_exponent,
+                inner_radius: (spot_light.inner_radius * radius_scale).clamp(0.0, radius.max(0.0)),
+                render_layers: render_layers_mask(render_layers),
+                shadow_strength,
+                shadow_softness: spot_light.shadow_softness.max(0.0) * radius_scale,
+                edge_exponent: spot_light.normalized_edge_exponent(),
+                extras,
+            });
+    }
+}
+
+#[derive(Component, Default, Clone, ShaderType)]
+pub struct ExtractedLineLight2d {
+    // World space endpoints of the light's segment.
+    pub start: Vec2,
+    pub end: Vec2,
+    pub radius: f32,
+    pub color: LinearRgba,
+    pub intensity: f32,
+    pub falloff: f32,
+    pub cast_shadows: u32,
+    pub occlusion_layers: u32,
+    // Range of the occluder owners this light ignores within the occluder exclusion buffer.
+    pub exclusion_offset: u32,
+    pub exclusion_count: u32,
+    // The radius of a glow around the end of the segment, used by beam lights.
+    pub end_glow_radius: f32,
+    // The views the light illuminates, as a bitmask of render layers.
+    pub render_layers: u32,
+}
+
+pub fn extract_line_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    line_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &LineLight2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        render_entity,
+        line_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &line_light_query
+    {
+        // Extracted lights are retained on their render entities, so hidden and static lights
+        // have their extracted light removed.
+        if !view_visibility.get() || is_static {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedLineLight2d>();
+            continue;
+        }
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            line_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        let half_length = line_light.length.max(0.0) * 0.5;
+        let modulation = modulation.copied().unwrap_or_default();
+        let to_world = |x: f32| {
+            global_transform
+                .transform_point(Vec3::new(x, 0.0, 0.0))
+                .xy()
+                + modulation.offset
+        };
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedLineLight2d {
+                start: to_world(-half_length),
+                end: to_world(half_length),
+                radius: line_light.radius,
+                color: line_light.color.to_linear() * modulation.color,
+                intensity: line_light.intensity * modulation.intensity,
+                falloff: line_light.falloff,
+                cast_shadows: if line_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                end_glow_radius: 0.0,
+                render_layers: render_layers_mask(render_layers),
+            });
+    }
+}
+
+/// Capsule lights are drawn as line lights, whose illumination is already rounded at the ends.
+pub fn extract_capsule_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    capsule_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &CapsuleLight2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        render_entity,
+        capsule_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &capsule_light_query
+    {
+        // Extracted lights are retained on their render entities, so hidden and static lights
+        // have their extracted light removed.
+        if !view_visibility.get() || is_static {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedLineLight2d>();
+            continue;
+        }
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            capsule_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        let half_length = capsule_light.half_length.max(0.0);
+        let modulation = modulation.copied().unwrap_or_default();
+        let to_world = |x: f32| {
+            global_transform
+                .transform_point(Vec3::new(x, 0.0, 0.0))
+                .xy()
+                + modulation.offset
+        };
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedLineLight2d {
+                start: to_world(-half_length),
+                end: to_world(half_length),
+                radius: capsule_light.radius,
+                color: capsule_light.color.to_linear() * modulation.color,
+                intensity: capsule_light.intensity * modulation.intensity,
+                falloff: capsule_light.falloff,
+                cast_shadows: if capsule_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                end_glow_radius: 0.0,
+                render_layers: render_layers_mask(render_layers),
+            });
+    }
+}
+
+/// Trail lights are drawn as a line light for each segment of their trail, dimmed by the age of
+/// the segment. Trails don't have a render entity of their own, so each segment is spawned as a
+/// temporary render entity, which is despawned at the end of the frame.
+pub fn extract_trail_lights(
+    mut commands: Commands,
+    trail_light_query: Extract<
+        Query<(
+            &TrailLight2d,
+            &TrailPoints2d,
+            &ViewVisibility,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+        )>,
+    >,
+) {
+    for (trail_light, trail_points, view_visibility, modulation, render_layers) in
+        &trail_light_query
+    {
+        if !view_visibility.get() || trail_light.point_lifetime <= 0.0 {
+            continue;
+        }
+        let modulation = modulation.copied().unwrap_or_default();
+
+        let segments = trail_points
+            .points
+            .iter()
+            .zip(trail_points.points.iter().skip(1));
+        for (start, end) in segments {
+            let age = (start.age + end.age) * 0.5;
+            let fade = (1.0 - age / trail_light.point_lifetime).clamp(0.0, 1.0);
+            commands.spawn((
+                ExtractedLineLight2d {
+                    start: start.position + modulation.offset,
+                    end: end.position + modulation.offset,
+                    radius: trail_light.radius,
+                    color: trail_light.color.to_linear() * modulation.color,
+                    intensity: trail_light.intensity * modulation.intensity * fade,
+                    falloff: trail_light.falloff,
+                    cast_shadows: 0,
+                    occlusion_layers: OcclusionLayers::default().0,
+                    exclusion_offset: 0,
+                    exclusion_count: 0,
+                    end_glow_radius: 0.0,
+                    render_layers: render_layers_mask(render_layers),
+                },
+                TemporaryRenderEntity,
+            ));
+        }
+    }
+}
+
+/// Beam lights are drawn as line lights, with an optional glow around their end.
+pub fn extract_beam_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    beam_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &BeamLight2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        render_entity,
+        beam_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &beam_light_query
+    {
+        // Extracted lights are retained on their render entities, so hidden and static lights
+        // have their extracted light removed.
+        if !view_visibility.get() || is_static {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedLineLight2d>();
+            continue;
+        }
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            beam_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        let (start, end) = beam_light.endpoints(global_transform);
+        let modulation = modulation.copied().unwrap_or_default();
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedLineLight2d {
+                start: start + modulation.offset,
+                end: end + modulation.offset,
+                radius: beam_light.thickness.max(0.0) * 0.5,
+                color: beam_light.color.to_linear() * modulation.color,
+                intensity: beam_light.intensity * modulation.intensity,
+                falloff: beam_light.lateral_falloff,
+                cast_shadows: if beam_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                end_glow_radius: beam_light.impact_glow_radius.max(0.0),
+                render_layers: render_layers_mask(render_layers),
             });
     }
 }
 
 #[derive(Component, Default, Clone, ShaderType)]
+pub struct ExtractedPolygonLight2d {
+    pub color: LinearRgba,
+    pub radius: f32,
+    pub intensity: f32,
+    pub falloff: f32,
+    // Range of the light's world space vertices within the polygon light vertex buffer.
+    pub vertex_offset: u32,
+    pub vertex_count: u32,
+    pub cast_shadows: u32,
+    pub occlusion_layers: u32,
+    // Range of the occluder owners this light ignores within the occluder exclusion buffer.
+    pub exclusion_offset: u32,
+    pub exclusion_count: u32,
+    // Whether the vertices form a closed polygon, rather than an open polyline.
+    pub closed: u32,
+    // The views the light illuminates, as a bitmask of render layers.
+    pub render_layers: u32,
+}
+
+pub fn extract_polygon_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    mut polygon_light_vertex_buffer: ResMut<PolygonLightVertexBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    polygon_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &PolygonLight2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
+        )>,
+    >,
+) {
+    polygon_light_vertex_buffer.vertices.clear();
+
+    for (
+        entity,
+        render_entity,
+        polygon_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &polygon_light_query
+    {
+        // Extracted lights are retained on their render entities, so lights which aren't drawn
+        // this frame have their extracted light removed, rather than drawing with a vertex
+        // range from a previous frame.
+        if !view_visibility.get() || is_static || polygon_light.vertices.len() < 3 {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPolygonLight2d>();
+            continue;
+        }
+
+        if polygon_light.vertices.len() > MAX_POLYGON_LIGHT_VERTICES {
+            warn_once!(
+                "Polygon light has {} vertices, but at most {MAX_POLYGON_LIGHT_VERTICES} are \
+                supported. Extra vertices will be ignored.",
+                polygon_light.vertices.len()
+            );
+        }
+        let vertices =
+            &polygon_light.vertices[..polygon_light.vertices.len().min(MAX_POLYGON_LIGHT_VERTICES)];
+
+        let vertex_offset = polygon_light_vertex_buffer.vertices.len();
+        if vertex_offset + vertices.len() > MAX_POLYGON_LIGHT_BUFFER_VERTICES {
+            warn_once!(
+                "Polygon lights exceed the limit of {MAX_POLYGON_LIGHT_BUFFER_VERTICES} vertices. \
+                Some polygon lights won't be drawn."
+            );
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPolygonLight2d>();
+            continue;
+        }
+        let modulation = modulation.copied().unwrap_or_default();
+        polygon_light_vertex_buffer
+            .vertices
+            .extend(vertices.iter().map(|vertex| {
+                global_transform.transform_point(vertex.extend(0.0)).xy() + modulation.offset
+            }));
+
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            polygon_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedPolygonLight2d {
+                color: polygon_light.color.to_linear() * modulation.color,
+                radius: polygon_light.radius,
+                intensity: polygon_light.intensity * modulation.intensity,
+                falloff: polygon_light.falloff,
+                vertex_offset: vertex_offset as u32,
+                vertex_count: vertices.len() as u32,
+                cast_shadows: if polygon_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                closed: 1,
+                render_layers: render_layers_mask(render_layers),
+            });
+    }
+}
+
+/// Polyline lights are drawn as open polygon lights, sharing the polygon light vertex buffer.
+pub fn extract_polyline_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    mut polygon_light_vertex_buffer: ResMut<PolygonLightVertexBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    polyline_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &PolylineLight2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        render_entity,
+        polyline_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &polyline_light_query
+    {
+        // Extracted lights are retained on their render entities, so lights which aren't drawn
+        // this frame have their extracted light removed, rather than drawing with a vertex
+        // range from a previous frame.
+        if !view_visibility.get() || is_static || polyline_light.points.is_empty() {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPolygonLight2d>();
+            continue;
+        }
+
+        if polyline_light.points.len() > MAX_POLYGON_LIGHT_VERTICES {
+            warn_once!(
+                "Polyline light has {} points, but at most {MAX_POLYGON_LIGHT_VERTICES} are \
+                supported. Extra points will be ignored.",
+                polyline_light.points.len()
+            );
+        }
+        let points =
+            &polyline_light.points[..polyline_light.points.len().min(MAX_POLYGON_LIGHT_VERTICES)];
+
+        let vertex_offset = polygon_light_vertex_buffer.vertices.len();
+        if vertex_offset + points.len() > MAX_POLYGON_LIGHT_BUFFER_VERTICES {
+            warn_once!(
+                "Polygon and polyline lights exceed the limit of \
+                {MAX_POLYGON_LIGHT_BUFFER_VERTICES} vertices. Some polyline lights won't be drawn."
+            );
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPolygonLight2d>();
+            continue;
+        }
+        let modulation = modulation.copied().unwrap_or_default();
+        polygon_light_vertex_buffer
+            .vertices
+            .extend(points.iter().map(|point| {
+                global_transform.transform_point(point.extend(0.0)).xy() + modulation.offset
+            }));
+
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            polyline_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedPolygonLight2d {
+                color: polyline_light.color.to_linear() * modulation.color,
+                radius: polyline_light.radius,
+                intensity: polyline_light.intensity * modulation.intensity,
+                falloff: polyline_light.falloff,
+                vertex_offset: vertex_offset as u32,
+                vertex_count: points.len() as u32,
+                cast_shadows: if polyline_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                closed: 0,
+                render_layers: render_layers_mask(render_layers),
+            });
+    }
+}
+
+/// Area lights are drawn as closed polygon lights with the four corners of their rectangle,
+/// sharing the polygon light vertex buffer.
+pub fn extract_area_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    mut polygon_light_vertex_buffer: ResMut<PolygonLightVertexBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    area_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &AreaLight2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        render_entity,
+        area_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &area_light_query
+    {
+        // Extracted lights are retained on their render entities, so hidden and static lights
+        // have their extracted light removed.
+        if !view_visibility.get() || is_static {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPolygonLight2d>();
+            continue;
+        }
+
+        let vertex_offset = polygon_light_vertex_buffer.vertices.len();
+        if vertex_offset + 4 > MAX_POLYGON_LIGHT_BUFFER_VERTICES {
+            warn_once!(
+                "Polygon, polyline and area lights exceed the limit of \
+                {MAX_POLYGON_LIGHT_BUFFER_VERTICES} vertices. Some area lights won't be drawn."
+            );
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPolygonLight2d>();
+            continue;
+        }
+        let modulation = modulation.copied().unwrap_or_default();
+        let center = global_transform.translation().xy() + modulation.offset;
+        let half_size = area_light.half_size.max(Vec2::ZERO) * global_transform.scale().xy().abs();
+        let rotation = rotation_2d(global_transform);
+        polygon_light_vertex_buffer.vertices.extend(
+            [
+                Vec2::new(-1.0, -1.0),
+                Vec2::new(1.0, -1.0),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(-1.0, 1.0),
+            ]
+            .map(|corner| center + rotation.rotate(corner * half_size)),
+        );
+
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            area_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedPolygonLight2d {
+                color: area_light.color.to_linear() * modulation.color,
+                radius: area_light.radius,
+                intensity: area_light.intensity * modulation.intensity,
+                falloff: area_light.falloff,
+                vertex_offset: vertex_offset as u32,
+                vertex_count: 4,
+                cast_shadows: if area_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                closed: 1,
+                render_layers: render_layers_mask(render_layers),
+            });
+    }
+}
+
+#[derive(Component, Default, Clone, ShaderType)]
+pub struct ExtractedRingLight2d {
+    pub center: Vec2,
+    pub radius: f32,
+    pub thickness: f32,
+    pub color: LinearRgba,
+    pub intensity: f32,
+    pub falloff: f32,
+    // World space direction of the middle of the lit arc, and half of the arc's angle.
+    pub arc_direction: Vec2,
+    pub arc_half_angle: f32,
+    pub cast_shadows: u32,
+    pub occlusion_layers: u32,
+    // Range of the occluder owners this light ignores within the occluder exclusion buffer.
+    pub exclusion_offset: u32,
+    pub exclusion_count: u32,
+    // The views the light illuminates, as a bitmask of render layers.
+    pub render_layers: u32,
+}
+
+pub fn extract_ring_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    ring_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &RingLight2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        render_entity,
+        ring_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &ring_light_query
+    {
+        // Extracted lights are retained on their render entities, so hidden and static lights
+        // have their extracted light removed.
+        if !view_visibility.get() || is_static {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedRingLight2d>();
+            continue;
+        }
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            ring_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        let arc_start = ring_light.arc_start.to_radians();
+        let arc_end = ring_light.arc_end.to_radians();
+        let arc_direction =
+            Vec2::from_angle((arc_start + arc_end) * 0.5).rotate(rotation_2d(global_transform));
+        let modulation = modulation.copied().unwrap_or_default();
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedRingLight2d {
+                center: global_transform.translation().xy() + modulation.offset,
+                radius: ring_light.radius.max(0.0),
+                thickness: ring_light.thickness.max(0.0),
+                color: ring_light.color.to_linear() * modulation.color,
+                intensity: ring_light.intensity * modulation.intensity,
+                falloff: ring_light.falloff,
+                arc_direction,
+                arc_half_angle: ((arc_end - arc_start) * 0.5).clamp(0.0, PI),
+                cast_shadows: if ring_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                render_layers: render_layers_mask(render_layers),
+            });
+    }
+}
+
+#[derive(Component, Default, Clone, Copy, ShaderType)]
+pub struct ExtractedDirectionalLight2d {
+    pub color: LinearRgba,
+    // The world space direction the light shines towards.
+    pub direction: Vec2,
+    pub cast_shadows: u32,
+    pub occlusion_layers: u32,
+    // Range of the occluder owners this light ignores within the occluder exclusion buffer.
+    pub exclusion_offset: u32,
+    pub exclusion_count: u32,
+    // The views the light illuminates, as a bitmask of render layers.
+    pub render_layers: u32,
+}
+
+pub fn extract_directional_lights(
+    mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    directional_light_query: Extract<
+        Query<(
+            Entity,
+            &RenderEntity,
+            &DirectionalLight2d,
+            Option<&OcclusionLayers>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+        )>,
+    >,
+) {
+    for (entity, render_entity, directional_light, occlusion_layers, modulation, render_layers) in
+        &directional_light_query
+    {
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            false,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        // Directional lights have no position, so they aren't offset by their modulation.
+        let modulation = modulation.copied().unwrap_or_default();
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedDirectionalLight2d {
+                color: directional_light.color.to_linear()
+                    * modulation.color
+                    * (directional_light.illuminance * modulation.intensity),
+                direction: Vec2::from_angle(directional_light.direction.to_radians()),
+                cast_shadows: if directional_light.cast_shadows { 1 } else { 0 },
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                render_layers: render_layers_mask(render_layers),
+            });
+    }
+}
+
+#[derive(Component, Default, Clone, ShaderType)]
+pub struct ExtractedEmissiveSprite2d {
+    // Rows of the matrix from world space into the sprite's local space, where the sprite
+    // covers -0.5 to 0.5 along both axes.
+    pub world_to_local_x: Vec2,
+    pub world_to_local_y: Vec2,
+    pub center: Vec2,
+    // The sprite's color, premultiplied by its alpha, and multiplied by its emissive color scale
+    // and intensity.
+    pub color: LinearRgba,
+    // The image's texture coordinates within the light cookie atlas, swapped along flipped axes.
+    pub uv_rect: Vec4,
+}
+
+/// Extracts the sprites marked with [`EmissiveSprite2d`].
+///
+/// Extracted sprites are retained on their render entities, so sprites which are hidden, or
+/// whose image isn't available, have their extracted sprite removed.
+pub fn extract_emissive_sprites(
+    mut commands: Commands,
+    mut light_cookie_atlas: ResMut<LightCookieAtlas>,
+    images: Extract<Res<Assets<Image>>>,
+    texture_atlas_layouts: Extract<Res<Assets<TextureAtlasLayout>>>,
+    emissive_sprite_query: Extract<
+        Query<(
+            &RenderEntity,
+            &EmissiveSprite2d,
+            &Sprite,
+            &Anchor,
+            &GlobalTransform,
+            &ViewVisibility,
+        )>,
+    >,
+) {
+    for (render_entity, emissive_sprite, sprite, anchor, global_transform, view_visibility) in
+        &emissive_sprite_query
+    {
+        let extracted_sprite = view_visibility
+            .get()
+            .then(|| {
+                extract_emissive_sprite(
+                    emissive_sprite,
+                    sprite,
+                    anchor,
+                    global_transform,
+                    &mut light_cookie_atlas,
+                    &images,
+                    &texture_atlas_layouts,
+                )
+            })
+            .flatten();
+
+        let mut entity_commands = commands.entity(render_entity.id());
+        match extracted_sprite {
+            Some(extracted_sprite) => entity_commands.insert(extracted_sprite),
+            None => entity_commands.remove::<ExtractedEmissiveSprite2d>(),
+        };
+    }
+}
+
+fn extract_emissive_sprite(
+    emissive_sprite: &EmissiveSprite2d,
+    sprite: &Sprite,
+    anchor: &Anchor,
+    global_transform: &GlobalTransform,
+    light_cookie_atlas: &mut LightCookieAtlas,
+    images: &Assets<Image>,
+    texture_atlas_layouts: &Assets<TextureAtlasLayout>,
+) -> Option<ExtractedEmissiveSprite2d> {
+    let image_size = images.get(sprite.image.id())?.size().as_vec2();
+    let cookie_rect = light_cookie_atlas.get_or_insert(sprite.image.id(), images)?;
+
+    // The region of the image shown by the sprite, in pixels. A sprite's rect is relative to
+    // its texture atlas frame.
+    let atlas_rect = sprite
+        .texture_atlas
+        .as_ref()
+        .and_then(|texture_atlas| texture_atlas.texture_rect(texture_atlas_layouts))
+        .map(|rect| rect.as_rect());
+    let rect = match (atlas_rect, sprite.rect) {
+        (None, None) => Rect::from_corners(Vec2::ZERO, image_size),
+        (Some(rect), None) | (None, Some(rect)) => rect,
+        (Some(atlas_rect), Some(rect)) => {
+            Rect::from_corners(atlas_rect.min + rect.min, atlas_rect.min + rect.max)
+        }
+    };
+    let size = sprite.custom_size.unwrap_or(rect.size());
+
+    let to_atlas = |point: Vec2| {
+        cookie_rect.xy() + (cookie_rect.zw() - cookie_rect.xy()) * (point / image_size)
+    };
+    let mut uv_min = to_atlas(rect.min);
+    let mut uv_max = to_atlas(rect.max);
+    if sprite.flip_x {
+        core::mem::swap(&mut uv_min.x, &mut uv_max.x);
+    }
+    if sprite.flip_y {
+        core::mem::swap(&mut uv_min.y, &mut uv_max.y);
+    }
+
+    let affine = global_transform.affine();
+    let axis_x = affine.transform_vector3(Vec3::X * size.x).xy();
+    let axis_y = affine.transform_vector3(Vec3::Y * size.y).xy();
+    let local_to_world = Mat2::from_cols(axis_x, axis_y);
+    // Sprites with no area can't be seen, so they don't emit light.
+    if local_to_world.determinant().abs() <= f32::EPSILON {
+        return None;
+    }
+    let world_to_local = local_to_world.inverse();
+
+    // Translucent sprites emit less light.
+    let sprite_color = sprite.color.to_linear();
+    let color = sprite_color.to_vec3()
+        * sprite_color.alpha
+        * emissive_sprite.color_scale.to_linear().to_vec3()
+        * emissive_sprite.intensity;
+
+    Some(ExtractedEmissiveSprite2d {
+        world_to_local_x: world_to_local.row(0),
+        world_to_local_y: world_to_local.row(1),
+        center: global_transform
+            .transform_point((-anchor.as_vec() * size).extend(0.0))
+            .xy(),
+        color: LinearRgba::from_vec3(color),
+        uv_rect: Vec4::new(uv_min.x, uv_min.y, uv_max.x, uv_max.y),
+    })
+}
+
+// Segments are given a small thickness, so that rays can't slip through them between
+// samples of the SDF texture.
+const SEGMENT_HALF_THICKNESS: f32 = 0.5;
+
+pub const OCCLUDER_SHAPE_RECTANGLE: u32 = 0;
+pub const OCCLUDER_SHAPE_CAPSULE: u32 = 1;
+pub const OCCLUDER_SHAPE_POLYGON: u32 = 2;
+pub const OCCLUDER_SHAPE_TRIANGLES: u32 = 3;
+pub const OCCLUDER_SHAPE_ELLIPSE: u32 = 4;
+pub const OCCLUDER_SHAPE_ANNULUS: u32 = 5;
+pub const OCCLUDER_SHAPE_ALPHA_MASK: u32 = 6;
+pub const OCCLUDER_SHAPE_SDF: u32 = 7;
+
+#[derive(Default, Clone, ShaderType)]
 pub struct ExtractedLightOccluder2d {
-    pub half_size: Vec2,
     pub center: Vec2,
+    // The occluder's rotation, as the cosine and sine of its angle.
+    pub rotation: Vec2,
+    // The parameters of the occluder's shape, as the bits of its half size, the world space
+    // endpoints of a capsule's inner segment, or the range of a polygon's vertices within the
+    // occluder vertex buffer. Alpha masks also pack their texture coordinates within the
+    // occluder mask atlas into the last two components.
+    pub shape_params: UVec4,
+    // The world space direction a one-sided occluder faces, or zero if the occluder is
+    // two-sided.
+    pub facing: Vec2,
+    pub radius: f32,
+    // The distance the occluder's shape is inflated by, or shrunk by if negative.
+    pub margin: f32,
+    // The thickness of a hollow occluder's border, or negative if the occluder is solid.
+    pub border_thickness: f32,
+    pub softness: f32,
+    pub shape: u32,
+    // The index of the occluder's main world entity, so that lights can ignore their own
+    // occluders.
+    pub owner: u32,
+    // The lights blocked by this occluder, as an `OcclusionLayers` bitmask.
+    pub occlusion_layers: u32,
+    // The views the occluder casts shadows in, as a bitmask of render layers.
+    pub render_layers: u32,
+    pub opacity: f32,
+    // The length of the occluder's shadows relative to their distance from the light, or zero
+    // if they're unlimited.
+    pub height: f32,
+    // The furthest distance the occluder's shadows reach, or negative if they're unlimited.
+    pub max_shadow_distance: f32,
+    // The fraction of light absorbed per world unit travelled through the occluder.
+    pub absorption: f32,
+    // The color of light that passes through the occluder, or black if untinted, packed by
+    // `pack_unorm8x4`.
+    pub tint: u32,
+    // The color that blocked light is modulated by, or black if shadows are black, packed by
+    // `pack_unorm8x4`.
+    pub shadow_color: u32,
 }
 
 #[derive(Component, Default, Clone, ShaderType)]
 pub struct ExtractedAmbientLight2d {
+    // The camera's ambient light, plus every global light. This is the bottom of the ambient
+    // light's gradient, if it has one.
     pub color: LinearRgba,
+    // The world space region covered by the camera's baked lightmap, which is empty when the
+    // camera doesn't have one.
+    pub baked_lightmap_min: Vec2,
+    pub baked_lightmap_max: Vec2,
+    // The factor the view's lighting is multiplied by.
+    pub exposure: f32,
+    // The camera's render layers, as a bitmask.
+    pub render_layers: u32,
+    // How the light of overlapping lights is combined, as a `LightBlend2d`.
+    pub blend: u32,
+    // The largest linear luminance of the view's lighting.
+    pub max_brightness: f32,
+    // The top of the ambient light's gradient, plus every global light.
+    pub gradient_top: LinearRgba,
+    // The world space heights of the bottom and top of the ambient light's gradient.
+    pub gradient_range: Vec2,
+    // Whether the ambient light has a gradient: 0 for none, 1 for a gradient across the screen
+    // and 2 for a gradient across `gradient_range`.
+    pub gradient: u32,
+    // The world space region covered by the ambient light's map, which is empty when it
+    // doesn't have one.
+    pub ambient_map_min: Vec2,
+    pub ambient_map_max: Vec2,
+    // The distance from occluders within which the ambient light is darkened, and by how much.
+    pub ambient_occlusion_radius: f32,
+    pub ambient_occlusion_strength: f32,
+}
+
+/// The settings of a camera's [`Light2d`] which aren't uploaded to the GPU.
+#[derive(Component, Default, Clone)]
+pub struct ExtractedLight2d {
+    pub emissive_spill: u32,
+    pub baked_lightmap: Option<AssetId<Image>>,
+    pub ambient_map: Option<AssetId<Image>>,
+    pub ambient_occlusion: bool,
+    pub vision_mask: Option<AssetId<Image>>,
 }
 
 pub fn extract_point_lights(
     mut commands: Commands,
+    mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>,
+    mut light_extras_buffer: ResMut<LightExtrasBuffer>,
+    mut light_cookie_atlas: ResMut<LightCookieAtlas>,
+    images: Extract<Res<Assets<Image>>>,
+    children_query: Extract<Query<&Children>>,
+    occluders_query: Extract<Query<(), With<LightOccluder2d>>>,
+    filtered_occluders_query: Extract<Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>>,
+    animation_time: Extract<Res<Light2dAnimationTime>>,
+    demoted_lights: Extract<Res<DemotedLights>>,
     point_light_query: Extract<
         Query<(
+            Entity,
             &RenderEntity,
             &PointLight2d,
             &GlobalTransform,
             &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Has<VisionSource2d>,
+            Option<&LightModulation2d>,
+            Option<&RenderLayers>,
+            Has<StaticLight2d>,
         )>,
     >,
 ) {
-    for (render_entity, point_light, global_transform, view_visibility) in &point_light_query {
-        if !view_visibility.get() {
+    for (
+        entity,
+        render_entity,
+        point_light,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        vision_source,
+        modulation,
+        render_layers,
+        is_static,
+    ) in &point_light_query
+    {
+        // Extracted lights are retained on their render entities, so lights which are hidden,
+        // disabled or culled by their level of detail have their extracted light removed,
+        // rather than being drawn as they were. Static lights are drawn by the baked lightmap
+        // instead.
+        if !view_visibility.get()
+            || !point_light.enabled
+            || demoted_lights.culled.contains(&entity)
+            || is_static
+        {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPointLight2d>();
             continue;
         }
+        let (exclusion_offset, exclusion_count) = extract_occluder_exclusions(
+            entity,
+            point_light.ignore_self_occlusion,
+            &children_query,
+            &occluders_query,
+            &filtered_occluders_query,
+            &mut occluder_exclusion_buffer,
+        );
+        // Lights whose cookie isn't available are drawn without it.
+        let cookie_rect = point_light
+            .cookie
+            .as_ref()
+            .and_then(|cookie| light_cookie_atlas.get_or_insert(cookie.id(), &images));
+        let rotation = rotation_2d(global_transform);
+        // Cookies with no speed stay exactly where they are. Offsets are wrapped, as the cookie
+        // repeats, so that scrolling cookies don't lose precision over time.
+        let elapsed = animation_time.elapsed_secs();
+        let cookie_rotation = Vec2::from_angle(
+            (point_light.cookie_rotation + point_light.cookie_rotation_speed * elapsed)
+                .to_radians(),
+        )
+        .rotate(rotation);
+        let cookie_offset = (point_light.cookie_offset + point_light.cookie_scroll_speed * elapsed)
+            .rem_euclid(Vec2::ONE);
+        let ellipticity = point_light.ellipticity.abs().max(Vec2::splat(f32::EPSILON));
+        let (noise_rect, noise_scale, noise_scroll) = noise_params(
+            point_light.modulation.as_ref(),
+            &mut light_cookie_atlas,
+            &images,
+        );
+        let (falloff_curve, falloff_exponent) =
+            falloff_curve_params(point_light.falloff_curve, point_light.attenuation_exponent);
+        // Lights whose shadows have no strength skip raymarching entirely.
+        let shadow_strength = point_light.shadow_strength.clamp(0.0, 1.0);
+        let radius_scale = point_light.radius_scale(global_transform);
+        let radius = point_light.radius * radius_scale;
+        let modulation = modulation.copied().unwrap_or_default();
+        let mut flags = falloff_curve << LIGHT_FLAG_FALLOFF_CURVE_SHIFT;
+        if point_light.cast_shadows
+            && shadow_strength > 0.0
+            && !demoted_lights.shadowless.contains(&entity)
+        {
+            flags |= LIGHT_FLAG_CAST_SHADOWS;
+        }
+        if vision_source {
+            flags |= LIGHT_FLAG_VISION;
+        }
+        let extras = extract_light_extras(
+            ExtractedLightExtras2d {
+                cookie_rect: cookie_rect.unwrap_or_default(),
+                noise_rect: noise_rect.unwrap_or_default(),
+                edge_color: edge_color_params(point_light.edge_color, modulation.color),
+                cookie_rotation,
+                cookie_offset,
+                noise_scale,
+                noise_scroll,
+                cookie_radius: if cookie_rect.is_some() {
+                    radius * point_light.cookie_scale.max(0.0)
+                } else {
+                    0.0
+                },
+                max_brightness: point_light.max_brightness.unwrap_or(f32::MAX),
+            },
+            &mut light_extras_buffer,
+        );
         commands
             .entity(render_entity.id())
             .insert(ExtractedPointLight2d {
-                color: point_light.color.to_linear(),
-                transform: global_transform.translation().xy(),
-                radius: point_light.radius,
-                intensity: point_light.intensity,
+                color: point_light.color.to_linear() * modulation.color,
+                transform: global_transform.translation().xy() + modulation.offset,
+                radius,
+                height: point_light.world_height(global_transform),
+                intensity: point_light
+                    .intensity_unit
+                    .to_intensity(point_light.intensity, radius)
+                    * modulation.intensity,
                 falloff: point_light.falloff,
-                cast_shadows: if point_light.cast_shadows { 1 } else { 0 },
+                ellipse_axis: rotation / ellipticity.x,
+                ellipse_aspect: ellipticity.x / ellipticity.y,
+                flags,
+                occlusion_layers: occlusion_layers.copied().unwrap_or_default().0,
+                exclusion_offset,
+                exclusion_count,
+                falloff_exponent,
+                inner_radius: (point_light.inner_radius * radius_scale).clamp(0.0, radius.max(0.0)),
+                source_radius: point_light.source_radius.max(0.0) * radius_scale,
+                render_layers: render_layers_mask(render_layers),
+                shadow_strength,
+                shadow_softness: point_light.shadow_softness.max(0.0) * radius_scale,
+                extras,
             });
     }
 }
 
-pub fn extract_light_occluders(
+/// Screen lights are drawn as point lights, which are moved into world space for each view by
+/// the light map pass.
+pub fn extract_screen_lights(
     mut commands: Commands,
-    light_occluders_query: Extract<
+    screen_light_query: Extract<
         Query<(
             &RenderEntity,
-            &LightOccluder2d,
-            &GlobalTransform,
+            &ScreenLight2d,
+            &InheritedVisibility,
+            Option<&RenderLayers>,
+            Option<&LightModulation2d>,
+        )>,
+    >,
+) {
+    for (render_entity, screen_light, inherited_visibility, render_layers, modulation) in
+        &screen_light_query
+    {
+        // Extracted lights are retained on their render entities, so hidden lights have their
+        // extracted light removed.
+        if !inherited_visibility.get() {
+            commands
+                .entity(render_entity.id())
+                .remove::<ExtractedPointLight2d>();
+            continue;
+        }
+        // Screen lights are anchored to the viewport, so they aren't offset by their modulation,
+        // which is in world space.
+        let modulation = modulation.copied().unwrap_or_default();
+        commands
+            .entity(render_entity.id())
+            .insert(ExtractedPointLight2d {
+                color: screen_light.color.to_linear() * modulation.color,
+                transform: screen_light.anchor,
+                radius: screen_light.radius_px.max(0.0),
+                height: DEFAULT_LIGHT_HEIGHT,
+                intensity: screen_light.intensity * modulation.intensity,
+                falloff: screen_light.falloff,
+                ellipse_axis: Vec2::X,
+                ellipse_aspect: 1.0,
+                flags: LIGHT_FLAG_SCREEN_SPACE,
+                occlusion_layers: OcclusionLayers::default().0,
+                exclusion_offset: 0,
+                exclusion_count: 0,
+                falloff_exponent: 2.0,
+                inner_radius: 0.0,
+                source_radius: 0.0,
+                render_layers: render_layers_mask(render_layers),
+                shadow_strength: 0.0,
+                shadow_softness: 0.0,
+                extras: NO_LIGHT_EXTRAS,
+            });
+    }
+}
+
+/// Resets the occluders ignored by each light, before any lights are extracted.
+pub fn clear_occluder_exclusions(mut occluder_exclusion_buffer: ResMut<OccluderExclusionBuffer>) {
+    occluder_exclusion_buffer.owners.clear();
+}
+
+/// Resets the extras of point and spot lights, before any lights are extracted.
+pub fn clear_light_extras(mut light_extras_buffer: ResMut<LightExtrasBuffer>) {
+    light_extras_buffer.extras.clear();
+}
+
+/// Records a light's extras, returning their index within the light extras buffer, or
+/// [`NO_LIGHT_EXTRAS`] if the light doesn't use any.
+fn extract_light_extras(
+    extras: ExtractedLightExtras2d,
+    light_extras_buffer: &mut LightExtrasBuffer,
+) -> u32 {
+    if !extras.is_used() {
+        return NO_LIGHT_EXTRAS;
+    }
+    light_extras_buffer.extras.push(extras);
+    (light_extras_buffer.extras.len() - 1) as u32
+}
+
+/// Frees up the light cookie atlas if it ran out of space, before any lights are extracted.
+pub fn reclaim_light_cookie_atlas(mut light_cookie_atlas: ResMut<LightCookieAtlas>) {
+    light_cookie_atlas.reclaim();
+}
+
+/// Records the occluders a light ignores, returning the range of the light's exclusions
+/// within the occluder exclusion buffer.
+///
+/// Lights ignoring self occlusion ignore the occluders on their own entity and its
+/// descendants. The hierarchy is walked every frame, so that lights follow changes to it, such
+/// as a torch being picked up or dropped. Lights also ignore occluders with an
+/// [`OccluderLightFilter`] which doesn't list them.
+fn extract_occluder_exclusions(
+    entity: Entity,
+    ignore_self_occlusion: bool,
+    children_query: &Query<&Children>,
+    occluders_query: &Query<(), With<LightOccluder2d>>,
+    filtered_occluders_query: &Query<(Entity, &OccluderLightFilter), With<LightOccluder2d>>,
+    occluder_exclusion_buffer: &mut OccluderExclusionBuffer,
+) -> (u32, u32) {
+    let exclusion_offset = occluder_exclusion_buffer.owners.len();
+
+    let own_occluders = ignore_self_occlusion
+        .then(|| iter::once(entity).chain(children_query.iter_descendants(entity)))
+        .into_iter()
+        .flatten()
+        .filter(|&entity| occluders_query.contains(entity));
+    let filtered_occluders = filtered_occluders_query
+        .iter()
+        .filter(|(_, filter)| !filter.blocks(entity))
+        .map(|(occluder, _)| occluder);
+
+    for occluder in own_occluders.chain(filtered_occluders) {
+        if occluder_exclusion_buffer.owners.len() >= MAX_OCCLUDER_EXCLUSIONS {
+            warn_once!(
+                "Lights exceed the limit of {MAX_OCCLUDER_EXCLUSIONS} ignored occluders. Some \
+                lights will be shadowed by occluders they should ignore."
+            );
+            break;
+        }
+
+        occluder_exclusion_buffer.owners.push(occluder.index());
+    }
+
+    (
+        exclusion_offset as u32,
+        (occluder_exclusion_buffer.owners.len() - exclusion_offset) as u32,
+    )
+}
+
+/// Local space triangulations of [`LightOccluder2dShape::Polygon`] occluders, keyed by main
+/// world entity and shape index, so that static polygons are only decomposed once.
+#[derive(Resource, Default)]
+pub struct PolygonOccluderCache {
+    pub triangles: HashMap<(Entity, usize), Vec<Vec2>>,
+}
+
+/// Occluders with shapes that couldn't be extracted yet, such as alpha masks whose images
+/// haven't loaded, which are extracted again every frame until they succeed.
+#[derive(Resource, Default)]
+pub struct IncompleteOccluders {
+    pub entities: HashSet<Entity>,
+}
+
+/// The extracted shapes of every visible [`LightOccluder2d`], keyed by main world entity.
+///
+/// Occluders are written directly into this resource rather than being inserted on their
+/// render entities, which avoids a command per occluder. They're sorted by entity, so that
+/// they're uploaded in a deterministic order.
+#[derive(Resource, Default)]
+pub struct ExtractedOccluders {
+    pub occluders: BTreeMap<Entity, ExtractedLightOccluders2d>,
+    /// Occluders marked with [`StaticLightOccluder2d`], which are uploaded to their own buffer.
+    pub static_occluders: BTreeMap<Entity, ExtractedLightOccluders2d>,
+    /// Whether any static occluders were extracted or removed this frame, requiring the static
+    /// occluder buffer to be rebuilt.
+    pub static_changed: bool,
+}
+
+impl ExtractedOccluders {
+    /// Removes an occluder's extracted shapes, returning whether it was static.
+    fn remove(&mut self, entity: Entity) -> bool {
+        self.occluders.remove(&entity);
+        self.static_occluders.remove(&entity).is_some()
+    }
+}
+
+/// The extracted shapes of a single [`LightOccluder2d`].
+#[derive(Component, Default, Clone)]
+pub struct ExtractedLightOccluders2d {
+    pub occluders: SmallVec<[ExtractedLightOccluder2d; 1]>,
+    /// World space polygon vertices of the shapes. Each shape's vertex offset is relative to
+    /// these vertices, until they're combined into the occluder vertex buffer.
+    pub vertices: Vec<Vec2>,
+    /// The distance field image used by the occluder's [`LightOccluder2dShape::Sdf`] shapes.
+    pub sdf_image: Option<AssetId<Image>>,
+}
+
+/// Extracts the shapes of visible [`LightOccluder2d`]s.
+///
+/// Extracted occluders are retained between frames, so occluders are only extracted again when
+/// they, their transform, or their occlusion layers change, or when they become visible.
+/// Static occluders cost nothing after they're first extracted.
+pub fn extract_light_occluders(
+    mut extracted_occluders: ResMut<ExtractedOccluders>,
+    mut polygon_cache: ResMut<PolygonOccluderCache>,
+    mut occluder_mask_atlas: ResMut<OccluderMaskAtlas>,
+    mut incomplete_occluders: ResMut<IncompleteOccluders>,
+    images: Extract<Res<Assets<Image>>>,
+    light_occluders_query: Extract<
+        Query<(
+            Entity,
+            Ref<LightOccluder2d>,
+            Ref<GlobalTransform>,
             &ViewVisibility,
+            Option<&OcclusionLayers>,
+            Option<&RenderLayers>,
+            Has<StaticLightOccluder2d>,
         )>,
     >,
 ) {
-    for (render_entity, light_occluder, global_transform, view_visibility) in &light_occluders_query
+    let extracted_occluders = extracted_occluders.as_mut();
+
+    extracted_occluders
+        .occluders
+        .retain(|entity, _| light_occluders_query.contains(*entity));
+
+    // Despawning a static occluder invalidates the static occluder buffer.
+    let static_count = extracted_occluders.static_occluders.len();
+    extracted_occluders
+        .static_occluders
+        .retain(|entity, _| light_occluders_query.contains(*entity));
+    extracted_occluders.static_changed = extracted_occluders.static_occluders.len() != static_count;
+    polygon_cache
+        .triangles
+        .retain(|(entity, _), _| light_occluders_query.contains(*entity));
+    incomplete_occluders
+        .entities
+        .retain(|entity| light_occluders_query.contains(*entity));
+
+    // Reclaiming the atlas moves every mask, so every occluder needs to be extracted again.
+    let atlas_reclaimed = occluder_mask_atlas.reclaim();
+
+    for (
+        entity,
+        light_occluder,
+        global_transform,
+        view_visibility,
+        occlusion_layers,
+        render_layers,
+        is_static,
+    ) in &light_occluders_query
     {
-        if !view_visibility.get() {
+        let opacity = light_occluder.opacity.clamp(0.0, 1.0);
+        let absorption = light_occluder.absorption.clamp(0.0, 1.0);
+        let tint = pack_unorm8x4(
+            light_occluder
+                .tint
+                .map_or(LinearRgba::BLACK, |tint| tint.to_linear()),
+        );
+        let shadow_color = pack_unorm8x4(
+            light_occluder
+                .shadow_color
+                .map_or(LinearRgba::BLACK, |shadow_color| shadow_color.to_linear()),
+        );
+        let facing = if light_occluder.one_sided {
+            (global_transform.rotation() * light_occluder.facing.extend(0.0))
+                .xy()
+                .normalize_or_zero()
+        } else {
+            Vec2::ZERO
+        };
+
+        // Occluders without any opacity or absorption have no effect, so they can be skipped
+        // entirely. Extracted occluders are retained between frames, so any previously
+        // extracted shapes need to be removed.
+        if !view_visibility.get()
+            || !light_occluder.enabled
+            || (opacity == 0.0 && absorption == 0.0)
+        {
+            extracted_occluders.static_changed |= extracted_occluders.remove(entity);
             continue;
         }
 
-        let extracted_occluder = match light_occluder.shape {
-            LightOccluder2dShape::Rectangle { half_size } => ExtractedLightOccluder2d {
-                half_size,
-                center: global_transform.translation().xy(),
-            },
+        let occlusion_layers = occlusion_layers.copied().unwrap_or_default().0;
+        let render_layers = render_layers_mask(render_layers);
+
+        // Removing an occluder's layers can't be detected with change detection, so the
+        // extracted layers are compared instead.
+        let previously_extracted = if is_static {
+            extracted_occluders.static_occluders.get(&entity)
+        } else {
+            extracted_occluders.occluders.get(&entity)
         };
 
-        commands
-            .entity(render_entity.id())
-            .insert(extracted_occluder);
+        if is_static && previously_extracted.is_some() && global_transform.is_changed() {
+            warn_once!(
+                "A static light occluder moved, which rebuilds every static occluder. Remove \
+                `StaticLightOccluder2d` from occluders that move."
+            );
+        }
+
+        if let Some(previously_extracted) = previously_extracted
+            && !atlas_reclaimed
+            && !light_occluder.is_changed()
+            && !global_transform.is_changed()
+            && !incomplete_occluders.entities.contains(&entity)
+            && previously_extracted.occluders.iter().all(|occluder| {
+                occluder.occlusion_layers == occlusion_layers
+                    && occluder.render_layers == render_layers
+            })
+        {
+            continue;
+        }
+
+        let shapes = iter::once((&light_occluder.shape, light_occluder.offset)).chain(
+            light_occluder.compound_shapes.iter().map(|compound_shape| {
+                (
+                    &compound_shape.shape,
+                    light_occluder.offset + compound_shape.offset,
+                )
+            }),
+        );
+
+        let mut extracted_occluder_shapes = ExtractedLightOccluders2d::default();
+        let mut incomplete = false;
+
+        for (index, (shape, offset)) in shapes.enumerate() {
+            if let LightOccluder2dShape::Polygon(vertices) = shape
+                && (light_occluder.is_changed()
+                    || !polygon_cache.triangles.contains_key(&(entity, index)))
+            {
+                polygon_cache
+                    .triangles
+                    .insert((entity, index), triangulate_or_bounds(vertices));
+            }
+
+            let Some(extracted_occluder) = extract_occluder_shape(
+                shape,
+                offset,
+                &global_transform,
+                polygon_cache.triangles.get(&(entity, index)),
+                &mut extracted_occluder_shapes.vertices,
+                &mut occluder_mask_atlas,
+                &images,
+            ) else {
+                incomplete |= matches!(shape, LightOccluder2dShape::AlphaMask { .. });
+                continue;
+            };
+
+            if let LightOccluder2dShape::Sdf { image, .. } = shape {
+                match extracted_occluder_shapes.sdf_image {
+                    None => extracted_occluder_shapes.sdf_image = Some(image.id()),
+                    Some(sdf_image) if sdf_image != image.id() => {
+                        warn_once!(
+                            "Only a single distance field image can be used by occluders. \
+                            Some distance field occluders will not cast shadows."
+                        );
+                        continue;
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            extracted_occluder_shapes
+                .occluders
+                .push(ExtractedLightOccluder2d {
+                    opacity,
+                    tint,
+                    facing,
+                    softness: light_occluder.softness.max(0.0),
+                    occlusion_layers,
+                    owner: entity.index(),
+                    height: light_occluder.height.max(0.0),
+                    max_shadow_distance: light_occluder
+                        .max_shadow_distance
+                        .map_or(-1.0, |distance| distance.max(0.0)),
+                    border_thickness: if light_occluder.hollow {
+                        light_occluder.border_thickness.max(0.0)
+                    } else {
+                        -1.0
+                    },
+                    shadow_color,
+                    margin: light_occluder.margin,
+                    absorption,
+                    render_layers,
+                    ..extracted_occluder
+                });
+        }
+
+        if incomplete {
+            incomplete_occluders.entities.insert(entity);
+        } else {
+            incomplete_occluders.entities.remove(&entity);
+        }
+
+        // Occluders that have been marked static, or had their marker removed, are moved
+        // between the maps.
+        extracted_occluders.static_changed |= extracted_occluders.remove(entity) || is_static;
+        if is_static {
+            extracted_occluders
+                .static_occluders
+                .insert(entity, extracted_occluder_shapes);
+        } else {
+            extracted_occluders
+                .occluders
+                .insert(entity, extracted_occluder_shapes);
+        }
+    }
+}
+
+/// Extracts a single occluder shape, offset from the occluder's origin in local space.
+fn extract_occluder_shape(
+    shape: &LightOccluder2dShape,
+    offset: Vec2,
+    global_transform: &GlobalTransform,
+    polygon_triangles: Option<&Vec<Vec2>>,
+    vertices: &mut Vec<Vec2>,
+    occluder_mask_atlas: &mut OccluderMaskAtlas,
+    images: &Assets<Image>,
+) -> Option<ExtractedLightOccluder2d> {
+    let to_world = |point: Vec2| {
+        global_transform
+            .transform_point((point + offset).extend(0.0))
+            .xy()
+    };
+    let center = to_world(Vec2::ZERO);
+    let rotation = rotation_2d(global_transform);
+    // Shapes are mirrored symmetrically, so negative scale can be ignored.
+    let scale = global_transform.scale().xy().abs();
+
+    let extracted_occluder = match shape {
+        LightOccluder2dShape::Rectangle { half_size } => ExtractedLightOccluder2d {
+            shape_params: half_size_params(*half_size * scale),
+            center,
+            rotation,
+            shape: OCCLUDER_SHAPE_RECTANGLE,
+            ..default()
+        },
+        LightOccluder2dShape::RoundedRectangle {
+            half_size,
+            corner_radius,
+        } => {
+            let half_size = *half_size * scale;
+            let corner_radius = corner_radius * scale.min_element();
+
+            ExtractedLightOccluder2d {
+                shape_params: half_size_params(half_size),
+                center,
+                rotation,
+                radius: corner_radius.clamp(0.0, half_size.min_element().max(0.0)),
+                shape: OCCLUDER_SHAPE_RECTANGLE,
+                ..default()
+            }
+        }
+        LightOccluder2dShape::Capsule {
+            half_length,
+            radius,
+        } => ExtractedLightOccluder2d {
+            center,
+            shape_params: segment_params(
+                to_world(Vec2::new(0.0, -half_length)),
+                to_world(Vec2::new(0.0, *half_length)),
+            ),
+            // The capsule's length is scaled along its axis, and its radius across it.
+            radius: radius * scale.x,
+            shape: OCCLUDER_SHAPE_CAPSULE,
+            ..default()
+        },
+        LightOccluder2dShape::Segment { start, end } => ExtractedLightOccluder2d {
+            center,
+            shape_params: segment_params(to_world(*start), to_world(*end)),
+            radius: SEGMENT_HALF_THICKNESS,
+            shape: OCCLUDER_SHAPE_CAPSULE,
+            ..default()
+        },
+        LightOccluder2dShape::Ellipse { half_size } => ExtractedLightOccluder2d {
+            shape_params: half_size_params(*half_size * scale),
+            center,
+            rotation,
+            shape: OCCLUDER_SHAPE_ELLIPSE,
+            ..default()
+        },
+        LightOccluder2dShape::Annulus {
+            inner_radius,
+            outer_radius,
+        } => {
+            if inner_radius > outer_radius {
+                warn_once!(
+                    "Annulus occluder has an inner radius ({inner_radius}) larger than its \
+                    outer radius ({outer_radius}). The radii will be swapped."
+                );
+            }
+
+            ExtractedLightOccluder2d {
+                // The inner and outer radius of the ring. Rings can't be stretched, so
+                // non-uniform scale uses the larger axis.
+                shape_params: half_size_params(
+                    Vec2::new(
+                        inner_radius.min(*outer_radius).max(0.0),
+                        inner_radius.max(*outer_radius).max(0.0),
+                    ) * scale.max_element(),
+                ),
+                center,
+                shape: OCCLUDER_SHAPE_ANNULUS,
+                ..default()
+            }
+        }
+        LightOccluder2dShape::Triangle { a, b, c } => {
+            return extract_triangle(center, [*a, *b, *c].map(to_world), vertices);
+        }
+        LightOccluder2dShape::ConvexPolygon(polygon) => {
+            return extract_convex_polygon(
+                center,
+                polygon.iter().copied().map(to_world),
+                polygon.len(),
+                vertices,
+            );
+        }
+        LightOccluder2dShape::Polygon(_) => {
+            return extract_triangles(
+                center,
+                polygon_triangles?.iter().copied().map(to_world),
+                vertices,
+            );
+        }
+        LightOccluder2dShape::AlphaMask { image, threshold } => {
+            // Images that haven't loaded yet don't occlude anything.
+            let mask = occluder_mask_atlas.get_or_bake(image.id(), *threshold, images)?;
+            let half_size = mask.image_size * 0.5 * scale;
+
+            ExtractedLightOccluder2d {
+                shape_params: alpha_mask_params(half_size, mask.uv_rect),
+                center,
+                rotation,
+                // The size of a mask texel in world units, using the smaller axis so that
+                // distances are never overestimated.
+                radius: (half_size * 2.0 / mask.mask_size.as_vec2()).min_element(),
+                shape: OCCLUDER_SHAPE_ALPHA_MASK,
+                ..default()
+            }
+        }
+        LightOccluder2dShape::Sdf { world_size, .. } => {
+            if world_size.cmple(Vec2::ZERO).any() {
+                return None;
+            }
+
+            ExtractedLightOccluder2d {
+                shape_params: half_size_params(*world_size * 0.5 * scale),
+                center,
+                rotation,
+                // Distances are scaled by the smaller axis, so that they're never
+                // overestimated.
+                radius: scale.min_element(),
+                shape: OCCLUDER_SHAPE_SDF,
+                ..default()
+            }
+        }
+    };
+
+    Some(extracted_occluder)
+}
+
+/// Returns the discriminant of a falloff curve in the light map pass, and its exponent, which
+/// is the light's attenuation exponent for the inverse square curve.
+fn falloff_curve_params(falloff_curve: FalloffCurve, attenuation_exponent: f32) -> (u32, f32) {
+    match falloff_curve {
+        FalloffCurve::InverseSquare => (0, attenuation_exponent.max(0.0)),
+        FalloffCurve::Linear => (1, 0.0),
+        FalloffCurve::Smoothstep => (2, 0.0),
+        FalloffCurve::Exponent(exponent) => (3, exponent.max(0.0)),
     }
 }
 
+/// Returns a light's noise texture coordinates within the light cookie atlas, or `None` if the
+/// light has no noise texture or it isn't available, along with the noise's scale and scroll
+/// speed. The noise is scrolled on the GPU, by the extracted [`Light2dAnimationTime`].
+fn noise_params(
+    noise: Option<&LightNoise2d>,
+    light_cookie_atlas: &mut LightCookieAtlas,
+    images: &Assets<Image>,
+) -> (Option<Vec4>, Vec2, Vec2) {
+    let Some(noise) = noise else {
+        return (None, Vec2::ONE, Vec2::ZERO);
+    };
+    (
+        light_cookie_atlas.get_or_insert(noise.texture.id(), images),
+        noise.scale.abs().max(Vec2::splat(f32::EPSILON)),
+        noise.scroll,
+    )
+}
+
+/// Returns a light's edge color for the light map pass, tinted by its modulation, with an alpha
+/// of zero if the light has no edge color.
+fn edge_color_params(edge_color: Option<Color>, modulation_color: LinearRgba) -> Vec4 {
+    match edge_color {
+        Some(edge_color) => (edge_color.to_linear() * modulation_color)
+            .to_vec3()
+            .extend(1.0),
+        None => Vec4::ZERO,
+    }
+}
+
+/// Returns the shape parameters of an occluder with a half size, or of an annulus with an inner
+/// and outer radius.
+fn half_size_params(half_size: Vec2) -> UVec4 {
+    UVec4::new(half_size.x.to_bits(), half_size.y.to_bits(), 0, 0)
+}
+
+/// Returns the shape parameters of a capsule between two world space points.
+fn segment_params(start: Vec2, end: Vec2) -> UVec4 {
+    UVec4::new(
+        start.x.to_bits(),
+        start.y.to_bits(),
+        end.x.to_bits(),
+        end.y.to_bits(),
+    )
+}
+
+/// Returns the shape parameters of an alpha mask, whose texture coordinates within the occluder
+/// mask atlas are packed as unorm16x2.
+fn alpha_mask_params(half_size: Vec2, uv_rect: Vec4) -> UVec4 {
+    UVec4::new(
+        half_size.x.to_bits(),
+        half_size.y.to_bits(),
+        pack_unorm16x2(uv_rect.xy()),
+        pack_unorm16x2(uv_rect.zw()),
+    )
+}
+
+/// Returns the shape parameters of a polygon occluder, from the range of its vertices.
+fn vertex_params(vertex_offset: usize, vertex_count: usize) -> UVec4 {
+    UVec4::new(vertex_offset as u32, vertex_count as u32, 0, 0)
+}
+
+/// Packs a color into four bytes, clamping each channel between zero and one.
+fn pack_unorm8x4(color: LinearRgba) -> u32 {
+    color
+        .to_f32_array()
+        .iter()
+        .enumerate()
+        .fold(0, |packed, (index, channel)| {
+            packed | (((channel.clamp(0.0, 1.0) * 255.0).round() as u32) << (index * 8))
+        })
+}
+
+/// Packs a pair of values between zero and one into two 16 bit halves.
+fn pack_unorm16x2(value: Vec2) -> u32 {
+    let [x, y] = (value.clamp(Vec2::ZERO, Vec2::ONE) * 65535.0)
+        .round()
+        .as_uvec2()
+        .to_array();
+    x | (y << 16)
+}
+
+/// Returns the rotation of a transform around the z axis, as the cosine and sine of its angle.
+fn rotation_2d(global_transform: &GlobalTransform) -> Vec2 {
+    (global_transform.rotation() * Vec3::X)
+        .xy()
+        .normalize_or(Vec2::X)
+}
+
+fn extract_convex_polygon(
+    center: Vec2,
+    vertices: impl Iterator<Item = Vec2>,
+    vertex_count: usize,
+    vertex_buffer: &mut Vec<Vec2>,
+) -> Option<ExtractedLightOccluder2d> {
+    if vertex_count > MAX_CONVEX_POLYGON_VERTICES {
+        warn_once!(
+            "Convex polygon occluder has {vertex_count} vertices, but at most \
+            {MAX_CONVEX_POLYGON_VERTICES} are supported. Extra vertices will be ignored."
+        );
+    }
+
+    let vertices: SmallVec<[Vec2; MAX_CONVEX_POLYGON_VERTICES]> =
+        vertices.take(MAX_CONVEX_POLYGON_VERTICES).collect();
+
+    match vertices.as_slice() {
+        // Not enough vertices to occlude anything.
+        [] | [_] => None,
+        // A two vertex polygon is a line segment, which is a very thin capsule.
+        [start, end] => Some(ExtractedLightOccluder2d {
+            center,
+            shape_params: segment_params(*start, *end),
+            radius: SEGMENT_HALF_THICKNESS,
+            shape: OCCLUDER_SHAPE_CAPSULE,
+            ..default()
+        }),
+        _ => {
+            let vertex_offset = vertex_buffer.len();
+            vertex_buffer.extend_from_slice(&vertices);
+
+            Some(ExtractedLightOccluder2d {
+                center,
+                shape_params: vertex_params(vertex_offset, vertices.len()),
+                shape: OCCLUDER_SHAPE_POLYGON,
+                ..default()
+            })
+        }
+    }
+}
+
+fn extract_triangle(
+    center: Vec2,
+    [a, b, c]: [Vec2; 3],
+    vertex_buffer: &mut Vec<Vec2>,
+) -> Option<ExtractedLightOccluder2d> {
+    // Collinear triangles have no area, so fall back to a segment between the two
+    // vertices furthest apart.
+    let scale = (b - a).length_squared().max((c - a).length_squared());
+    if (b - a).perp_dot(c - a).abs() <= scale * 1e-6 {
+        let (start, end) = [(a, b), (b, c), (c, a)]
+            .into_iter()
+            .max_by(|(a1, b1), (a2, b2)| {
+                a1.distance_squared(*b1)
+                    .total_cmp(&a2.distance_squared(*b2))
+            })?;
+
+        return Some(ExtractedLightOccluder2d {
+            center,
+            shape_params: segment_params(start, end),
+            radius: SEGMENT_HALF_THICKNESS,
+            shape: OCCLUDER_SHAPE_CAPSULE,
+            ..default()
+        });
+    }
+
+    extract_triangles(center, [a, b, c].into_iter(), vertex_buffer)
+}
+
+fn triangulate_or_bounds(vertices: &[Vec2]) -> Vec<Vec2> {
+    if let Some(triangles) = triangulate(vertices) {
+        return triangles;
+    }
+
+    warn!(
+        "Polygon occluder is self-intersecting or degenerate, and can't be decomposed. \
+        Falling back to the polygon's bounding box."
+    );
+
+    let min = vertices
+        .iter()
+        .copied()
+        .reduce(Vec2::min)
+        .unwrap_or_default();
+    let max = vertices
+        .iter()
+        .copied()
+        .reduce(Vec2::max)
+        .unwrap_or_default();
+
+    vec![
+        min,
+        Vec2::new(max.x, min.y),
+        max,
+        min,
+        max,
+        Vec2::new(min.x, max.y),
+    ]
+}
+
+fn extract_triangles(
+    center: Vec2,
+    vertices: impl ExactSizeIterator<Item = Vec2>,
+    vertex_buffer: &mut Vec<Vec2>,
+) -> Option<ExtractedLightOccluder2d> {
+    let vertex_offset = vertex_buffer.len();
+    let vertex_count = vertices.len();
+
+    if vertex_count < 3 {
+        return None;
+    }
+
+    vertex_buffer.extend(vertices);
+
+    Some(ExtractedLightOccluder2d {
+        center,
+        shape_params: vertex_params(vertex_offset, vertex_count),
+        shape: OCCLUDER_SHAPE_TRIANGLES,
+        ..default()
+    })
+}
+
 pub fn extract_ambient_lights(
     mut commands: Commands,
-    light_2d_query: Extract<Query<(&RenderEntity, &Light2d)>>,
+    images: Extract<Res<Assets<Image>>>,
+    light_2d_query: Extract<
+        Query<(
+            &RenderEntity,
+            &Light2d,
+            Option<&BakedLightmap2d>,
+            Option<&AmbientFlashes2d>,
+            Option<&VisionMask2d>,
+            Option<&RenderLayers>,
+        )>,
+    >,
+    global_light_query: Extract<Query<(&GlobalLight2d, &InheritedVisibility)>>,
 ) {
-    for (render_entity, light_2d) in &light_2d_query {
-        commands
-            .entity(render_entity.id())
-            .insert(ExtractedAmbientLight2d {
-                color: light_2d.ambient_light.color.to_linear() * light_2d.ambient_light.brightness,
+    // Global lights are never occluded, so they're folded into the ambient light of each view.
+    let global_color = global_light_color(&global_light_query);
+
+    for (render_entity, light_2d, baked_lightmap, ambient_flashes, vision_mask, render_layers) in
+        &light_2d_query
+    {
+        // Lightmaps are only sampled once their image exists, and cover nothing until then.
+        let baked_lightmap = baked_lightmap
+            .filter(|baked_lightmap| images.contains(&baked_lightmap.image))
+            .filter(|baked_lightmap| !baked_lightmap.region.is_empty());
+
+        let ambient_light = &light_2d.ambient_light;
+        // Likewise, ambient maps leave the ambient light untouched until their image exists.
+        let ambient_map = ambient_light
+            .ambient_map
+            .as_ref()
+            .filter(|ambient_map| images.contains(*ambient_map))
+            .filter(|_| !ambient_light.ambient_map_region.is_empty());
+        let ambient_map_region = match ambient_map {
+            Some(_) => ambient_light.ambient_map_region,
+            None => Rect::default(),
+        };
+        // Ambient occlusion that wouldn't darken anything is skipped, like it's disabled.
+        let ambient_occlusion = light_2d
+            .ambient_occlusion
+            .map(|ambient_occlusion| AmbientOcclusion2d {
+                radius: ambient_occlusion.radius,
+                strength: ambient_occlusion.strength.clamp(0.0, 1.0),
+            })
+            .filter(|ambient_occlusion| {
+                ambient_occlusion.radius > 0.0 && ambient_occlusion.strength > 0.0
             });
+        // Flashes brighten the ambient light to at least their own color, before global lights
+        // are added.
+        let ambient_flash = ambient_flashes.map_or(Vec3::ZERO, AmbientFlashes2d::color);
+        let ambient_color = |color: Color| {
+            let color = (color.to_linear().to_vec3() * ambient_light.brightness).max(ambient_flash);
+            LinearRgba::from_vec3(color) + global_color
+        };
+        let (color, gradient_top, gradient_range, gradient) = match &ambient_light.gradient {
+            Some(gradient) => (
+                ambient_color(gradient.bottom),
+                ambient_color(gradient.top),
+                Vec2::new(gradient.world_range.start, gradient.world_range.end),
+                match gradient.space {
+                    AmbientGradientSpace::Screen => 1,
+                    AmbientGradientSpace::World => 2,
+                },
+            ),
+            None => {
+                let color = ambient_color(ambient_light.color);
+                (color, color, Vec2::ZERO, 0)
+            }
+        };
+
+        commands.entity(render_entity.id()).insert((
+            ExtractedAmbientLight2d {
+                color,
+                baked_lightmap_min: baked_lightmap
+                    .map_or(Vec2::ZERO, |baked_lightmap| baked_lightmap.region.min),
+                baked_lightmap_max: baked_lightmap
+                    .map_or(Vec2::ZERO, |baked_lightmap| baked_lightmap.region.max),
+                exposure: light_2d.exposure.multiplier(),
+                // Cameras without render layers only see the default layer.
+                render_layers: render_layers_bits(render_layers.unwrap_or_default()),
+                blend: match light_2d.blend {
+                    LightBlend2d::Add => 0,
+                    LightBlend2d::Max => 1,
+                    LightBlend2d::Screen => 2,
+                },
+                max_brightness: light_2d.max_brightness.unwrap_or(f32::MAX),
+                gradient_top,
+                gradient_range,
+                gradient,
+                ambient_map_min: ambient_map_region.min,
+                ambient_map_max: ambient_map_region.max,
+                ambient_occlusion_radius: ambient_occlusion
+                    .map_or(0.0, |ambient_occlusion| ambient_occlusion.radius),
+                ambient_occlusion_strength: ambient_occlusion
+                    .map_or(0.0, |ambient_occlusion| ambient_occlusion.strength),
+            },
+            ExtractedLight2d {
+                emissive_spill: light_2d.emissive_spill,
+                baked_lightmap: baked_lightmap.map(|baked_lightmap| baked_lightmap.image.id()),
+                ambient_map: ambient_map.map(Handle::id),
+                ambient_occlusion: ambient_occlusion.is_some(),
+                vision_mask: vision_mask.map(|vision_mask| vision_mask.image.id()),
+            },
+        ));
+    }
+}
+
+#[derive(Default, Clone, Copy, ShaderType)]
+pub struct ExtractedAmbientLightZone2d {
+    // The zone's ambient light, plus every global light.
+    pub color: LinearRgba,
+    pub center: Vec2,
+    // The world space half size of a rectangle zone, or the radius of a circle zone in x.
+    pub half_size: Vec2,
+    // The zone's rotation, as the cosine and sine of its angle.
+    pub rotation: Vec2,
+    // The zone's shape: 0 for a rectangle and 1 for a circle.
+    pub shape: u32,
+    pub edge_softness: f32,
+    // The views the zone affects, as a bitmask of render layers.
+    pub render_layers: u32,
+}
+
+pub fn extract_ambient_light_zones(
+    mut ambient_light_zone_buffer: ResMut<AmbientLightZoneBuffer>,
+    zone_query: Extract<
+        Query<(
+            Entity,
+            &AmbientLightZone2d,
+            &GlobalTransform,
+            &ViewVisibility,
+            Option<&RenderLayers>,
+        )>,
+    >,
+    global_light_query: Extract<Query<(&GlobalLight2d, &InheritedVisibility)>>,
+) {
+    // Zones replace the ambient light of each view, so global lights are added to them in the
+    // same way.
+    let global_color = global_light_color(&global_light_query);
+
+    let mut zones: Vec<_> = zone_query
+        .iter()
+        .filter(|(_, _, _, view_visibility, _)| view_visibility.get())
+        .collect();
+    // Zones are layered by priority, then by entity, so that zones with the same priority don't
+    // swap places between frames.
+    zones.sort_by_key(|(entity, zone, ..)| (zone.priority, *entity));
+    if zones.len() > MAX_AMBIENT_LIGHT_ZONES {
+        warn_once!(
+            "Ambient light zones exceed the limit of {MAX_AMBIENT_LIGHT_ZONES} zones. The zones \
+            with the lowest priorities won't be drawn."
+        );
+        zones.drain(..zones.len() - MAX_AMBIENT_LIGHT_ZONES);
+    }
+
+    ambient_light_zone_buffer.zones.clear();
+    ambient_light_zone_buffer
+        .zones
+        .extend(
+            zones
+                .into_iter()
+                .map(
+                    |(_, zone, global_transform, _, render_layers)| ExtractedAmbientLightZone2d {
+                        color: zone.color.to_linear() * zone.brightness + global_color,
+                        center: global_transform.translation().xy(),
+                        half_size: zone.world_half_size(global_transform),
+                        rotation: rotation_2d(global_transform),
+                        shape: match zone.shape {
+                            AmbientLightZoneShape::Rectangle { .. } => 0,
+                            AmbientLightZoneShape::Circle { .. } => 1,
+                        },
+                        edge_softness: zone.edge_softness.max(0.0),
+                        render_layers: render_layers_mask(render_layers),
+                    },
+                ),
+        );
+}
+
+/// Returns the combined color of every visible global light.
+fn global_light_color(
+    global_light_query: &Query<(&GlobalLight2d, &InheritedVisibility)>,
+) -> LinearRgba {
+    global_light_query
+        .iter()
+        .filter(|(_, inherited_visibility)| inherited_visibility.get())
+        .fold(LinearRgba::NONE, |color, (global_light, _)| {
+            color + global_light.color.to_linear() * global_light.intensity
+        })
+}
+
+/// Returns an entity's render layers as a bitmask, or every layer if it doesn't have any.
+fn render_layers_mask(render_layers: Option<&RenderLayers>) -> u32 {
+    render_layers.map_or(u32::MAX, render_layers_bits)
+}
+
+/// Returns the first 32 render layers as a bitmask. Later layers can't be represented, and are
+/// ignored.
+fn render_layers_bits(render_layers: &RenderLayers) -> u32 {
+    render_layers
+        .iter()
+        .filter(|&layer| layer < u32::BITS as usize)
+        .fold(0, |bits, layer| bits | 1 << layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{run_extract, visible};
+
+    /// Extracts the first shape of a visible occluder with the given scale.
+    fn extract_scaled(shape: LightOccluder2dShape, scale: Vec3) -> ExtractedLightOccluder2d {
+        let mut render_world = World::new();
+        render_world.init_resource::<ExtractedOccluders>();
+        render_world.init_resource::<PolygonOccluderCache>();
+        render_world.init_resource::<OccluderMaskAtlas>();
+        render_world.init_resource::<IncompleteOccluders>();
+
+        let mut main_world = World::new();
+        main_world.init_resource::<Assets<Image>>();
+        let entity = main_world
+            .spawn((
+                LightOccluder2d { shape, ..default() },
+                GlobalTransform::from(Transform::from_xyz(10.0, 20.0, 0.0).with_scale(scale)),
+                visible(),
+            ))
+            .id();
+        run_extract(&mut render_world, main_world, extract_light_occluders);
+
+        render_world.resource::<ExtractedOccluders>().occluders[&entity].occluders[0].clone()
+    }
+
+    fn assert_same_shape(a: &ExtractedLightOccluder2d, b: &ExtractedLightOccluder2d) {
+        assert_eq!(a.shape, b.shape);
+        assert_eq!(a.shape_params, b.shape_params);
+        assert_eq!(a.center, b.center);
+        assert_eq!(a.rotation, b.rotation);
+        assert_eq!(a.radius, b.radius);
+    }
+
+    #[test]
+    fn scaled_rectangle_matches_resized_rectangle() {
+        let scaled = extract_scaled(
+            LightOccluder2dShape::Rectangle {
+                half_size: Vec2::new(1.0, 1.0),
+            },
+            Vec3::new(4.0, 2.0, 1.0),
+        );
+        let resized = extract_scaled(
+            LightOccluder2dShape::Rectangle {
+                half_size: Vec2::new(4.0, 2.0),
+            },
+            Vec3::ONE,
+        );
+
+        assert_same_shape(&scaled, &resized);
+        assert_eq!(scaled.shape_params, half_size_params(Vec2::new(4.0, 2.0)));
+    }
+
+    #[test]
+    fn negative_scale_is_mirrored() {
+        let mirrored = extract_scaled(
+            LightOccluder2dShape::Rectangle {
+                half_size: Vec2::new(1.0, 1.0),
+            },
+            Vec3::new(-4.0, 2.0, 1.0),
+        );
+
+        assert_eq!(mirrored.shape_params, half_size_params(Vec2::new(4.0, 2.0)));
+    }
+
+    #[test]
+    fn scaled_rounded_rectangle_matches_resized_rounded_rectangle() {
+        let scaled = extract_scaled(
+            LightOccluder2dShape::RoundedRectangle {
+                half_size: Vec2::new(2.0, 1.0),
+                corner_radius: 0.5,
+            },
+            Vec3::new(2.0, 3.0, 1.0),
+        );
+        let resized = extract_scaled(
+            LightOccluder2dShape::RoundedRectangle {
+                half_size: Vec2::new(4.0, 3.0),
+                corner_radius: 1.0,
+            },
+            Vec3::ONE,
+        );
+
+        assert_same_shape(&scaled, &resized);
+    }
+
+    #[test]
+    fn scaled_ellipse_matches_resized_ellipse() {
+        let scaled = extract_scaled(
+            LightOccluder2dShape::Ellipse {
+                half_size: Vec2::new(1.0, 2.0),
+            },
+            Vec3::new(3.0, 0.5, 1.0),
+        );
+        let resized = extract_scaled(
+            LightOccluder2dShape::Ellipse {
+                half_size: Vec2::new(3.0, 1.0),
+            },
+            Vec3::ONE,
+        );
+
+        assert_same_shape(&scaled, &resized);
     }
 }
@@ -0,0 +1,247 @@
+use bevy::{
+    asset::{AssetId, Assets},
+    color::ColorToPacked,
+    ecs::{
+        resource::Resource,
+        system::{Res, ResMut},
+    },
+    image::Image,
+    log::warn_once,
+    math::{UVec2, Vec4},
+    platform::collections::HashMap,
+    render::{
+        render_resource::{
+            Extent3d, Origin3d, TexelCopyBufferLayout, TexelCopyTextureInfo, Texture,
+            TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+            TextureView, TextureViewDescriptor,
+        },
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+
+const LIGHT_COOKIE_ATLAS: &str = "light_cookie_atlas";
+
+/// The width and height of the light cookie atlas, in texels.
+pub const LIGHT_COOKIE_ATLAS_SIZE: u32 = 1024;
+
+// Images larger than this are downsampled before being copied into the atlas.
+const MAX_COOKIE_SIZE: u32 = 256;
+
+// Empty texels around each cookie, so that filtering doesn't bleed between cookies.
+const COOKIE_PADDING: u32 = 1;
+
+const TEXEL_SIZE: usize = 4;
+
+/// Cookie images of lights, packed into a single texture so that any number of them can be
+/// sampled by the light map pass.
+///
+/// Cookies are stored as sRGB colors, and multiply the light passing through them.
+#[derive(Resource)]
+pub struct LightCookieAtlas {
+    cookies: HashMap<AssetId<Image>, Vec4>,
+    data: Vec<u8>,
+    // Cookies are packed into rows, left to right and top to bottom.
+    cursor: UVec2,
+    row_height: u32,
+    full: bool,
+    dirty: bool,
+    pub texture: Option<Texture>,
+    pub texture_view: Option<TextureView>,
+}
+
+impl Default for LightCookieAtlas {
+    fn default() -> Self {
+        Self {
+            cookies: HashMap::default(),
+            data: vec![
+                0;
+                (LIGHT_COOKIE_ATLAS_SIZE * LIGHT_COOKIE_ATLAS_SIZE) as usize * TEXEL_SIZE
+            ],
+            cursor: UVec2::ZERO,
+            row_height: 0,
+            full: false,
+            dirty: true,
+            texture: None,
+            texture_view: None,
+        }
+    }
+}
+
+impl LightCookieAtlas {
+    /// Clears the atlas if it ran out of space last frame, so that cookies which are no longer
+    /// used don't take up space forever.
+    pub fn reclaim(&mut self) {
+        if !self.full {
+            return;
+        }
+
+        self.cookies.clear();
+        self.data.fill(0);
+        self.cursor = UVec2::ZERO;
+        self.row_height = 0;
+        self.full = false;
+        self.dirty = true;
+    }
+
+    /// Returns the texture coordinates of a cookie's first and last texel centers within the
+    /// atlas, copying the image into the atlas if necessary.
+    ///
+    /// Returns `None` if the image isn't loaded, can't be read, or doesn't fit in the atlas.
+    pub fn get_or_insert(
+        &mut self,
+        image_id: AssetId<Image>,
+        images: &Assets<Image>,
+    ) -> Option<Vec4> {
+        if let Some(uv_rect) = self.cookies.get(&image_id) {
+            return Some(*uv_rect);
+        }
+
+        let image = images.get(image_id)?;
+
+        if image.data.is_none() {
+            warn_once!(
+                "Light cookie image has no data in the main world, so it can't be used as a \
+                cookie. Make sure the image's asset usage includes the main world."
+            );
+            return None;
+        }
+
+        let (cookie_size, cookie) = read_cookie(image)?;
+        let position = self.allocate(cookie_size)?;
+
+        let row_size = cookie_size.x as usize * TEXEL_SIZE;
+        for (y, row) in cookie.chunks(row_size).enumerate() {
+            let start = ((position.y + y as u32) * LIGHT_COOKIE_ATLAS_SIZE + position.x) as usize
+                * TEXEL_SIZE;
+            self.data[start..start + row.len()].copy_from_slice(row);
+        }
+        self.dirty = true;
+
+        let atlas_size = LIGHT_COOKIE_ATLAS_SIZE as f32;
+        let min = (position.as_vec2() + 0.5) / atlas_size;
+        let max = ((position + cookie_size).as_vec2() - 0.5) / atlas_size;
+        let uv_rect = Vec4::new(min.x, min.y, max.x, max.y);
+        self.cookies.insert(image_id, uv_rect);
+
+        Some(uv_rect)
+    }
+
+    /// Reserves space for a cookie, returning the position of its first texel.
+    fn allocate(&mut self, cookie_size: UVec2) -> Option<UVec2> {
+        let padded_size = cookie_size + 2 * COOKIE_PADDING;
+
+        if self.cursor.x + padded_size.x > LIGHT_COOKIE_ATLAS_SIZE {
+            self.cursor = UVec2::new(0, self.cursor.y + self.row_height);
+            self.row_height = 0;
+        }
+
+        if self.cursor.y + padded_size.y > LIGHT_COOKIE_ATLAS_SIZE {
+            warn_once!(
+                "Light cookies don't fit in the {LIGHT_COOKIE_ATLAS_SIZE}x\
+                {LIGHT_COOKIE_ATLAS_SIZE} light cookie atlas. Some lights will be drawn without \
+                their cookies."
+            );
+            self.full = true;
+            return None;
+        }
+
+        let position = self.cursor + COOKIE_PADDING;
+        self.cursor.x += padded_size.x;
+        self.row_height = self.row_height.max(padded_size.y);
+
+        Some(position)
+    }
+}
+
+/// Reads an image's colors as sRGB texels, returning the size of the cookie and its texels.
+fn read_cookie(image: &Image) -> Option<(UVec2, Vec<u8>)> {
+    let image_size = image.size();
+    if image_size.min_element() == 0 {
+        return None;
+    }
+
+    // Large images are downsampled, so that cookies fit in the atlas.
+    let step = (image_size.max_element() as f32 / MAX_COOKIE_SIZE as f32).max(1.0);
+    let cookie_size = (image_size.as_vec2() / step)
+        .ceil()
+        .as_uvec2()
+        .min(UVec2::splat(MAX_COOKIE_SIZE));
+
+    let mut cookie = Vec::with_capacity((cookie_size.x * cookie_size.y) as usize * TEXEL_SIZE);
+    for y in 0..cookie_size.y {
+        for x in 0..cookie_size.x {
+            let pixel = ((UVec2::new(x, y).as_vec2() + 0.5) * step)
+                .as_uvec2()
+                .min(image_size - 1);
+
+            let Ok(color) = image.get_color_at(pixel.x, pixel.y) else {
+                warn_once!(
+                    "Light cookie image uses a texture format that can't be read, so it can't \
+                    be used as a cookie."
+                );
+                return None;
+            };
+            cookie.extend_from_slice(&color.to_srgba().to_u8_array());
+        }
+    }
+
+    Some((cookie_size, cookie))
+}
+
+pub fn prepare_light_cookie_atlas(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut light_cookie_atlas: ResMut<LightCookieAtlas>,
+) {
+    let light_cookie_atlas = light_cookie_atlas.as_mut();
+
+    if light_cookie_atlas.texture.is_none() {
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some(LIGHT_COOKIE_ATLAS),
+            size: Extent3d {
+                width: LIGHT_COOKIE_ATLAS_SIZE,
+                height: LIGHT_COOKIE_ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        light_cookie_atlas.texture_view =
+            Some(texture.create_view(&TextureViewDescriptor::default()));
+        light_cookie_atlas.texture = Some(texture);
+        light_cookie_atlas.dirty = true;
+    }
+
+    let Some(texture) = &light_cookie_atlas.texture else {
+        return;
+    };
+
+    if !light_cookie_atlas.dirty {
+        return;
+    }
+
+    render_queue.write_texture(
+        TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        &light_cookie_atlas.data,
+        TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(LIGHT_COOKIE_ATLAS_SIZE * TEXEL_SIZE as u32),
+            rows_per_image: None,
+        },
+        Extent3d {
+            width: LIGHT_COOKIE_ATLAS_SIZE,
+            height: LIGHT_COOKIE_ATLAS_SIZE,
+            depth_or_array_layers: 1,
+        },
+    );
+    light_cookie_atlas.dirty = false;
+}
@@ -1,18 +1,129 @@
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
 #import bevy_render::view::View
-#import bevy_light_2d::types::{AmbientLight2d, PointLight2d, PointLightMeta, SpotLight2d, SpotLightMeta}
+#import bevy_light_2d::types::{
+    AmbientLight2d,
+    AmbientLightZone2d,
+    DirectionalLight2d,
+    LightExtras2d,
+    LightOccluder2d,
+    LineLight2d,
+    MAX_OCCLUDERS,
+    PointLight2d,
+    PolygonLight2d,
+    RingLight2d,
+    SpotLight2d,
+    occluder_shadow_color,
+    occluder_tint,
+}
 #import bevy_light_2d::view_transformations::{
     frag_coord_to_ndc,
     ndc_to_world,
     ndc_to_uv,
+    uv_to_ndc,
     world_to_ndc
 };
 
 // We're currently only using a single uniform binding for point lights in
 // WebGL2, which is limited to 4kb in BatchedUniformBuffer, so we need to
 // ensure our point lights can fit in 4kb.
-const MAX_POINT_LIGHTS: u32 = 82u;
-const MAX_SPOT_LIGHTS:  u32 = 64u;
+//
+// As each point light is 96 bytes, we can fit 4096 / 96 = 42 point lights.
+// Spot lights are 112 bytes, and light extras are 96 bytes.
+const MAX_POINT_LIGHTS: u32 = 42u;
+const MAX_SPOT_LIGHTS:  u32 = 36u;
+const MAX_LIGHT_EXTRAS: u32 = 42u;
+const MAX_LINE_LIGHTS: u32 = 51u;
+const MAX_RING_LIGHTS: u32 = 51u;
+const MAX_POLYGON_LIGHTS: u32 = 64u;
+
+// Rays that get this close to a light are considered to have reached it. This
+// allows lights sitting on thin occluders (such as segments) to illuminate both
+// sides of the occluder.
+const RAY_TARGET_EPSILON: f32 = 1.0;
+
+// The smallest step a ray takes while travelling through an occluder that
+// doesn't fully block it.
+const MIN_RAY_STEP: f32 = 1.0;
+
+// Shadows of a limited length fade out over this distance, rather than ending
+// in a hard line.
+const SHADOW_FADE_DISTANCE: f32 = 8.0;
+
+const ALL_OCCLUSION_LAYERS: u32 = 0xffffffffu;
+
+// The falloff curves of point and spot lights, other than the default inverse
+// square curve.
+const FALLOFF_CURVE_LINEAR: u32 = 1u;
+const FALLOFF_CURVE_SMOOTHSTEP: u32 = 2u;
+const FALLOFF_CURVE_EXPONENT: u32 = 3u;
+
+// The flags of point and spot lights, with the light's falloff curve stored
+// above them.
+const LIGHT_FLAG_CAST_SHADOWS: u32 = 1u;
+const LIGHT_FLAG_VISION: u32 = 2u;
+const LIGHT_FLAG_SCREEN_SPACE: u32 = 4u;
+const LIGHT_FLAG_PROJECTION: u32 = 8u;
+const LIGHT_FLAG_FALLOFF_CURVE_SHIFT: u32 = 8u;
+
+// The index of the extras of lights that don't use any.
+const NO_LIGHT_EXTRAS: u32 = 0xffffffffu;
+
+// The maximum number of directional lights in each frame.
+const MAX_DIRECTIONAL_LIGHTS: u32 = 8u;
+
+// How the light of overlapping lights is combined, matching `LightBlend2d`.
+const LIGHT_BLEND_ADD: u32 = 0u;
+const LIGHT_BLEND_MAX: u32 = 1u;
+const LIGHT_BLEND_SCREEN: u32 = 2u;
+
+// The maximum number of occluders that can be ignored across all lights.
+const MAX_OCCLUDER_EXCLUSIONS: u32 = 256u;
+
+// The maximum number of vertices across all polygon, polyline and area lights.
+const MAX_POLYGON_LIGHT_BUFFER_VERTICES: u32 = 256u;
+
+// The maximum number of ambient light zones in each frame.
+const MAX_AMBIENT_LIGHT_ZONES: u32 = 32u;
+
+const AMBIENT_LIGHT_ZONE_CIRCLE: u32 = 1u;
+
+// The number of samples taken in a ring around each fragment for ambient
+// occlusion.
+const AMBIENT_OCCLUSION_SAMPLES: u32 = 8u;
+
+const TAU: f32 = 6.28318530718;
+
+// The counts, directional lights, occluder exclusions, polygon light vertices
+// and ambient light zones used by the light map, which share a single uniform.
+struct LightMapMeta {
+    point_light_count: u32,
+    spot_light_count: u32,
+    directional_light_count: u32,
+    line_light_count: u32,
+    ring_light_count: u32,
+    polygon_light_count: u32,
+    // Static occluders are indexed before the rest of the occluders.
+    static_occluder_count: u32,
+    ambient_light_zone_count: u32,
+    // The elapsed light animation time, in seconds, which scrolls noise textures.
+    time: f32,
+    directional_lights: array<DirectionalLight2d, MAX_DIRECTIONAL_LIGHTS>,
+    // The owners of occluders ignored by lights, packed in fours as uniform
+    // array elements must be 16 byte aligned.
+    occluder_exclusions: array<vec4<u32>, MAX_OCCLUDER_EXCLUSIONS / 4u>,
+    // Vertices for all polygon, polyline and area lights, packed in pairs.
+    polygon_light_vertices: array<vec4<f32>, MAX_POLYGON_LIGHT_BUFFER_VERTICES / 2u>,
+    // Ambient light zones, in the order they're layered.
+    ambient_light_zones: array<AmbientLightZone2d, MAX_AMBIENT_LIGHT_ZONES>,
+}
+
+// The occluders a light interacts with.
+struct OcclusionFilter {
+    occlusion_layers: u32,
+    // Range of the occluder owners the light ignores.
+    exclusion_offset: u32,
+    exclusion_count: u32,
+}
 
 @group(0) @binding(0)
 var<uniform> view: View;
@@ -21,8 +132,10 @@ var<uniform> view: View;
 var<uniform> ambient_light: AmbientLight2d;
 
 // WebGL2 does not support storage buffers, so we fall back to a fixed length
-// array in a uniform buffer.
-#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 6
+// array in a uniform buffer. The light map binds eight storage buffers (point,
+// spot, line, ring and polygon lights, light extras, and dynamic and static
+// occluders), which every WebGPU device supports.
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
     @group(0) @binding(2)
     var<storage> point_lights: array<PointLight2d>;
 #else
@@ -31,7 +144,7 @@ var<uniform> ambient_light: AmbientLight2d;
 #endif
 
 @group(0) @binding(3)
-var<uniform> point_light_meta: PointLightMeta;
+var<uniform> light_map_meta: LightMapMeta;
 
 @group(0) @binding(4)
 var sdf: texture_2d<f32>;
@@ -40,7 +153,7 @@ var sdf: texture_2d<f32>;
 var sdf_sampler: sampler;
 
 // Spot lights: SSBO on modern backends, UBO array on WebGL2
-#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 6
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
     @group(0) @binding(6)
     var<storage> spot_lights: array<SpotLight2d>;
 #else
@@ -48,56 +161,574 @@ var sdf_sampler: sampler;
     var<uniform> spot_lights: array<SpotLight2d, MAX_SPOT_LIGHTS>;
 #endif
 
-@group(0) @binding(7)
-var<uniform> spot_light_meta: SpotLightMeta;
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
+    @group(0) @binding(7)
+    var<storage> occluders: array<LightOccluder2d>;
+#else
+    @group(0) @binding(7)
+    var<uniform> occluders: array<LightOccluder2d, MAX_OCCLUDERS>;
+#endif
+
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
+    @group(0) @binding(8)
+    var<storage> static_occluders: array<LightOccluder2d>;
+#else
+    @group(0) @binding(8)
+    var<uniform> static_occluders: array<LightOccluder2d, MAX_OCCLUDERS>;
+#endif
+
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
+    @group(0) @binding(9)
+    var<storage> light_extras: array<LightExtras2d>;
+#else
+    @group(0) @binding(9)
+    var<uniform> light_extras: array<LightExtras2d, MAX_LIGHT_EXTRAS>;
+#endif
+
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
+    @group(0) @binding(10)
+    var<storage> line_lights: array<LineLight2d>;
+#else
+    @group(0) @binding(10)
+    var<uniform> line_lights: array<LineLight2d, MAX_LINE_LIGHTS>;
+#endif
+
+@group(0) @binding(11)
+var light_cookie_atlas: texture_2d<f32>;
+
+@group(0) @binding(12)
+var light_cookie_sampler: sampler;
+
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
+    @group(0) @binding(13)
+    var<storage> ring_lights: array<RingLight2d>;
+#else
+    @group(0) @binding(13)
+    var<uniform> ring_lights: array<RingLight2d, MAX_RING_LIGHTS>;
+#endif
+
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 8
+    @group(0) @binding(14)
+    var<storage> polygon_lights: array<PolygonLight2d>;
+#else
+    @group(0) @binding(14)
+    var<uniform> polygon_lights: array<PolygonLight2d, MAX_POLYGON_LIGHTS>;
+#endif
+
+// Light baked from static lights, covering the region between the ambient
+// light's `baked_lightmap_min` and `baked_lightmap_max`. Lightmaps are stored
+// as floats, which can't be filtered on every platform, so they're loaded.
+@group(0) @binding(15)
+var baked_lightmap: texture_2d<f32>;
+
+// A texture the ambient light is multiplied by, covering the region between
+// the ambient light's `ambient_map_min` and `ambient_map_max`. It's sampled
+// with the light cookie sampler.
+@group(0) @binding(16)
+var ambient_map: texture_2d<f32>;
+
+struct LightMapOutput {
+    @location(0) light: vec4<f32>,
+    // Light scattered by volumetric lights, which is added on top of the lit
+    // scene.
+    @location(1) volumetric: vec4<f32>,
+}
 
 @fragment
-fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+fn fragment(in: FullscreenVertexOutput) -> LightMapOutput {
     let pos = ndc_to_world(frag_coord_to_ndc(in.position.xy));
+    var ambient_color = ambient_zone_color(pos, ambient_light_color(pos, in.uv)) * ambient_map_color(pos);
 
+    // Fragments inside opaque, two-sided occluders that block every light only
+    // receive ambient light.
     if get_distance(pos) <= 0.0 {
-        return vec4(ambient_light.color.rgb, 1.0);
+        let occluder = get_occluder(get_occluder_index(pos));
+        if all(occluder_transmittance(occluder) <= vec3(0.0))
+            && !is_one_sided(occluder)
+            && occluder.occlusion_layers == ALL_OCCLUSION_LAYERS
+            && in_view(occluder.render_layers) {
+            return LightMapOutput(vec4(clamp_brightness(ambient_color * ambient_light.exposure, ambient_light.max_brightness), 1.0), vec4(0.0));
+        }
+    }
+
+#ifdef AMBIENT_OCCLUSION
+    ambient_color *= ambient_occlusion(pos);
+#endif
+
+    var lighting_color = ambient_color + baked_light(pos);
+    var volumetric_color = vec3(0.0);
+
+    // Directional lights
+    if light_map_meta.directional_light_count > 0u {
+        // Directional light shadows are cast across the whole view, so rays are marched
+        // towards the light until they could have left the view.
+        let view_diagonal = distance(ndc_to_world(vec2(-1.0)), ndc_to_world(vec2(1.0)));
+
+        for (var i = 0u; i < light_map_meta.directional_light_count; i++) {
+            let light = light_map_meta.directional_lights[i];
+            if !in_view(light.render_layers) {
+                continue;
+            }
+            let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+            let light_pos = pos - normalize(light.direction) * view_diagonal;
+            let visibility = shadow(pos, light_pos, light.cast_shadows, filter);
+            lighting_color = blend_light(lighting_color, light.color.rgb * visibility);
+        }
     }
 
-    var lighting_color = ambient_light.color.rgb;
-    
     // Point lights
-    for (var i = 0u; i < point_light_meta.count; i++) {
-        let light = point_lights[i];
-        let dist = distance(light.center, pos);
+    for (var i = 0u; i < light_map_meta.point_light_count; i++) {
+        let light = get_point_light(i);
+        let dist = point_light_distance(light, pos);
 
-        if dist < light.radius {
-            let raymarch = raymarch(pos, light.center);
+        if dist < light.radius && in_view(light.render_layers) {
+            let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+            let extras = get_light_extras(light.extras);
+            var visibility = mix(
+                vec3(1.0),
+                soft_shadow(pos, light.center, light.flags & LIGHT_FLAG_CAST_SHADOWS, filter, light.source_radius, light.shadow_softness),
+                light.shadow_strength,
+            );
+
+            // Lights without a cookie skip sampling the cookie atlas.
+            if extras.cookie_radius > 0.0 && any(visibility > vec3(0.0)) {
+                visibility *= point_light_cookie(light, extras, pos);
+            }
+
+            if extras.noise_rect.z > 0.0 && any(visibility > vec3(0.0)) {
+                visibility *= light_noise(extras.noise_rect, extras.noise_scale, extras.noise_scroll, pos);
+            }
 
-            if raymarch > 0.0 || light.cast_shadows == 0 {
-                lighting_color += light.color.rgb * attenuation(dist, light.radius, light.intensity, light.falloff);
+            if any(visibility > vec3(0.0)) {
+                let falloff = curve_attenuation(hotspot_distance(dist, light.inner_radius, light.radius), light.radius, 1.0, light.falloff, light_falloff_curve(light.flags), light.falloff_exponent);
+                lighting_color = blend_light(lighting_color, clamp_brightness(gradient_color(light.color.rgb, extras.edge_color, falloff) * light.intensity * falloff * visibility, extras.max_brightness));
             }
         }
     }
 
     // Spot lights
-    for (var i = 0u; i < spot_light_meta.count; i++) {
+    for (var i = 0u; i < light_map_meta.spot_light_count; i++) {
         let light = spot_lights[i];
         let effective_center = get_effective_spot_light_center(light, pos);
         let dist = distance(effective_center, pos);
-        if dist < light.radius {
+        if dist < light.radius && in_view(light.render_layers) {
             let mask = spot_mask(light, pos, effective_center);
             if mask > 0.0 {
-                let vis = raymarch(pos, effective_center);
-                if vis > 0.0 || light.cast_shadows == 0u {
-                    lighting_color += light.color.rgb * attenuation(dist, light.radius, light.intensity, light.falloff) * mask;
+                let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+                let extras = get_light_extras(light.extras);
+                var vis = mix(vec3(1.0), soft_shadow(pos, effective_center, light.flags & LIGHT_FLAG_CAST_SHADOWS, filter, 0.0, light.shadow_softness), light.shadow_strength);
+#ifdef SPOT_LIGHT_PROJECTION
+                if (light.flags & LIGHT_FLAG_PROJECTION) != 0u && any(vis > vec3(0.0)) {
+                    vis *= spot_light_projection(light, extras, pos, effective_center);
+                }
+#endif
+                if extras.noise_rect.z > 0.0 && any(vis > vec3(0.0)) {
+                    vis *= light_noise(extras.noise_rect, extras.noise_scale, extras.noise_scroll, pos);
                 }
+                if any(vis > vec3(0.0)) {
+                    let falloff = curve_attenuation(hotspot_distance(dist, light.inner_radius, light.radius), light.radius, 1.0, light.falloff, light_falloff_curve(light.flags), light.falloff_exponent);
+                    lighting_color = blend_light(lighting_color, clamp_brightness(gradient_color(light.color.rgb, extras.edge_color, falloff) * light.intensity * falloff * mask * vis, extras.max_brightness));
+                }
+#ifdef VOLUMETRIC_SAMPLES
+                if light.volumetric_intensity > 0.0 {
+                    volumetric_color += light.color.rgb * light.volumetric_intensity * mask
+                        * in_scatter(light, pos, effective_center, filter, in.position.xy);
+                }
+#endif
+            }
+        }
+    }
+
+    // Line lights
+    for (var i = 0u; i < light_map_meta.line_light_count; i++) {
+        let light = line_lights[i];
+        if !in_view(light.render_layers) {
+            continue;
+        }
+        // Line lights are attenuated like a point light at the nearest point on their segment.
+        let nearest = nearest_point_on_segment(pos, light.start, light.end);
+        let dist = distance(nearest, pos);
+        let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+
+        if dist < light.radius {
+            let visibility = shadow(pos, nearest, light.cast_shadows, filter);
+
+            if any(visibility > vec3(0.0)) {
+                lighting_color = blend_light(lighting_color, light.color.rgb * attenuation(dist, light.radius, light.intensity, light.falloff) * visibility);
+            }
+        }
+
+        // Beam lights glow around the end of their segment, where they hit their target.
+        let glow_dist = distance(light.end, pos);
+        if glow_dist < light.end_glow_radius {
+            let visibility = shadow(pos, light.end, light.cast_shadows, filter);
+
+            if any(visibility > vec3(0.0)) {
+                lighting_color = blend_light(lighting_color, light.color.rgb * attenuation(glow_dist, light.end_glow_radius, light.intensity, light.falloff) * visibility);
+            }
+        }
+    }
+
+    // Ring lights
+    for (var i = 0u; i < light_map_meta.ring_light_count; i++) {
+        let light = ring_lights[i];
+        // Ring lights are attenuated like a point light at the nearest point on their arc,
+        // across half of the ring's thickness on either side.
+        let nearest = nearest_point_on_ring(pos, light);
+        let dist = distance(nearest, pos);
+        let half_thickness = light.thickness * 0.5;
+
+        if dist < half_thickness && in_view(light.render_layers) {
+            let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+            let visibility = shadow(pos, nearest, light.cast_shadows, filter);
+
+            if any(visibility > vec3(0.0)) {
+                lighting_color = blend_light(lighting_color, light.color.rgb * attenuation(dist, half_thickness, light.intensity, light.falloff) * visibility);
             }
         }
     }
 
-    return vec4(lighting_color, 1.0);
+    // Polygon lights
+    for (var i = 0u; i < light_map_meta.polygon_light_count; i++) {
+        let light = polygon_lights[i];
+        // Polygon lights are attenuated like a point light at the nearest point on their
+        // polygon, so points inside the polygon receive the full intensity.
+        let nearest = nearest_point_on_polygon(pos, light);
+        let dist = distance(nearest, pos);
+
+        if dist < light.radius && in_view(light.render_layers) {
+            let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+            let visibility = shadow(pos, nearest, light.cast_shadows, filter);
+
+            if any(visibility > vec3(0.0)) {
+                lighting_color = blend_light(lighting_color, light.color.rgb * attenuation(dist, light.radius, light.intensity, light.falloff) * visibility);
+            }
+        }
+    }
+
+    // Lights with a negative intensity subtract from the light of everything else, including
+    // ambient light, but never make the scene darker than black.
+    return LightMapOutput(
+        vec4(clamp_brightness(max(lighting_color, vec3(0.0)) * ambient_light.exposure, ambient_light.max_brightness), 1.0),
+        vec4(max(volumetric_color, vec3(0.0)) * ambient_light.exposure, 1.0),
+    );
+}
+
+// Renders how visible each point of the view is to point and spot lights that
+// are vision sources. The vision mask can have a different size to the view,
+// so positions come from texture coordinates rather than fragment coordinates.
+@fragment
+fn vision_mask(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pos = ndc_to_world(uv_to_ndc(in.uv));
+    var visibility = 0.0;
+
+    // Vision is always blocked by occluders, even for lights that don't cast
+    // shadows.
+    for (var i = 0u; i < light_map_meta.point_light_count; i++) {
+        let light = get_point_light(i);
+        if (light.flags & LIGHT_FLAG_VISION) != 0u && point_light_distance(light, pos) < light.radius
+            && in_view(light.render_layers) {
+            let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+            let vis = shadow(pos, light.center, 1u, filter);
+            visibility = max(visibility, max(vis.r, max(vis.g, vis.b)));
+        }
+    }
+
+    for (var i = 0u; i < light_map_meta.spot_light_count; i++) {
+        let light = spot_lights[i];
+        let effective_center = get_effective_spot_light_center(light, pos);
+        if (light.flags & LIGHT_FLAG_VISION) != 0u && distance(effective_center, pos) < light.radius
+            && in_view(light.render_layers) {
+            let mask = spot_mask(light, pos, effective_center);
+            if mask > 0.0 {
+                let filter = OcclusionFilter(light.occlusion_layers, light.exclusion_offset, light.exclusion_count);
+                let vis = shadow(pos, effective_center, 1u, filter);
+                visibility = max(visibility, mask * max(vis.r, max(vis.g, vis.b)));
+            }
+        }
+    }
+
+    return vec4(visibility, 0.0, 0.0, 1.0);
+}
+
+fn polygon_light_vertex(index: u32) -> vec2<f32> {
+    let pair = light_map_meta.polygon_light_vertices[index / 2u];
+    return select(pair.xy, pair.zw, index % 2u == 1u);
+}
+
+// Returns the point within a polygon light's polygon nearest to `pos`. The
+// polygon may be concave. Open polylines skip the edge between their last and
+// first vertices, and have no inside.
+// See https://iquilezles.org/articles/distfunctions2d/
+fn nearest_point_on_polygon(pos: vec2<f32>, light: PolygonLight2d) -> vec2<f32> {
+    var nearest = polygon_light_vertex(light.vertex_offset);
+    var nearest_distance_squared = dot(pos - nearest, pos - nearest);
+    var inside = false;
+    var j = light.vertex_count - 1u;
+
+    for (var i = 0u; i < light.vertex_count; i++) {
+        if i == 0u && light.closed == 0u {
+            j = i;
+            continue;
+        }
+
+        let vi = polygon_light_vertex(light.vertex_offset + i);
+        let vj = polygon_light_vertex(light.vertex_offset + j);
+        let e = vj - vi;
+        let w = pos - vi;
+        let b = vi + e * clamp(dot(w, e) / max(dot(e, e), 1e-6), 0.0, 1.0);
+        let distance_squared = dot(pos - b, pos - b);
+        if distance_squared < nearest_distance_squared {
+            nearest = b;
+            nearest_distance_squared = distance_squared;
+        }
+
+        let c = vec3<bool>(pos.y >= vi.y, pos.y < vj.y, e.x * w.y > e.y * w.x);
+        if all(c) || all(!c) {
+            inside = !inside;
+        }
+
+        j = i;
+    }
+
+    if inside && light.closed != 0u {
+        return pos;
+    }
+    return nearest;
+}
+
+// Returns the point on a ring light's arc nearest to `pos`.
+fn nearest_point_on_ring(pos: vec2<f32>, light: RingLight2d) -> vec2<f32> {
+    let axis = light.arc_direction;
+    let to_pos = pos - light.center;
+    // The ring's center is equally near to every point on the ring.
+    var angle = 0.0;
+    if any(to_pos != vec2(0.0)) {
+        angle = atan2(axis.x * to_pos.y - axis.y * to_pos.x, dot(axis, to_pos));
+    }
+    angle = clamp(angle, -light.arc_half_angle, light.arc_half_angle);
+    let c = cos(angle);
+    let s = sin(angle);
+    let direction = vec2(axis.x * c - axis.y * s, axis.x * s + axis.y * c);
+    return light.center + direction * light.radius;
+}
+
+// Returns the point on the segment from `start` to `end` nearest to `pos`.
+fn nearest_point_on_segment(pos: vec2<f32>, start: vec2<f32>, end: vec2<f32>) -> vec2<f32> {
+    let segment = end - start;
+    let length_squared = dot(segment, segment);
+    if length_squared == 0.0 {
+        return start;
+    }
+    let t = clamp(dot(pos - start, segment) / length_squared, 0.0, 1.0);
+    return start + segment * t;
+}
+
+// Returns a point light, moving screen lights from viewport coordinates into
+// world space for this view.
+fn get_point_light(index: u32) -> PointLight2d {
+    var light = point_lights[index];
+    if (light.flags & LIGHT_FLAG_SCREEN_SPACE) != 0u {
+        let center = ndc_to_world(uv_to_ndc(light.center));
+        let edge = ndc_to_world(uv_to_ndc(light.center + vec2(light.radius / view.viewport.z, 0.0)));
+        light.center = center;
+        light.radius = distance(center, edge);
+    }
+    return light;
+}
+
+// Returns the extras of a point or spot light, or the extras of a light that
+// doesn't use any.
+fn get_light_extras(index: u32) -> LightExtras2d {
+    if index == NO_LIGHT_EXTRAS {
+        return LightExtras2d(
+            vec4(0.0),
+            vec4(0.0),
+            vec4(0.0),
+            vec2(1.0, 0.0),
+            vec2(0.0),
+            vec2(1.0),
+            vec2(0.0),
+            0.0,
+            3.40282347e+38,
+        );
+    }
+    return light_extras[index];
+}
+
+// Returns the falloff curve stored in a point or spot light's flags.
+fn light_falloff_curve(flags: u32) -> u32 {
+    return flags >> LIGHT_FLAG_FALLOFF_CURVE_SHIFT;
+}
+
+// Returns the distance from a point light's center, scaled by the light's
+// ellipticity along each of its axes.
+fn point_light_distance(light: PointLight2d, pos: vec2<f32>) -> f32 {
+    let d = pos - light.center;
+    let axis = light.ellipse_axis;
+    return length(vec2(dot(d, axis), dot(d, vec2(-axis.y, axis.x)) * light.ellipse_aspect));
+}
+
+// Samples a point light's cookie, which covers a square around the light in
+// the light's local space. No light is emitted outside of the cookie.
+fn point_light_cookie(light: PointLight2d, extras: LightExtras2d, pos: vec2<f32>) -> vec3<f32> {
+    let r = extras.cookie_rotation;
+    let d = (pos - light.center) / extras.cookie_radius;
+    let local_pos = vec2(dot(d, r), dot(d, vec2(-r.y, r.x)));
+    if any(abs(local_pos) > vec2(1.0)) {
+        return vec3(0.0);
+    }
+    // Image rows go downwards, while world space y goes upwards.
+    var uv = vec2(local_pos.x, -local_pos.y) * 0.5 + 0.5;
+    // Offset cookies wrap around. Cookies that aren't offset are left as they
+    // are, so that their edges don't wrap.
+    if any(extras.cookie_offset != vec2(0.0)) {
+        uv = fract(uv + extras.cookie_offset);
+    }
+    let atlas_uv = mix(extras.cookie_rect.xy, extras.cookie_rect.zw, uv);
+    return textureSampleLevel(light_cookie_atlas, light_cookie_sampler, atlas_uv, 0.0).rgb;
+}
+
+// Samples a light's noise texture, which is tiled across the world and scrolled
+// over time. The texture is wrapped manually, as it's packed in the light
+// cookie atlas. Lights without a noise texture skip sampling it.
+fn light_noise(rect: vec4<f32>, scale: vec2<f32>, scroll: vec2<f32>, pos: vec2<f32>) -> vec3<f32> {
+    // The offset is wrapped to a single tile, as the texture repeats, so that
+    // positions far from the origin don't lose precision.
+    let offset = (scroll * light_map_meta.time) % scale;
+    let tile_pos = (pos - offset) / scale;
+    // Image rows go downwards, while world space y goes upwards.
+    let uv = fract(vec2(tile_pos.x, -tile_pos.y));
+    let atlas_uv = mix(rect.xy, rect.zw, uv);
+    return textureSampleLevel(light_cookie_atlas, light_cookie_sampler, atlas_uv, 0.0).rgb;
 }
 
 fn square(x: f32) -> f32 {
     return x * x;
 }
 
+// Returns the ambient light at a world space position, and its texture
+// coordinates within the view, following the ambient light's gradient if it
+// has one.
+fn ambient_light_color(pos: vec2<f32>, uv: vec2<f32>) -> vec3<f32> {
+    if ambient_light.gradient == 0u {
+        return ambient_light.color.rgb;
+    }
+    var t: f32;
+    if ambient_light.gradient == 1u {
+        // Texture coordinates go downwards, from the top of the view.
+        t = 1.0 - uv.y;
+    } else {
+        let range = ambient_light.gradient_range;
+        let height = range.y - range.x;
+        if height == 0.0 {
+            t = select(1.0, 0.0, pos.y < range.x);
+        } else {
+            t = (pos.y - range.x) / height;
+        }
+    }
+    return mix(ambient_light.color.rgb, ambient_light.gradient_top.rgb, clamp(t, 0.0, 1.0));
+}
+
+// Returns how much ambient light reaches a world space position past nearby
+// occluders, from 1.0 in the open down to 1.0 - strength in tight corners.
+//
+// The distance field is sampled at the position and in a ring around it, so
+// that positions near several occluders are darker than those beside one.
+fn ambient_occlusion(pos: vec2<f32>) -> f32 {
+    let radius = ambient_light.ambient_occlusion_radius;
+    let dist = get_distance(pos);
+    // The insides of occluders aren't darkened, and every sample of positions
+    // far enough away from occluders is unoccluded.
+    if dist <= 0.0 || dist >= radius * 1.5 {
+        return 1.0;
+    }
+
+    var occlusion = 1.0 - clamp(dist / radius, 0.0, 1.0);
+    for (var i = 0u; i < AMBIENT_OCCLUSION_SAMPLES; i++) {
+        let angle = f32(i) * TAU / f32(AMBIENT_OCCLUSION_SAMPLES);
+        let sample_pos = pos + vec2(cos(angle), sin(angle)) * radius * 0.5;
+        occlusion += 1.0 - clamp(get_distance(sample_pos) / radius, 0.0, 1.0);
+    }
+    occlusion /= f32(AMBIENT_OCCLUSION_SAMPLES + 1u);
+
+    return 1.0 - ambient_light.ambient_occlusion_strength * occlusion;
+}
+
+// Returns the ambient map's color at a world space position, or white outside
+// of the map's region.
+fn ambient_map_color(pos: vec2<f32>) -> vec3<f32> {
+    let region_min = ambient_light.ambient_map_min;
+    let region_max = ambient_light.ambient_map_max;
+    if any(region_max <= region_min) || any(pos < region_min) || any(pos > region_max) {
+        return vec3(1.0);
+    }
+
+    // Texture rows go downwards, while world space y goes upwards.
+    let uv = vec2(pos.x - region_min.x, region_max.y - pos.y) / (region_max - region_min);
+    return textureSampleLevel(ambient_map, light_cookie_sampler, uv, 0.0).rgb;
+}
+
+// Layers the ambient light zones covering a world space position over the
+// view's ambient light.
+fn ambient_zone_color(pos: vec2<f32>, ambient_color: vec3<f32>) -> vec3<f32> {
+    var color = ambient_color;
+    for (var i = 0u; i < light_map_meta.ambient_light_zone_count; i++) {
+        let zone = light_map_meta.ambient_light_zones[i];
+        if !in_view(zone.render_layers) {
+            continue;
+        }
+        let weight = ambient_zone_weight(zone, pos);
+        if weight > 0.0 {
+            color = mix(color, zone.color.rgb, weight);
+        }
+    }
+    return color;
+}
+
+// Returns how much of a zone's ambient light reaches a world space position,
+// fading in over the zone's edge softness inside its edge.
+fn ambient_zone_weight(zone: AmbientLightZone2d, pos: vec2<f32>) -> f32 {
+    let offset = pos - zone.center;
+    let local = vec2(dot(offset, zone.rotation), dot(offset, vec2(-zone.rotation.y, zone.rotation.x)));
+    var dist: f32;
+    if zone.shape == AMBIENT_LIGHT_ZONE_CIRCLE {
+        dist = length(local) - zone.half_size.x;
+    } else {
+        let q = abs(local) - zone.half_size;
+        dist = length(max(q, vec2(0.0))) + min(max(q.x, q.y), 0.0);
+    }
+    if zone.edge_softness <= 0.0 {
+        return select(0.0, 1.0, dist <= 0.0);
+    }
+    return smoothstep(0.0, zone.edge_softness, -dist);
+}
+
+// Returns the baked light at a world space position, interpolated between the
+// four nearest texels, or no light outside of the lightmap's region.
+fn baked_light(pos: vec2<f32>) -> vec3<f32> {
+    let region_min = ambient_light.baked_lightmap_min;
+    let region_max = ambient_light.baked_lightmap_max;
+    if any(region_max <= region_min) || any(pos < region_min) || any(pos > region_max) {
+        return vec3(0.0);
+    }
+
+    // Lightmap rows go downwards, while world space y goes upwards.
+    let size = vec2<i32>(textureDimensions(baked_lightmap));
+    let uv = vec2(pos.x - region_min.x, region_max.y - pos.y) / (region_max - region_min);
+    let texel = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(texel));
+    let t = fract(texel);
+
+    let max_texel = size - 1;
+    let a = textureLoad(baked_lightmap, clamp(base, vec2(0), max_texel), 0).rgb;
+    let b = textureLoad(baked_lightmap, clamp(base + vec2(1, 0), vec2(0), max_texel), 0).rgb;
+    let c = textureLoad(baked_lightmap, clamp(base + vec2(0, 1), vec2(0), max_texel), 0).rgb;
+    let d = textureLoad(baked_lightmap, clamp(base + vec2(1, 1), vec2(0), max_texel), 0).rgb;
+
+    return mix(mix(a, b, t.x), mix(c, d, t.x), t.y);
+}
+
 // Compute light attenutation.
 // See https://lisyarus.github.io/blog/posts/point-light-attenuation.html
 fn attenuation(dist: f32, radius: f32, intensity: f32, falloff: f32) -> f32 {
@@ -109,56 +740,362 @@ fn attenuation(dist: f32, radius: f32, intensity: f32, falloff: f32) -> f32 {
     return intensity * square(1.0 - s2) / (1.0 + falloff * s2);
 }
 
+// Remaps a distance from a light so that attenuation begins at the light's
+// inner radius, leaving the light at its full intensity within it.
+fn hotspot_distance(dist: f32, inner_radius: f32, radius: f32) -> f32 {
+    if inner_radius <= 0.0 {
+        return dist;
+    }
+    if dist <= inner_radius {
+        return 0.0;
+    }
+    return (dist - inner_radius) * radius / (radius - inner_radius);
+}
+
+// Compute light attenuation along a falloff curve, matching `FalloffCurve`.
+// Only the inverse square curve is shaped by the light's falloff, and by its
+// attenuation exponent, which is passed as the curve's exponent.
+fn curve_attenuation(dist: f32, radius: f32, intensity: f32, falloff: f32, curve: u32, exponent: f32) -> f32 {
+    let s = dist / radius;
+    if s > 1.0 {
+        return 0.0;
+    }
+    if curve == FALLOFF_CURVE_LINEAR {
+        return intensity * (1.0 - s);
+    }
+    if curve == FALLOFF_CURVE_SMOOTHSTEP {
+        return intensity * (1.0 - smoothstep(0.0, 1.0, s));
+    }
+    if curve == FALLOFF_CURVE_EXPONENT {
+        return intensity * pow(1.0 - s, exponent);
+    }
+    // The default exponent of 2.0 takes the exact same path as before, so
+    // lights that don't change it are lit identically.
+    if exponent != 2.0 {
+        let s2 = square(s);
+        return intensity * pow(1.0 - s2, exponent) / (1.0 + falloff * s2);
+    }
+    return attenuation(dist, radius, intensity, falloff);
+}
+
+// Shifts a light's color towards its edge color as the light falls off, where
+// `falloff` is the light's attenuation at unit intensity. Lights whose edge
+// color has an alpha of zero are a single color.
+fn gradient_color(color: vec3<f32>, edge_color: vec4<f32>, falloff: f32) -> vec3<f32> {
+    if edge_color.a == 0.0 {
+        return color;
+    }
+    return mix(edge_color.rgb, color, clamp(falloff, 0.0, 1.0));
+}
+
 fn get_distance(pos: vec2<f32>) -> f32 {
     let uv = ndc_to_uv(world_to_ndc(pos));
     let dist = textureSampleLevel(sdf, sdf_sampler, uv, 0.0).r;
     return dist;
 }
 
-fn distance_squared(a: vec2<f32>, b: vec2<f32>) -> f32 {
-    let c = a - b;
-    return dot(c, c);
+struct SdfSample {
+    distance: f32,
+    // The softness of the nearest occluder.
+    softness: f32,
+}
+
+fn sample_sdf(pos: vec2<f32>) -> SdfSample {
+    let uv = ndc_to_uv(world_to_ndc(pos));
+    let sample = textureSampleLevel(sdf, sdf_sampler, uv, 0.0);
+    return SdfSample(sample.r, sample.a);
+}
+
+// Returns the index of the nearest occluder. Indices can't be interpolated, so
+// the nearest texel is loaded rather than sampled.
+fn get_occluder_index(pos: vec2<f32>) -> u32 {
+    let uv = clamp(ndc_to_uv(world_to_ndc(pos)), vec2(0.0), vec2(1.0));
+    let size = textureDimensions(sdf);
+    let texel = min(vec2<u32>(uv * vec2<f32>(size)), size - vec2(1u));
+    let sample = textureLoad(sdf, texel, 0);
+    return u32(sample.g) + u32(sample.b) * 2048u;
+}
+
+// Returns an occluder by its index across the static and the rest of the
+// occluders.
+fn get_occluder(index: u32) -> LightOccluder2d {
+    if index < light_map_meta.static_occluder_count {
+        return static_occluders[index];
+    }
+    return occluders[index - light_map_meta.static_occluder_count];
+}
+
+// The fraction of each color channel that passes through an occluder.
+// Occluders let through light of their tint's color, scaled by their opacity.
+// Untinted occluders have a black tint. The light that's blocked is modulated
+// by the occluder's shadow color, rather than being removed.
+fn occluder_transmittance(occluder: LightOccluder2d) -> vec3<f32> {
+    let transmittance = 1.0 - occluder.opacity * (1.0 - occluder_tint(occluder).rgb);
+    return transmittance + (1.0 - transmittance) * occluder_shadow_color(occluder).rgb;
+}
+
+fn is_one_sided(occluder: LightOccluder2d) -> bool {
+    return any(occluder.facing != vec2(0.0));
+}
+
+// Whether an occluder blocks a ray travelling from a fragment towards a light.
+// One-sided occluders only block light arriving from in front of them.
+fn blocks_ray(occluder: LightOccluder2d, ray_direction: vec2<f32>) -> bool {
+    return !is_one_sided(occluder) || dot(ray_direction, occluder.facing) > 0.0;
+}
+
+// Combines the light of a light with the light accumulated so far, using the
+// view's blend mode.
+fn blend_light(accumulated: vec3<f32>, light: vec3<f32>) -> vec3<f32> {
+    switch ambient_light.blend {
+        case LIGHT_BLEND_MAX: {
+            return max(accumulated, light);
+        }
+        case LIGHT_BLEND_SCREEN: {
+            // Screen blending is only defined between zero and one, so light stops
+            // brightening a fragment once it's white.
+            return accumulated + min(light, vec3(1.0)) * max(vec3(1.0) - accumulated, vec3(0.0));
+        }
+        default: {
+            return accumulated + light;
+        }
+    }
+}
+
+// Scales a color down so that its linear luminance is at most
+// `max_brightness`, keeping its hue.
+fn clamp_brightness(color: vec3<f32>, max_brightness: f32) -> vec3<f32> {
+    let luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+    if luminance <= max_brightness {
+        return color;
+    }
+    return color * (max(max_brightness, 0.0) / luminance);
+}
+
+// Whether a light or occluder shares one of the view's render layers.
+fn in_view(render_layers: u32) -> bool {
+    return (render_layers & ambient_light.render_layers) != 0u;
+}
+
+// Whether an occluder blocks a light, which requires them to share an
+// occlusion layer, the occluder to be in the view, and the light not to ignore
+// the occluder.
+fn affects_light(occluder: LightOccluder2d, filter: OcclusionFilter) -> bool {
+    if (occluder.occlusion_layers & filter.occlusion_layers) == 0u
+        || !in_view(occluder.render_layers) {
+        return false;
+    }
+
+    for (var i = 0u; i < filter.exclusion_count; i++) {
+        let index = filter.exclusion_offset + i;
+        if light_map_meta.occluder_exclusions[index / 4u][index % 4u] == occluder.owner {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+// How strongly an occluder shades a fragment `shadow_distance` behind it, from
+// a light `light_distance` in front of it. Occluders with a height cast
+// shadows as long as their distance from the light, scaled by their height,
+// and shadows can't be longer than the occluder's maximum shadow distance.
+fn shadow_strength(occluder: LightOccluder2d, shadow_distance: f32, light_distance: f32) -> f32 {
+    var strength = 1.0;
+
+    if occluder.height > 0.0 {
+        strength = min(strength, shadow_fade(occluder.height * light_distance, shadow_distance));
+    }
+
+    // Negative distances are unlimited.
+    if occluder.max_shadow_distance >= 0.0 {
+        strength = min(strength, shadow_fade(occluder.max_shadow_distance, shadow_distance));
+    }
+
+    return strength;
+}
+
+// Fades out a shadow as it approaches its length, so that nothing beyond it is
+// shaded.
+fn shadow_fade(shadow_length: f32, shadow_distance: f32) -> f32 {
+    return 1.0 - smoothstep(shadow_length - SHADOW_FADE_DISTANCE, shadow_length, shadow_distance);
 }
 
-fn raymarch(ray_origin: vec2<f32>, ray_target: vec2<f32>) -> f32 {
+// Returns how much of each color channel reaches `ray_origin` from a light at
+// `ray_target`.
+fn shadow(ray_origin: vec2<f32>, ray_target: vec2<f32>, cast_shadows: u32, filter: OcclusionFilter) -> vec3<f32> {
+    if cast_shadows == 0u {
+        return vec3(1.0);
+    }
+    return raymarch(ray_origin, ray_target, filter, 0.0, 0.0);
+}
+
+// Returns how much of each color channel reaches `ray_origin` from a light at
+// `ray_target` with a source radius or a shadow softness, whose shadows have a
+// penumbra.
+fn soft_shadow(
+    ray_origin: vec2<f32>,
+    ray_target: vec2<f32>,
+    cast_shadows: u32,
+    filter: OcclusionFilter,
+    source_radius: f32,
+    softness: f32,
+) -> vec3<f32> {
+    if cast_shadows == 0u {
+        return vec3(1.0);
+    }
+    return raymarch(ray_origin, ray_target, filter, source_radius, softness);
+}
+
+// Marches a ray towards the target, returning the fraction of each color
+// channel that passes through any occluders along the way. Occluders that
+// don't affect the light are ignored. Targets with a source radius or a shadow
+// softness are partially shaded by occluders the ray passes close to.
+fn raymarch(ray_origin: vec2<f32>, ray_target: vec2<f32>, filter: OcclusionFilter, source_radius: f32, softness: f32) -> vec3<f32> {
     let ray_direction = normalize(ray_target - ray_origin);
-    let stop_at = distance_squared(ray_origin, ray_target);
+    let ray_length = distance(ray_origin, ray_target);
+    let stop_at = square(max(ray_length - RAY_TARGET_EPSILON, 0.0));
 
     var ray_progress: f32 = 0.0;
     var pos = vec2<f32>(0.0);
+    var transmittance = vec3<f32>(1.0);
+    var inside_occluder = false;
+    var current_occluder = 0u;
+    // Rays that pass close to soft occluders are partially shaded.
+    var shade: f32 = 1.0;
 
     for (var i = 0; i < 32; i++) {
         pos = ray_origin + ray_progress * ray_direction;
 
         if (ray_progress * ray_progress >= stop_at) {
             // ray found target
-            return 1.0;
+            return transmittance * shade;
         }
 
-        let dist = get_distance(pos);
+        let sample = sample_sdf(pos);
+        let dist = sample.distance;
 
         if dist <= 0.0 {
-            break;
-        }
+            let occluder_index = get_occluder_index(pos);
+            let occluder = get_occluder(occluder_index);
+
+            let step = max(-dist, MIN_RAY_STEP);
+
+            if blocks_ray(occluder, ray_direction) && affects_light(occluder, filter) {
+                let strength = shadow_strength(occluder, ray_progress, ray_length - ray_progress);
+
+                // Translucent occluders only filter the light once as the ray enters them.
+                if !inside_occluder || occluder_index != current_occluder {
+                    transmittance *= mix(vec3(1.0), occluder_transmittance(occluder), strength);
+                }
+
+#ifdef OCCLUDER_ABSORPTION
+                // Absorbing occluders remove a fraction of the light for every world unit
+                // the ray travels through them.
+                if occluder.absorption > 0.0 {
+                    transmittance *= mix(1.0, pow(1.0 - occluder.absorption, step), strength);
+                }
+#endif
+            }
+            inside_occluder = true;
+            current_occluder = occluder_index;
+
+            if all(transmittance <= vec3(0.0)) {
+                break;
+            }
+
+            // Step through the occluder.
+            ray_progress += step;
+        } else {
+            // The penumbra of a soft occluder, or of a light with soft shadows,
+            // widens up to its softness, but can't be wider than the distance the
+            // ray has travelled, to avoid shading fragments right next to the
+            // occluder. Rays inside occluders are still blocked, so light never
+            // leaks through them.
+            var penumbra = min(max(sample.softness, softness), ray_progress);
+            // The penumbra of a light with a source radius is the width of the cone
+            // between the fragment and the light's source, so it widens as the
+            // fragment gets further from the occluder.
+            if source_radius > 0.0 {
+                penumbra = max(penumbra, source_radius * ray_progress / ray_length);
+            }
+            let occluder = get_occluder(get_occluder_index(pos));
+            if penumbra > 0.0 && affects_light(occluder, filter) {
+                let strength = shadow_strength(occluder, ray_progress, ray_length - ray_progress);
+                shade = min(shade, mix(1.0, clamp(dist / penumbra, 0.0, 1.0), strength));
+            }
 
-        ray_progress += dist;
+            inside_occluder = false;
+            ray_progress += dist;
+        }
     }
 
     // ray found occluder
-    return 0.0;
+    return vec3(0.0);
+}
+
+#ifdef VOLUMETRIC_SAMPLES
+// Returns the light scattered towards the viewer along a spot light's beam,
+// between the light and `pos`, by marching samples along it. Samples shadowed
+// by occluders don't scatter any light, carving streaks out of the beam.
+fn in_scatter(
+    light: SpotLight2d,
+    pos: vec2<f32>,
+    effective_center: vec2<f32>,
+    filter: OcclusionFilter,
+    frag_coord: vec2<f32>,
+) -> vec3<f32> {
+    let sample_count = #{VOLUMETRIC_SAMPLES}u;
+
+    // Samples are jittered per fragment with interleaved gradient noise, which
+    // trades banding for less noticeable noise.
+    let jitter = fract(52.9829189 * fract(dot(frag_coord, vec2(0.06711056, 0.00583715))));
+
+    var scattered = vec3(0.0);
+    for (var i = 0u; i < sample_count; i++) {
+        let t = (f32(i) + jitter) / f32(sample_count);
+        let sample_pos = mix(effective_center, pos, t);
+        let dist = distance(effective_center, sample_pos);
+        let strength = curve_attenuation(hotspot_distance(dist, light.inner_radius, light.radius), light.radius, light.intensity, light.falloff, light_falloff_curve(light.flags), light.falloff_exponent);
+        if strength != 0.0 {
+            scattered += strength * shadow(sample_pos, effective_center, light.flags & LIGHT_FLAG_CAST_SHADOWS, filter);
+        }
+    }
+    return scattered / f32(sample_count);
 }
+#endif
 
 // Calculates the mask for a given spotlight. 
 // The direction, inner_angle, and outer_angle can be modulated to control the lit area of the spotlight.
 // Returns: a 0..1 value representing the intensity of a spotlight at a given position
 fn spot_mask(light: SpotLight2d, pos: vec2<f32>, effective_center: vec2<f32>) -> f32 {
     let to_frag = normalize(pos - effective_center);
-    let cos_theta = dot(-to_frag, normalize(light.direction));
+    let cos_theta = dot(to_frag, normalize(light.direction));
     let cos_inner = cos(light.inner_angle);
     let cos_outer = cos(light.outer_angle);
-    return clamp(smoothstep(cos_outer, cos_inner, cos_theta), 0.0, 1.0);
+    return pow(clamp(smoothstep(cos_outer, cos_inner, cos_theta), 0.0, 1.0), light.edge_exponent);
 }
 
+#ifdef SPOT_LIGHT_PROJECTION
+// Samples a spot light's projection texture, which is stretched across the
+// light's cone. The texture's horizontal axis follows the angle from the cone's
+// axis, and its vertical axis follows the distance from the light.
+fn spot_light_projection(light: SpotLight2d, extras: LightExtras2d, pos: vec2<f32>, effective_center: vec2<f32>) -> vec3<f32> {
+    let axis = normalize(light.direction);
+    let to_frag = pos - effective_center;
+    let angle = atan2(axis.x * to_frag.y - axis.y * to_frag.x, dot(axis, to_frag));
+    let half_angle = max(light.outer_angle, 0.0001);
+    // Angles increase anticlockwise, while image columns go left to right when
+    // looking along the cone.
+    let uv = vec2(0.5 - angle / (2.0 * half_angle), length(to_frag) / light.radius);
+    if any(uv < vec2(0.0)) || any(uv > vec2(1.0)) {
+        return vec3(0.0);
+    }
+    let atlas_uv = mix(extras.cookie_rect.xy, extras.cookie_rect.zw, uv);
+    return textureSampleLevel(light_cookie_atlas, light_cookie_sampler, atlas_uv, 0.0).rgb;
+}
+#endif
+
 // Calculates the effective center for a light from a given source_width.
 // If the source_width is 0, this will simply return the center position.
 // Returns: a vec2<f32> representing the closest point of the light source to the fragment.
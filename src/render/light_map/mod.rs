@@ -1,3 +1,4 @@
+mod cookie_atlas;
 mod node;
 mod pipeline;
 mod prepare;
@@ -5,18 +6,26 @@ mod prepare;
 use bevy::{
     asset::{Handle, weak_handle},
     ecs::{component::Component, resource::Resource},
-    math::Vec3,
+    math::{UVec4, Vec2, Vec4},
     render::{
         render_graph::RenderLabel,
-        render_resource::{ShaderType, UniformBuffer},
+        render_resource::{CachedRenderPipelineId, ShaderType, UniformBuffer},
         texture::CachedTexture,
     },
     shader::Shader,
 };
 
+use crate::render::extract::{
+    ExtractedAmbientLightZone2d, ExtractedDirectionalLight2d, ExtractedLightExtras2d,
+};
+
+pub use cookie_atlas::{LIGHT_COOKIE_ATLAS_SIZE, LightCookieAtlas, prepare_light_cookie_atlas};
 pub use node::LightMapNode;
 pub use pipeline::LightMapPipeline;
-pub use prepare::{prepare_light_map_texture, prepare_point_light_count, prepare_spot_light_count};
+pub use prepare::{
+    prepare_light_extras, prepare_light_map_meta, prepare_light_map_pipelines,
+    prepare_light_map_texture,
+};
 
 pub const LIGHT_MAP_SHADER: Handle<Shader> = weak_handle!("48777bb3-8a37-4b4d-a4f2-f10ff1ee4360");
 
@@ -26,46 +35,117 @@ pub struct LightMapPass;
 #[derive(Component)]
 pub struct LightMapTexture {
     pub light_map: CachedTexture,
+    // Light scattered by volumetric lights, which is added on top of the lit scene.
+    pub volumetric: CachedTexture,
 }
 
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct LightMapPipelineKey {
+    /// Whether rays track how far they travel through absorbing occluders, so that scenes
+    /// without them don't pay for it.
+    pub absorption: bool,
+    /// Whether spot lights sample their projection textures, so that scenes without them
+    /// don't pay for it.
+    pub spot_light_projection: bool,
+    /// The number of samples taken towards volumetric spot lights, or `0` if there aren't any.
+    pub volumetric_samples: u32,
+    /// Whether the pipeline renders a view's vision mask, rather than its light map.
+    pub vision_mask: bool,
+    /// Whether the ambient light is darkened near occluders, so that views without ambient
+    /// occlusion don't pay for it.
+    pub ambient_occlusion: bool,
+}
+
+#[derive(Component)]
+pub struct LightMapPipelineId(pub CachedRenderPipelineId);
+
+#[derive(Component)]
+pub struct VisionMaskPipelineId(pub CachedRenderPipelineId);
+
 #[derive(Resource, Default)]
-pub struct PointLightMetaBuffer {
-    pub buffer: UniformBuffer<PointLightMeta>,
+pub struct LightMapMetaBuffer {
+    pub buffer: UniformBuffer<LightMapMeta>,
 }
 
-#[derive(Default, ShaderType)]
-pub struct PointLightMeta {
-    pub count: u32,
-    // WebGL2 structs must be 16 byte aligned.
-    _padding: Vec3,
+/// The maximum number of occluders that can be ignored across all lights.
+pub const MAX_OCCLUDER_EXCLUSIONS: usize = 256;
+
+#[derive(Resource, Default)]
+pub struct OccluderExclusionBuffer {
+    /// The owners of occluders ignored by each light, extracted this frame. Each light refers
+    /// to a range of this list.
+    pub owners: Vec<u32>,
 }
 
-impl PointLightMeta {
-    pub fn new(count: u32) -> Self {
-        Self {
-            count,
-            _padding: Vec3::ZERO,
-        }
-    }
+#[derive(Resource, Default)]
+pub struct LightExtrasBuffer {
+    /// The extras of point and spot lights extracted this frame, which lights refer to by index.
+    pub extras: Vec<ExtractedLightExtras2d>,
+}
+
+/// The maximum number of directional lights that can be drawn in each frame.
+pub const MAX_DIRECTIONAL_LIGHTS: usize = 8;
+
+/// The maximum number of vertices that can be uploaded across all polygon, polyline and area
+/// lights.
+pub const MAX_POLYGON_LIGHT_BUFFER_VERTICES: usize = 256;
+
+#[derive(Resource, Default)]
+pub struct PolygonLightVertexBuffer {
+    /// World space polygon, polyline and area light vertices extracted this frame.
+    pub vertices: Vec<Vec2>,
 }
 
+/// The maximum number of ambient light zones that can be drawn in each frame.
+pub const MAX_AMBIENT_LIGHT_ZONES: usize = 32;
+
 #[derive(Resource, Default)]
-pub struct SpotLightMetaBuffer {
-    pub buffer: UniformBuffer<SpotLightMeta>,
+pub struct AmbientLightZoneBuffer {
+    /// Ambient light zones extracted this frame, in the order they're layered.
+    pub zones: Vec<ExtractedAmbientLightZone2d>,
 }
 
-#[derive(Default, ShaderType)]
-pub struct SpotLightMeta {
-    pub count: u32,
-    // WebGL2 structs must be 16 byte aligned.
-    _padding: Vec3,
+/// The counts, directional lights, occluder exclusions, polygon light vertices and ambient light
+/// zones used by the light map pass. They share a single uniform, so that the pass stays within
+/// the uniform buffer limits of WebGPU and WebGL2.
+#[derive(ShaderType)]
+pub struct LightMapMeta {
+    pub point_light_count: u32,
+    pub spot_light_count: u32,
+    pub directional_light_count: u32,
+    pub line_light_count: u32,
+    pub ring_light_count: u32,
+    pub polygon_light_count: u32,
+    // Static occluders are indexed before the rest of the occluders.
+    pub static_occluder_count: u32,
+    pub ambient_light_zone_count: u32,
+    // The elapsed light animation time, in seconds, which scrolls noise textures.
+    pub time: f32,
+    pub directional_lights: [ExtractedDirectionalLight2d; MAX_DIRECTIONAL_LIGHTS],
+    // Uniform array elements must be 16 byte aligned, so owners are packed in fours.
+    pub occluder_exclusions: [UVec4; MAX_OCCLUDER_EXCLUSIONS / 4],
+    // Uniform array elements must be 16 byte aligned, so vertices are packed in pairs.
+    pub polygon_light_vertices: [Vec4; MAX_POLYGON_LIGHT_BUFFER_VERTICES / 2],
+    // Ambient light zones, in the order they're layered.
+    pub ambient_light_zones: [ExtractedAmbientLightZone2d; MAX_AMBIENT_LIGHT_ZONES],
 }
 
-impl SpotLightMeta {
-    pub fn new(count: u32) -> Self {
+impl Default for LightMapMeta {
+    fn default() -> Self {
         Self {
-            count,
-            _padding: Vec3::ZERO,
+            point_light_count: 0,
+            spot_light_count: 0,
+            directional_light_count: 0,
+            line_light_count: 0,
+            ring_light_count: 0,
+            polygon_light_count: 0,
+            static_occluder_count: 0,
+            ambient_light_zone_count: 0,
+            time: 0.0,
+            directional_lights: [ExtractedDirectionalLight2d::default(); MAX_DIRECTIONAL_LIGHTS],
+            occluder_exclusions: [UVec4::ZERO; MAX_OCCLUDER_EXCLUSIONS / 4],
+            polygon_light_vertices: [Vec4::ZERO; MAX_POLYGON_LIGHT_BUFFER_VERTICES / 2],
+            ambient_light_zones: [ExtractedAmbientLightZone2d::default(); MAX_AMBIENT_LIGHT_ZONES],
         }
     }
 }
@@ -1,25 +1,33 @@
 use bevy::ecs::system::lifetimeless::Read;
 use bevy::prelude::*;
 use bevy::render::extract_component::{ComponentUniforms, DynamicUniformIndex};
+use bevy::render::render_asset::RenderAssets;
 use bevy::render::render_graph::ViewNode;
 
 use bevy::render::render_resource::{
     BindGroupEntries, GpuArrayBuffer, Operations, PipelineCache, RenderPassColorAttachment,
-    RenderPassDescriptor,
+    RenderPassDescriptor, TextureFormat,
 };
 use bevy::render::renderer::RenderDevice;
+use bevy::render::texture::{FallbackImage, GpuImage};
 use bevy::render::view::{ViewUniformOffset, ViewUniforms};
 use smallvec::{SmallVec, smallvec};
 
 use crate::render::empty_buffer::EmptyBuffer;
 use crate::render::extract::{
-    ExtractedAmbientLight2d, ExtractedPointLight2d, ExtractedSpotLight2d,
+    ExtractedAmbientLight2d, ExtractedLight2d, ExtractedLightExtras2d, ExtractedLightOccluder2d,
+    ExtractedLineLight2d, ExtractedPointLight2d, ExtractedPolygonLight2d, ExtractedRingLight2d,
+    ExtractedSpotLight2d,
 };
-use crate::render::sdf::SdfTexture;
+use crate::render::sdf::{SdfTexture, StaticOccluderBuffer};
 
-use super::{LightMapPipeline, LightMapTexture, PointLightMetaBuffer, SpotLightMetaBuffer};
+use super::{
+    LightCookieAtlas, LightMapMetaBuffer, LightMapPipeline, LightMapPipelineId, LightMapTexture,
+    VisionMaskPipelineId,
+};
 
 const LIGHT_MAP_PASS: &str = "light_map_pass";
+const VISION_MASK_PASS: &str = "vision_mask_pass";
 const LIGHT_MAP_BIND_GROUP: &str = "light_map_bind_group";
 
 #[derive(Default)]
@@ -31,17 +39,24 @@ impl ViewNode for LightMapNode {
         Read<ViewUniformOffset>,
         Read<LightMapTexture>,
         Read<SdfTexture>,
+        Read<LightMapPipelineId>,
+        Read<ExtractedLight2d>,
+        Option<Read<VisionMaskPipelineId>>,
     );
 
     fn run<'w>(
         &self,
         _graph: &mut bevy::render::render_graph::RenderGraphContext,
         render_context: &mut bevy::render::renderer::RenderContext<'w>,
-        (ambient_index, view_offset, light_map_texture, sdf_texture): bevy::ecs::query::QueryItem<
-            'w,
-            'w,
-            Self::ViewQuery,
-        >,
+        (
+            ambient_index,
+            view_offset,
+            light_map_texture,
+            sdf_texture,
+            pipeline_id,
+            light_2d,
+            vision_mask_pipeline_id,
+        ): bevy::ecs::query::QueryItem<'w, 'w, Self::ViewQuery>,
         world: &'w World,
     ) -> Result<(), bevy::render::render_graph::NodeRunError> {
         let light_map_pipeline = world.resource::<LightMapPipeline>();
@@ -52,11 +67,17 @@ impl ViewNode for LightMapNode {
             Some(view_uniform_binding),
             Some(ambient_light_uniform),
             Some(point_light_binding),
-            Some(point_light_count_binding),
+            Some(light_map_meta_binding),
             Some(spot_light_binding),
-            Some(spot_light_count_binding),
+            Some(occluder_binding),
+            Some(static_occluder_binding),
+            Some(light_extras_binding),
+            Some(line_light_binding),
+            Some(light_cookie_atlas),
+            Some(ring_light_binding),
+            Some(polygon_light_binding),
         ) = (
-            pipeline_cache.get_render_pipeline(light_map_pipeline.pipeline_id),
+            pipeline_cache.get_render_pipeline(pipeline_id.0),
             world.resource::<ViewUniforms>().uniforms.binding(),
             world
                 .resource::<ComponentUniforms<ExtractedAmbientLight2d>>()
@@ -66,17 +87,54 @@ impl ViewNode for LightMapNode {
                 .resource::<GpuArrayBuffer<ExtractedPointLight2d>>()
                 .binding()
                 .or(world.resource::<EmptyBuffer>().binding()),
-            world.resource::<PointLightMetaBuffer>().buffer.binding(),
+            world.resource::<LightMapMetaBuffer>().buffer.binding(),
             world
                 .resource::<GpuArrayBuffer<ExtractedSpotLight2d>>()
                 .binding()
                 .or(world.resource::<EmptyBuffer>().binding()),
-            world.resource::<SpotLightMetaBuffer>().buffer.binding(),
+            world
+                .resource::<GpuArrayBuffer<ExtractedLightOccluder2d>>()
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
+            world
+                .resource::<StaticOccluderBuffer>()
+                .buffer
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
+            world
+                .resource::<GpuArrayBuffer<ExtractedLightExtras2d>>()
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
+            world
+                .resource::<GpuArrayBuffer<ExtractedLineLight2d>>()
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
+            world.resource::<LightCookieAtlas>().texture_view.as_ref(),
+            world
+                .resource::<GpuArrayBuffer<ExtractedRingLight2d>>()
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
+            world
+                .resource::<GpuArrayBuffer<ExtractedPolygonLight2d>>()
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
         )
         else {
             return Ok(());
         };
 
+        // Views without a baked lightmap bind a fallback texture, which is never sampled.
+        let baked_lightmap = light_2d
+            .baked_lightmap
+            .and_then(|image| world.resource::<RenderAssets<GpuImage>>().get(image))
+            .unwrap_or(&world.resource::<FallbackImage>().d2);
+        // Views without a loaded ambient map bind a white fallback texture, which leaves the
+        // ambient light untouched.
+        let ambient_map = light_2d
+            .ambient_map
+            .and_then(|image| world.resource::<RenderAssets<GpuImage>>().get(image))
+            .unwrap_or(&world.resource::<FallbackImage>().d2);
+
         let light_map_bind_group = render_context.render_device().create_bind_group(
             LIGHT_MAP_BIND_GROUP,
             &light_map_pipeline.layout,
@@ -84,26 +142,43 @@ impl ViewNode for LightMapNode {
                 view_uniform_binding.clone(),
                 ambient_light_uniform.clone(),
                 point_light_binding.clone(),
-                point_light_count_binding.clone(),
+                light_map_meta_binding,
                 &sdf_texture.sdf.default_view,
                 &light_map_pipeline.sdf_sampler,
                 spot_light_binding.clone(),
-                spot_light_count_binding.clone(),
+                occluder_binding,
+                static_occluder_binding,
+                light_extras_binding,
+                line_light_binding,
+                light_cookie_atlas,
+                &light_map_pipeline.cookie_sampler,
+                ring_light_binding,
+                polygon_light_binding,
+                &baked_lightmap.texture_view,
+                &ambient_map.texture_view,
             )),
         );
 
         let mut light_map_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
             label: Some(LIGHT_MAP_PASS),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: &light_map_texture.light_map.default_view,
-                resolve_target: None,
-                ops: Operations::default(),
-                depth_slice: None,
-            })],
+            color_attachments: &[
+                Some(RenderPassColorAttachment {
+                    view: &light_map_texture.light_map.default_view,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                    depth_slice: None,
+                }),
+                Some(RenderPassColorAttachment {
+                    view: &light_map_texture.volumetric.default_view,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                    depth_slice: None,
+                }),
+            ],
             ..default()
         });
 
-        let mut light_map_offsets: SmallVec<[u32; 4]> =
+        let mut light_map_offsets: SmallVec<[u32; 11]> =
             smallvec![view_offset.offset, ambient_index.index()];
 
         // Storage buffers aren't available in WebGL2. We fall back to a
@@ -118,11 +193,45 @@ impl ViewNode for LightMapNode {
         {
             light_map_offsets.push(0); // point lights array
             light_map_offsets.push(0); // spot lights array
+            light_map_offsets.push(0); // occluders array
+            light_map_offsets.push(0); // static occluders array
+            light_map_offsets.push(0); // light extras array
+            light_map_offsets.push(0); // line lights array
+            light_map_offsets.push(0); // ring lights array
+            light_map_offsets.push(0); // polygon lights array
         }
 
         light_map_pass.set_render_pipeline(pipeline);
         light_map_pass.set_bind_group(0, &light_map_bind_group, &light_map_offsets);
         light_map_pass.draw(0..3, 0..1);
+        drop(light_map_pass);
+
+        // The vision mask shares the light map's bindings, but is drawn into the image of the
+        // view's `VisionMask2d`, once both are ready.
+        let (Some(vision_mask_pipeline), Some(vision_mask_image)) = (
+            vision_mask_pipeline_id
+                .and_then(|pipeline_id| pipeline_cache.get_render_pipeline(pipeline_id.0)),
+            light_2d
+                .vision_mask
+                .and_then(|image| world.resource::<RenderAssets<GpuImage>>().get(image))
+                .filter(|image| image.texture_format == TextureFormat::R8Unorm),
+        ) else {
+            return Ok(());
+        };
+
+        let mut vision_mask_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some(VISION_MASK_PASS),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &vision_mask_image.texture_view,
+                resolve_target: None,
+                ops: Operations::default(),
+                depth_slice: None,
+            })],
+            ..default()
+        });
+        vision_mask_pass.set_render_pipeline(vision_mask_pipeline);
+        vision_mask_pass.set_bind_group(0, &light_map_bind_group, &light_map_offsets);
+        vision_mask_pass.draw(0..3, 0..1);
 
         Ok(())
     }
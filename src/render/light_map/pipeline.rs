@@ -3,19 +3,22 @@ use bevy::ecs::resource::Resource;
 use bevy::ecs::world::{FromWorld, World};
 use bevy::render::render_resource::binding_types::{sampler, texture_2d, uniform_buffer};
 use bevy::render::render_resource::{
-    BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState, ColorWrites,
-    FragmentState, GpuArrayBuffer, MultisampleState, PipelineCache, PrimitiveState,
+    AddressMode, BindGroupLayout, BindGroupLayoutEntries, ColorTargetState, ColorWrites,
+    FilterMode, FragmentState, GpuArrayBuffer, MultisampleState, PrimitiveState,
     RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
-    TextureFormat, TextureSampleType,
+    SpecializedRenderPipeline, TextureFormat, TextureSampleType,
 };
 use bevy::render::renderer::RenderDevice;
 use bevy::render::view::ViewUniform;
+use bevy::shader::ShaderDefVal;
 
 use crate::render::extract::{
-    ExtractedAmbientLight2d, ExtractedPointLight2d, ExtractedSpotLight2d,
+    ExtractedAmbientLight2d, ExtractedLightExtras2d, ExtractedLightOccluder2d,
+    ExtractedLineLight2d, ExtractedPointLight2d, ExtractedPolygonLight2d, ExtractedRingLight2d,
+    ExtractedSpotLight2d,
 };
 
-use super::{LIGHT_MAP_SHADER, PointLightMeta, SpotLightMeta};
+use super::{LIGHT_MAP_SHADER, LightMapMeta, LightMapPipelineKey};
 
 const LIGHT_MAP_BIND_GROUP_LAYOUT: &str = "light_map_group_layout";
 const LIGHT_MAP_PIPELINE: &str = "light_map_pipeline";
@@ -24,7 +27,8 @@ const LIGHT_MAP_PIPELINE: &str = "light_map_pipeline";
 pub struct LightMapPipeline {
     pub layout: BindGroupLayout,
     pub sdf_sampler: Sampler,
-    pub pipeline_id: CachedRenderPipelineId,
+    pub cookie_sampler: Sampler,
+    pub fullscreen_shader: FullscreenShader,
 }
 
 impl FromWorld for LightMapPipeline {
@@ -40,45 +44,108 @@ impl FromWorld for LightMapPipeline {
                     uniform_buffer::<ViewUniform>(true),
                     uniform_buffer::<ExtractedAmbientLight2d>(true),
                     GpuArrayBuffer::<ExtractedPointLight2d>::binding_layout(render_device),
-                    uniform_buffer::<PointLightMeta>(false),
+                    uniform_buffer::<LightMapMeta>(false),
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
                     GpuArrayBuffer::<ExtractedSpotLight2d>::binding_layout(render_device),
-                    uniform_buffer::<SpotLightMeta>(false),
+                    GpuArrayBuffer::<ExtractedLightOccluder2d>::binding_layout(render_device),
+                    GpuArrayBuffer::<ExtractedLightOccluder2d>::binding_layout(render_device),
+                    GpuArrayBuffer::<ExtractedLightExtras2d>::binding_layout(render_device),
+                    GpuArrayBuffer::<ExtractedLineLight2d>::binding_layout(render_device),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    GpuArrayBuffer::<ExtractedRingLight2d>::binding_layout(render_device),
+                    GpuArrayBuffer::<ExtractedPolygonLight2d>::binding_layout(render_device),
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
                 ),
             ),
         );
 
         let sdf_sampler = render_device.create_sampler(&SamplerDescriptor::default());
 
-        let pipeline_id =
-            world
-                .resource_mut::<PipelineCache>()
-                .queue_render_pipeline(RenderPipelineDescriptor {
-                    label: Some(LIGHT_MAP_PIPELINE.into()),
-                    layout: vec![layout.clone()],
-                    vertex: fullscreen_shader.to_vertex_state(),
-                    fragment: Some(FragmentState {
-                        shader: LIGHT_MAP_SHADER,
-                        shader_defs: vec![],
-                        entry_point: Some("fragment".into()),
-                        targets: vec![Some(ColorTargetState {
-                            format: TextureFormat::Rgba16Float,
-                            blend: None,
-                            write_mask: ColorWrites::ALL,
-                        })],
-                    }),
-                    primitive: PrimitiveState::default(),
-                    depth_stencil: None,
-                    multisample: MultisampleState::default(),
-                    push_constant_ranges: vec![],
-                    zero_initialize_workgroup_memory: false,
-                });
+        // Cookies are sampled with linear filtering, so that their patterns are smooth.
+        let cookie_sampler = render_device.create_sampler(&SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
 
         Self {
             layout,
             sdf_sampler,
-            pipeline_id,
+            cookie_sampler,
+            fullscreen_shader,
+        }
+    }
+}
+
+impl SpecializedRenderPipeline for LightMapPipeline {
+    type Key = LightMapPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if key.absorption {
+            shader_defs.push("OCCLUDER_ABSORPTION".into());
+        }
+        if key.ambient_occlusion {
+            shader_defs.push("AMBIENT_OCCLUSION".into());
+        }
+        if key.spot_light_projection {
+            shader_defs.push("SPOT_LIGHT_PROJECTION".into());
+        }
+        if key.volumetric_samples > 0 && !key.vision_mask {
+            shader_defs.push(ShaderDefVal::UInt(
+                "VOLUMETRIC_SAMPLES".into(),
+                key.volumetric_samples,
+            ));
+        }
+
+        // Vision masks are rendered into a single channel image, by their own entry point.
+        let (entry_point, targets) = if key.vision_mask {
+            (
+                "vision_mask",
+                vec![Some(ColorTargetState {
+                    format: TextureFormat::R8Unorm,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            )
+        } else {
+            (
+                "fragment",
+                vec![
+                    Some(ColorTargetState {
+                        format: TextureFormat::Rgba16Float,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    }),
+                    Some(ColorTargetState {
+                        format: TextureFormat::Rgba16Float,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    }),
+                ],
+            )
+        };
+
+        RenderPipelineDescriptor {
+            label: Some(LIGHT_MAP_PIPELINE.into()),
+            layout: vec![self.layout.clone()],
+            vertex: self.fullscreen_shader.to_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: LIGHT_MAP_SHADER,
+                shader_defs,
+                entry_point: Some(entry_point.into()),
+                targets,
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
         }
     }
 }
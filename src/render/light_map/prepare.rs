@@ -1,23 +1,39 @@
 use bevy::{
     ecs::{
         entity::Entity,
+        query::With,
         system::{Commands, Query, Res, ResMut},
     },
+    log::warn_once,
+    math::{UVec4, Vec4},
     render::{
-        render_resource::{TextureDescriptor, TextureDimension, TextureFormat, TextureUsages},
+        render_resource::{
+            GpuArrayBuffer, PipelineCache, SpecializedRenderPipelines, TextureDescriptor,
+            TextureDimension, TextureFormat, TextureUsages,
+        },
         renderer::{RenderDevice, RenderQueue},
         texture::TextureCache,
         view::ViewTarget,
     },
 };
 
-use crate::render::extract::{ExtractedPointLight2d, ExtractedSpotLight2d};
+use crate::light::VolumetricLightQuality;
+use crate::modulation::Light2dAnimationTime;
+use crate::render::extract::{
+    ExtractedDirectionalLight2d, ExtractedLight2d, ExtractedLightExtras2d, ExtractedLineLight2d,
+    ExtractedPointLight2d, ExtractedPolygonLight2d, ExtractedRingLight2d, ExtractedSpotLight2d,
+};
+use crate::render::sdf::OccluderMetaBuffer;
 
 use super::{
-    LightMapTexture, PointLightMeta, PointLightMetaBuffer, SpotLightMeta, SpotLightMetaBuffer,
+    AmbientLightZoneBuffer, LightExtrasBuffer, LightMapMetaBuffer, LightMapPipeline,
+    LightMapPipelineId, LightMapPipelineKey, LightMapTexture, MAX_AMBIENT_LIGHT_ZONES,
+    MAX_DIRECTIONAL_LIGHTS, OccluderExclusionBuffer, PolygonLightVertexBuffer,
+    VisionMaskPipelineId,
 };
 
 const LIGHT_MAP_TEXTURE: &str = "light_map_texture";
+const VOLUMETRIC_LIGHT_TEXTURE: &str = "volumetric_light_texture";
 
 pub fn prepare_light_map_texture(
     mut commands: Commands,
@@ -40,34 +56,172 @@ pub fn prepare_light_map_texture(
             },
         );
 
+        let volumetric_texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some(VOLUMETRIC_LIGHT_TEXTURE),
+                size: view_target.main_texture().size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba16Float,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+
         commands.entity(entity).insert(LightMapTexture {
             light_map: light_map_texture,
+            volumetric: volumetric_texture,
         });
     }
 }
 
-pub fn prepare_point_light_count(
+pub fn prepare_light_map_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<LightMapPipeline>>,
+    light_map_pipeline: Res<LightMapPipeline>,
+    occluder_meta: Res<OccluderMetaBuffer>,
+    volumetric_light_quality: Res<VolumetricLightQuality>,
+    spot_lights: Query<&ExtractedSpotLight2d>,
+    view_targets: Query<(Entity, &ExtractedLight2d), With<ViewTarget>>,
+) {
+    let key = LightMapPipelineKey {
+        absorption: occluder_meta.absorption,
+        spot_light_projection: spot_lights.iter().any(|light| light.projection != 0),
+        volumetric_samples: if spot_lights
+            .iter()
+            .any(|light| light.volumetric_intensity > 0.0)
+        {
+            volumetric_light_quality.samples
+        } else {
+            0
+        },
+        vision_mask: false,
+        ambient_occlusion: false,
+    };
+
+    for (entity, light_2d) in &view_targets {
+        let light_map_key = LightMapPipelineKey {
+            ambient_occlusion: light_2d.ambient_occlusion,
+            ..key
+        };
+        let pipeline_id = pipelines.specialize(&pipeline_cache, &light_map_pipeline, light_map_key);
+        commands
+            .entity(entity)
+            .insert(LightMapPipelineId(pipeline_id));
+
+        if light_2d.vision_mask.is_some() {
+            let vision_mask_key = LightMapPipelineKey {
+                vision_mask: true,
+                ..key
+            };
+            let vision_mask_pipeline_id =
+                pipelines.specialize(&pipeline_cache, &light_map_pipeline, vision_mask_key);
+            commands
+                .entity(entity)
+                .insert(VisionMaskPipelineId(vision_mask_pipeline_id));
+        } else {
+            commands.entity(entity).remove::<VisionMaskPipelineId>();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn prepare_light_map_meta(
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
     point_lights: Query<&ExtractedPointLight2d>,
-    mut point_light_count: ResMut<PointLightMetaBuffer>,
+    spot_lights: Query<&ExtractedSpotLight2d>,
+    directional_lights: Query<&ExtractedDirectionalLight2d>,
+    line_lights: Query<&ExtractedLineLight2d>,
+    ring_lights: Query<&ExtractedRingLight2d>,
+    polygon_lights: Query<&ExtractedPolygonLight2d>,
+    occluder_meta: Res<OccluderMetaBuffer>,
+    occluder_exclusion_buffer: Res<OccluderExclusionBuffer>,
+    polygon_light_vertex_buffer: Res<PolygonLightVertexBuffer>,
+    ambient_light_zone_buffer: Res<AmbientLightZoneBuffer>,
+    animation_time: Res<Light2dAnimationTime>,
+    mut light_map_meta: ResMut<LightMapMetaBuffer>,
 ) {
-    let meta = PointLightMeta::new(point_lights.iter().len() as u32);
-    point_light_count.buffer.set(meta);
-    point_light_count
+    let meta = light_map_meta.buffer.get_mut();
+    meta.time = animation_time.elapsed_secs();
+    meta.point_light_count = point_lights.iter().len() as u32;
+    meta.spot_light_count = spot_lights.iter().len() as u32;
+    meta.directional_light_count =
+        directional_lights.iter().len().min(MAX_DIRECTIONAL_LIGHTS) as u32;
+    if directional_lights.iter().len() > MAX_DIRECTIONAL_LIGHTS {
+        warn_once!(
+            "There are {} directional lights, but at most {MAX_DIRECTIONAL_LIGHTS} are supported. \
+            Some directional lights won't be drawn.",
+            directional_lights.iter().len()
+        );
+    }
+    for (packed, directional_light) in meta.directional_lights.iter_mut().zip(&directional_lights) {
+        *packed = *directional_light;
+    }
+    meta.line_light_count = line_lights.iter().len() as u32;
+    meta.ring_light_count = ring_lights.iter().len() as u32;
+    meta.polygon_light_count = polygon_lights.iter().len() as u32;
+    meta.static_occluder_count = occluder_meta.buffer.get().static_count;
+
+    for (packed, owners) in meta
+        .occluder_exclusions
+        .iter_mut()
+        .zip(occluder_exclusion_buffer.owners.chunks(4))
+    {
+        let owner = |index: usize| owners.get(index).copied().unwrap_or_default();
+        *packed = UVec4::new(owner(0), owner(1), owner(2), owner(3));
+    }
+
+    for (packed, pair) in meta
+        .polygon_light_vertices
+        .iter_mut()
+        .zip(polygon_light_vertex_buffer.vertices.chunks(2))
+    {
+        let second = pair.get(1).copied().unwrap_or_default();
+        *packed = Vec4::new(pair[0].x, pair[0].y, second.x, second.y);
+    }
+
+    for (zone, extracted_zone) in meta
+        .ambient_light_zones
+        .iter_mut()
+        .zip(&ambient_light_zone_buffer.zones)
+    {
+        *zone = *extracted_zone;
+    }
+    meta.ambient_light_zone_count = ambient_light_zone_buffer
+        .zones
+        .len()
+        .min(MAX_AMBIENT_LIGHT_ZONES) as u32;
+
+    light_map_meta
         .buffer
         .write_buffer(&render_device, &render_queue);
 }
 
-pub fn prepare_spot_light_count(
+pub fn prepare_light_extras(
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
-    spot_lights: Query<&ExtractedSpotLight2d>,
-    mut spot_light_count: ResMut<SpotLightMetaBuffer>,
+    light_extras_buffer: Res<LightExtrasBuffer>,
+    mut gpu_light_extras: ResMut<GpuArrayBuffer<ExtractedLightExtras2d>>,
 ) {
-    let meta = SpotLightMeta::new(spot_lights.iter().len() as u32);
-    spot_light_count.buffer.set(meta);
-    spot_light_count
-        .buffer
-        .write_buffer(&render_device, &render_queue);
+    gpu_light_extras.clear();
+
+    for extras in &light_extras_buffer.extras {
+        // WebGL2 only binds the first batch of a uniform array buffer.
+        if gpu_light_extras
+            .push(*extras)
+            .dynamic_offset
+            .is_some_and(|offset| offset.get() > 0)
+        {
+            warn_once!(
+                "Point and spot lights with cookies, noise, edge colors or brightness limits \
+                exceed the limit of this platform. Some of them will be drawn without them."
+            );
+        }
+    }
+
+    gpu_light_extras.write_buffer(&render_device, &render_queue);
 }
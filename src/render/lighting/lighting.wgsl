@@ -9,9 +9,43 @@ var light_map_texture: texture_2d<f32>;
 @group(0) @binding(2)
 var texture_sampler: sampler;
 
+@group(0) @binding(3)
+var emissive_texture: texture_2d<f32>;
+
+@group(0) @binding(4)
+var volumetric_texture: texture_2d<f32>;
+
 @fragment
 fn fragment(vo: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let light_frag = textureSample(light_map_texture, texture_sampler, vo.uv);
     let scene_frag = textureSample(screen_texture, texture_sampler, vo.uv);
-    return scene_frag * light_frag;
+    let volumetric_frag = textureSample(volumetric_texture, texture_sampler, vo.uv);
+
+    // Light scattered by volumetric lights glows on its own, rather than lighting the scene.
+    return scene_frag * (light_frag + vec4(emissive(vo.uv), 0.0)) + vec4(volumetric_frag.rgb, 0.0);
+}
+
+// Returns the light emitted by emissive sprites at `uv`.
+fn emissive(uv: vec2<f32>) -> vec3<f32> {
+    let emitted = textureSampleLevel(emissive_texture, texture_sampler, uv, 0.0).rgb;
+
+#ifdef EMISSIVE_SPILL
+    // Emitted light spills over with a 5x5 gaussian kernel spread across the
+    // spill radius. Sprites keep their own light, rather than it being spread
+    // thin by the blur.
+    let spacing = f32(#{EMISSIVE_SPILL}) * 0.5 / vec2<f32>(textureDimensions(emissive_texture));
+    var spill = vec3(0.0);
+    var total_weight = 0.0;
+    for (var y = -2; y <= 2; y++) {
+        for (var x = -2; x <= 2; x++) {
+            let offset = vec2(f32(x), f32(y));
+            let weight = exp(-0.5 * dot(offset, offset));
+            spill += textureSampleLevel(emissive_texture, texture_sampler, uv + offset * spacing, 0.0).rgb * weight;
+            total_weight += weight;
+        }
+    }
+    return max(emitted, spill / total_weight);
+#else
+    return emitted;
+#endif
 }
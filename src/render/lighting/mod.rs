@@ -21,6 +21,8 @@ pub struct LightingPass;
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct LightingPipelineKey {
     pub hdr: bool,
+    /// The radius (in pixels) that emitted light is blurred over, or zero if it isn't blurred.
+    pub emissive_spill: u32,
 }
 
 #[derive(Component)]
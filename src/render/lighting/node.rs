@@ -7,6 +7,7 @@ use bevy::render::render_resource::{
 };
 use bevy::render::view::ViewTarget;
 
+use crate::render::emissive::EmissiveTexture;
 use crate::render::light_map::LightMapTexture;
 
 use super::{LightingPipeline, LightingPipelineId};
@@ -22,13 +23,14 @@ impl ViewNode for LightingNode {
         Read<ViewTarget>,
         Read<LightingPipelineId>,
         Read<LightMapTexture>,
+        Read<EmissiveTexture>,
     );
 
     fn run<'w>(
         &self,
         _graph: &mut bevy::render::render_graph::RenderGraphContext,
         render_context: &mut bevy::render::renderer::RenderContext<'w>,
-        (view_target, pipeline_id, light_map_texture): bevy::ecs::query::QueryItem<
+        (view_target, pipeline_id, light_map_texture, emissive_texture): bevy::ecs::query::QueryItem<
             'w,
             'w,
             Self::ViewQuery,
@@ -51,6 +53,8 @@ impl ViewNode for LightingNode {
                 post_process.source,
                 &light_map_texture.light_map.default_view,
                 &pipeline.sampler,
+                &emissive_texture.emissive.default_view,
+                &light_map_texture.volumetric.default_view,
             )),
         );
 
@@ -9,6 +9,7 @@ use bevy::render::render_resource::{
 };
 use bevy::render::renderer::RenderDevice;
 use bevy::render::view::ViewTarget;
+use bevy::shader::ShaderDefVal;
 
 use super::{LIGHTING_SHADER, LightingPipelineKey};
 
@@ -34,6 +35,8 @@ impl FromWorld for LightingPipeline {
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
                 ),
             ),
         );
@@ -53,13 +56,21 @@ impl SpecializedRenderPipeline for LightingPipeline {
     type Key = LightingPipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if key.emissive_spill > 0 {
+            shader_defs.push(ShaderDefVal::UInt(
+                "EMISSIVE_SPILL".into(),
+                key.emissive_spill,
+            ));
+        }
+
         RenderPipelineDescriptor {
             label: Some(LIGHTING_PIPELINE.into()),
             layout: vec![self.layout.clone()],
             vertex: self.fullscreen_shader.to_vertex_state(),
             fragment: Some(FragmentState {
                 shader: LIGHTING_SHADER,
-                shader_defs: vec![],
+                shader_defs,
                 entry_point: Some("fragment".into()),
                 targets: vec![Some(ColorTargetState {
                     format: if key.hdr {
@@ -6,7 +6,7 @@ use bevy::{
     },
 };
 
-use crate::render::extract::ExtractedAmbientLight2d;
+use crate::render::extract::ExtractedLight2d;
 
 use super::{LightingPipeline, LightingPipelineId, LightingPipelineKey};
 
@@ -15,13 +15,16 @@ pub fn prepare_lighting_pipelines(
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<LightingPipeline>>,
     lighting_pipeline: Res<LightingPipeline>,
-    view_targets: Query<(Entity, &ExtractedView), With<ExtractedAmbientLight2d>>,
+    view_targets: Query<(Entity, &ExtractedView, &ExtractedLight2d)>,
 ) {
-    for (entity, view) in view_targets.iter() {
+    for (entity, view, light_2d) in view_targets.iter() {
         let pipeline_id = pipelines.specialize(
             &pipeline_cache,
             &lighting_pipeline,
-            LightingPipelineKey { hdr: view.hdr },
+            LightingPipelineKey {
+                hdr: view.hdr,
+                emissive_spill: light_2d.emissive_spill,
+            },
         );
 
         commands
@@ -3,6 +3,7 @@ use bevy::{
     prelude::Shader,
 };
 
+pub mod emissive;
 pub mod empty_buffer;
 pub mod extract;
 pub mod light_map;
@@ -0,0 +1,300 @@
+use bevy::{
+    asset::{AssetId, Assets},
+    color::Alpha,
+    ecs::{
+        resource::Resource,
+        system::{Res, ResMut},
+    },
+    image::Image,
+    log::warn_once,
+    math::{IVec2, UVec2, Vec2, Vec4},
+    platform::collections::HashMap,
+    render::{
+        render_resource::{
+            Extent3d, Origin3d, TexelCopyBufferLayout, TexelCopyTextureInfo, Texture,
+            TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+            TextureView, TextureViewDescriptor,
+        },
+        renderer::{RenderDevice, RenderQueue},
+    },
+};
+
+const OCCLUDER_MASK_ATLAS: &str = "occluder_mask_atlas";
+
+/// The width and height of the occluder mask atlas, in texels.
+pub const OCCLUDER_MASK_ATLAS_SIZE: u32 = 1024;
+
+/// The distance (in mask texels) that can be stored in the atlas. Distances further than this
+/// are clamped, which keeps them a lower bound of the true distance.
+pub const MASK_SDF_RANGE: f32 = 8.0;
+
+// Images larger than this are downsampled before being baked.
+const MAX_MASK_SIZE: u32 = 128;
+
+// Empty texels around each mask, so that filtering doesn't bleed between masks.
+const MASK_PADDING: u32 = 1;
+
+// The encoded value of a texel that's as far from the mask as can be stored.
+const EMPTY_TEXEL: u8 = u8::MAX;
+
+/// An alpha mask that has been baked into the occluder mask atlas.
+#[derive(Clone, Copy)]
+pub struct OccluderMask {
+    /// The texture coordinates of the mask's first and last texel centers within the atlas.
+    pub uv_rect: Vec4,
+    /// The size of the source image, in pixels.
+    pub image_size: Vec2,
+    /// The size of the baked mask, in texels.
+    pub mask_size: UVec2,
+}
+
+/// Signed distance fields of alpha mask occluders, packed into a single texture so that any
+/// number of them can be sampled by the SDF pass.
+///
+/// Each texel stores the distance to the mask's edge in mask texels, remapped from
+/// `-MASK_SDF_RANGE..MASK_SDF_RANGE` to `0..1`.
+#[derive(Resource)]
+pub struct OccluderMaskAtlas {
+    masks: HashMap<(AssetId<Image>, u32), OccluderMask>,
+    data: Vec<u8>,
+    // Masks are packed into rows, left to right and top to bottom.
+    cursor: UVec2,
+    row_height: u32,
+    full: bool,
+    dirty: bool,
+    pub texture: Option<Texture>,
+    pub texture_view: Option<TextureView>,
+}
+
+impl Default for OccluderMaskAtlas {
+    fn default() -> Self {
+        Self {
+            masks: HashMap::default(),
+            data: vec![EMPTY_TEXEL; (OCCLUDER_MASK_ATLAS_SIZE * OCCLUDER_MASK_ATLAS_SIZE) as usize],
+            cursor: UVec2::ZERO,
+            row_height: 0,
+            full: false,
+            dirty: true,
+            texture: None,
+            texture_view: None,
+        }
+    }
+}
+
+impl OccluderMaskAtlas {
+    /// Clears the atlas if it ran out of space last frame, so that masks which are no longer
+    /// used don't take up space forever. Returns `true` if the atlas was cleared.
+    pub fn reclaim(&mut self) -> bool {
+        if !self.full {
+            return false;
+        }
+
+        self.masks.clear();
+        self.data.fill(EMPTY_TEXEL);
+        self.cursor = UVec2::ZERO;
+        self.row_height = 0;
+        self.full = false;
+        self.dirty = true;
+
+        true
+    }
+
+    /// Returns the baked mask for an image and alpha threshold, baking it if necessary.
+    ///
+    /// Returns `None` if the image isn't loaded, can't be read, or doesn't fit in the atlas.
+    pub fn get_or_bake(
+        &mut self,
+        image_id: AssetId<Image>,
+        threshold: f32,
+        images: &Assets<Image>,
+    ) -> Option<OccluderMask> {
+        let key = (image_id, threshold.to_bits());
+        if let Some(mask) = self.masks.get(&key) {
+            return Some(*mask);
+        }
+
+        let image = images.get(image_id)?;
+
+        if image.data.is_none() {
+            warn_once!(
+                "Alpha mask occluder image has no data in the main world, so it can't be used \
+                as an occluder. Make sure the image's asset usage includes the main world."
+            );
+            return None;
+        }
+
+        let (mask_size, mask) = bake_mask(image, threshold)?;
+        let position = self.allocate(mask_size)?;
+
+        for (y, row) in mask.chunks(mask_size.x as usize).enumerate() {
+            let start = ((position.y + y as u32) * OCCLUDER_MASK_ATLAS_SIZE + position.x) as usize;
+            self.data[start..start + row.len()].copy_from_slice(row);
+        }
+        self.dirty = true;
+
+        let atlas_size = OCCLUDER_MASK_ATLAS_SIZE as f32;
+        let min = (position.as_vec2() + 0.5) / atlas_size;
+        let max = ((position + mask_size).as_vec2() - 0.5) / atlas_size;
+
+        let mask = OccluderMask {
+            uv_rect: Vec4::new(min.x, min.y, max.x, max.y),
+            image_size: image.size().as_vec2(),
+            mask_size,
+        };
+        self.masks.insert(key, mask);
+
+        Some(mask)
+    }
+
+    /// Reserves space for a mask, returning the position of its first texel.
+    fn allocate(&mut self, mask_size: UVec2) -> Option<UVec2> {
+        let padded_size = mask_size + 2 * MASK_PADDING;
+
+        if self.cursor.x + padded_size.x > OCCLUDER_MASK_ATLAS_SIZE {
+            self.cursor = UVec2::new(0, self.cursor.y + self.row_height);
+            self.row_height = 0;
+        }
+
+        if self.cursor.y + padded_size.y > OCCLUDER_MASK_ATLAS_SIZE {
+            warn_once!(
+                "Alpha mask occluders don't fit in the {OCCLUDER_MASK_ATLAS_SIZE}x\
+                {OCCLUDER_MASK_ATLAS_SIZE} occluder mask atlas. Some alpha mask occluders will \
+                not cast shadows."
+            );
+            self.full = true;
+            return None;
+        }
+
+        let position = self.cursor + MASK_PADDING;
+        self.cursor.x += padded_size.x;
+        self.row_height = self.row_height.max(padded_size.y);
+
+        Some(position)
+    }
+}
+
+/// Bakes an image's alpha channel into an encoded signed distance field, returning the size of
+/// the mask and its texels.
+fn bake_mask(image: &Image, threshold: f32) -> Option<(UVec2, Vec<u8>)> {
+    let image_size = image.size();
+    if image_size.min_element() == 0 {
+        return None;
+    }
+
+    // Large images are downsampled, so that baking stays cheap and masks fit in the atlas.
+    let step = (image_size.max_element() as f32 / MAX_MASK_SIZE as f32).max(1.0);
+    let mask_size = (image_size.as_vec2() / step)
+        .ceil()
+        .as_uvec2()
+        .min(UVec2::splat(MAX_MASK_SIZE));
+
+    let mut solid = Vec::with_capacity((mask_size.x * mask_size.y) as usize);
+    for y in 0..mask_size.y {
+        for x in 0..mask_size.x {
+            let pixel = ((UVec2::new(x, y).as_vec2() + 0.5) * step)
+                .as_uvec2()
+                .min(image_size - 1);
+
+            let Ok(color) = image.get_color_at(pixel.x, pixel.y) else {
+                warn_once!(
+                    "Alpha mask occluder image uses a texture format that can't be read, so it \
+                    can't be used as an occluder."
+                );
+                return None;
+            };
+            solid.push(color.alpha() >= threshold);
+        }
+    }
+
+    let is_solid = |texel: IVec2| {
+        texel.cmpge(IVec2::ZERO).all()
+            && texel.cmplt(mask_size.as_ivec2()).all()
+            && solid[(texel.y * mask_size.x as i32 + texel.x) as usize]
+    };
+
+    // Brute force search for the nearest texel on the other side of the edge. Masks are small
+    // and only baked once, so this is fast enough.
+    let range = MASK_SDF_RANGE as i32;
+    let mut mask = Vec::with_capacity(solid.len());
+    for y in 0..mask_size.y as i32 {
+        for x in 0..mask_size.x as i32 {
+            let texel = IVec2::new(x, y);
+            let inside = is_solid(texel);
+
+            let mut distance = MASK_SDF_RANGE;
+            for dy in -range..=range {
+                for dx in -range..=range {
+                    let offset = IVec2::new(dx, dy);
+                    if is_solid(texel + offset) != inside {
+                        // The edge lies halfway between the two texels.
+                        distance = distance.min(offset.as_vec2().length() - 0.5);
+                    }
+                }
+            }
+
+            let signed_distance = if inside { -distance } else { distance };
+            let encoded = (signed_distance / MASK_SDF_RANGE) * 0.5 + 0.5;
+            mask.push((encoded.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+    }
+
+    Some((mask_size, mask))
+}
+
+pub fn prepare_occluder_mask_atlas(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut occluder_mask_atlas: ResMut<OccluderMaskAtlas>,
+) {
+    let occluder_mask_atlas = occluder_mask_atlas.as_mut();
+
+    if occluder_mask_atlas.texture.is_none() {
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some(OCCLUDER_MASK_ATLAS),
+            size: Extent3d {
+                width: OCCLUDER_MASK_ATLAS_SIZE,
+                height: OCCLUDER_MASK_ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R8Unorm,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        occluder_mask_atlas.texture_view =
+            Some(texture.create_view(&TextureViewDescriptor::default()));
+        occluder_mask_atlas.texture = Some(texture);
+        occluder_mask_atlas.dirty = true;
+    }
+
+    let Some(texture) = &occluder_mask_atlas.texture else {
+        return;
+    };
+
+    if !occluder_mask_atlas.dirty {
+        return;
+    }
+
+    render_queue.write_texture(
+        TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        &occluder_mask_atlas.data,
+        TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(OCCLUDER_MASK_ATLAS_SIZE),
+            rows_per_image: None,
+        },
+        Extent3d {
+            width: OCCLUDER_MASK_ATLAS_SIZE,
+            height: OCCLUDER_MASK_ATLAS_SIZE,
+            depth_or_array_layers: 1,
+        },
+    );
+    occluder_mask_atlas.dirty = false;
+}
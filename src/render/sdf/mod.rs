@@ -1,23 +1,34 @@
+mod mask_atlas;
 mod node;
 mod pipeline;
 mod prepare;
 
 use bevy::{
-    asset::{Handle, weak_handle},
+    asset::{AssetId, Handle, weak_handle},
     ecs::{component::Component, resource::Resource},
-    math::Vec3,
+    image::Image,
+    math::{Vec2, Vec4},
     render::{
         render_graph::RenderLabel,
-        render_resource::{ShaderType, UniformBuffer},
+        render_resource::{GpuArrayBuffer, ShaderType, UniformBuffer},
         texture::CachedTexture,
     },
     shader::Shader,
 };
 
+use crate::render::extract::ExtractedLightOccluder2d;
+
+pub use mask_atlas::{
+    MASK_SDF_RANGE, OCCLUDER_MASK_ATLAS_SIZE, OccluderMask, OccluderMaskAtlas,
+    prepare_occluder_mask_atlas,
+};
 pub use node::SdfNode;
 pub use pipeline::SdfPipeline;
-pub use prepare::prepare_occluder_meta;
+pub use prepare::prepare_occluder_sdf_texture;
+pub use prepare::prepare_occluder_vertices;
+pub use prepare::prepare_occluders;
 pub use prepare::prepare_sdf_texture;
+pub use prepare::prepare_static_occluders;
 
 pub const SDF_SHADER: Handle<Shader> = weak_handle!("16251728-6dd9-481e-95a7-7c2e0ff8d920");
 
@@ -31,20 +42,74 @@ pub struct SdfTexture {
 #[derive(Resource, Default)]
 pub struct OccluderMetaBuffer {
     pub buffer: UniformBuffer<OccluderMeta>,
+    /// Whether any occluder absorbs light, which requires the light map pipeline variant
+    /// that tracks how far rays travel through occluders.
+    pub absorption: bool,
+    /// The number of occluders in the [`StaticOccluderBuffer`].
+    pub static_count: u32,
+    /// Whether any static occluder absorbs light.
+    pub static_absorption: bool,
 }
 
 #[derive(Default, ShaderType)]
 pub struct OccluderMeta {
     pub count: u32,
+    // Static occluders are indexed before the rest of the occluders.
+    pub static_count: u32,
     // WebGL2 structs must be 16 byte aligned.
-    _padding: Vec3,
+    _padding: Vec2,
 }
 
 impl OccluderMeta {
-    pub fn new(count: u32) -> Self {
+    pub fn new(count: u32, static_count: u32) -> Self {
         Self {
             count,
-            _padding: Vec3::ZERO,
+            static_count,
+            _padding: Vec2::ZERO,
+        }
+    }
+}
+
+/// The occluders marked with [`StaticLightOccluder2d`], which are only uploaded when they
+/// change.
+///
+/// [`StaticLightOccluder2d`]: crate::occluder::StaticLightOccluder2d
+#[derive(Resource)]
+pub struct StaticOccluderBuffer {
+    pub buffer: GpuArrayBuffer<ExtractedLightOccluder2d>,
+}
+
+/// The distance field image sampled by [`LightOccluder2dShape::Sdf`] occluders this frame, if
+/// it has been loaded.
+///
+/// [`LightOccluder2dShape::Sdf`]: crate::occluder::LightOccluder2dShape::Sdf
+#[derive(Resource, Default, PartialEq)]
+pub struct OccluderSdfTexture {
+    pub image: Option<AssetId<Image>>,
+}
+
+/// The maximum number of polygon vertices that can be uploaded across all occluders.
+pub const MAX_OCCLUDER_VERTICES: usize = 512;
+
+#[derive(Resource, Default)]
+pub struct OccluderVertexBuffer {
+    /// World space polygon vertices extracted this frame.
+    pub vertices: Vec<Vec2>,
+    /// World space polygon vertices of static occluders, which come before the rest.
+    pub static_vertices: Vec<Vec2>,
+    pub buffer: UniformBuffer<OccluderVertices>,
+}
+
+#[derive(ShaderType)]
+pub struct OccluderVertices {
+    // Uniform array elements must be 16 byte aligned, so vertices are packed in pairs.
+    pub vertices: [Vec4; MAX_OCCLUDER_VERTICES / 2],
+}
+
+impl Default for OccluderVertices {
+    fn default() -> Self {
+        Self {
+            vertices: [Vec4::ZERO; MAX_OCCLUDER_VERTICES / 2],
         }
     }
 }
@@ -2,11 +2,13 @@ use bevy::ecs::system::lifetimeless::Read;
 use bevy::prelude::*;
 use bevy::render::render_graph::ViewNode;
 
+use bevy::render::render_asset::RenderAssets;
 use bevy::render::render_resource::{
     BindGroupEntries, GpuArrayBuffer, Operations, PipelineCache, RenderPassColorAttachment,
     RenderPassDescriptor,
 };
 use bevy::render::renderer::RenderDevice;
+use bevy::render::texture::{FallbackImage, GpuImage};
 use bevy::render::view::{ViewUniformOffset, ViewUniforms};
 use smallvec::{SmallVec, smallvec};
 
@@ -14,7 +16,10 @@ use crate::render::empty_buffer::EmptyBuffer;
 use crate::render::extract::ExtractedLightOccluder2d;
 
 use super::pipeline::SdfPipeline;
-use super::{OccluderMetaBuffer, SdfTexture};
+use super::{
+    OccluderMaskAtlas, OccluderMetaBuffer, OccluderSdfTexture, OccluderVertexBuffer, SdfTexture,
+    StaticOccluderBuffer,
+};
 
 const SDF_PASS: &str = "sdf_pass";
 const SDF_BIND_GROUP: &str = "sdf_bind_group";
@@ -40,6 +45,9 @@ impl ViewNode for SdfNode {
             Some(view_uniform_binding),
             Some(light_occluders_binding),
             Some(occluder_meta_buffer),
+            Some(occluder_vertices_buffer),
+            Some(occluder_mask_atlas),
+            Some(static_occluders_binding),
         ) = (
             pipeline_cache.get_render_pipeline(sdf_pipeline.pipeline_id),
             world.resource::<ViewUniforms>().uniforms.binding(),
@@ -48,11 +56,25 @@ impl ViewNode for SdfNode {
                 .binding()
                 .or(world.resource::<EmptyBuffer>().binding()),
             world.resource::<OccluderMetaBuffer>().buffer.binding(),
+            world.resource::<OccluderVertexBuffer>().buffer.binding(),
+            world.resource::<OccluderMaskAtlas>().texture_view.as_ref(),
+            world
+                .resource::<StaticOccluderBuffer>()
+                .buffer
+                .binding()
+                .or(world.resource::<EmptyBuffer>().binding()),
         )
         else {
             return Ok(());
         };
 
+        // Without any distance field occluders, an empty texture is bound instead.
+        let occluder_sdf_texture = world
+            .resource::<OccluderSdfTexture>()
+            .image
+            .and_then(|image| world.resource::<RenderAssets<GpuImage>>().get(image))
+            .unwrap_or(&world.resource::<FallbackImage>().d2);
+
         let bind_group = render_context.render_device().create_bind_group(
             SDF_BIND_GROUP,
             &sdf_pipeline.layout,
@@ -60,6 +82,11 @@ impl ViewNode for SdfNode {
                 view_uniform_binding.clone(),
                 light_occluders_binding,
                 occluder_meta_buffer,
+                occluder_vertices_buffer,
+                occluder_mask_atlas,
+                &sdf_pipeline.mask_sampler,
+                &occluder_sdf_texture.texture_view,
+                static_occluders_binding,
             )),
         );
 
@@ -86,7 +113,8 @@ impl ViewNode for SdfNode {
             .max_storage_buffers_per_shader_stage
             == 0
         {
-            dynamic_offsets.push(0);
+            dynamic_offsets.push(0); // occluders array
+            dynamic_offsets.push(0); // static occluders array
         }
 
         sdf_pass.set_render_pipeline(pipeline);
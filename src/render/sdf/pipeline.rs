@@ -1,18 +1,18 @@
 use bevy::core_pipeline::FullscreenShader;
 use bevy::prelude::*;
-use bevy::render::render_resource::binding_types::uniform_buffer;
+use bevy::render::render_resource::binding_types::{sampler, texture_2d, uniform_buffer};
 use bevy::render::render_resource::{
-    BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState, ColorWrites,
-    FragmentState, GpuArrayBuffer, MultisampleState, PipelineCache, PrimitiveState,
-    RenderPipelineDescriptor, ShaderStages, TextureFormat,
+    AddressMode, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState,
+    ColorWrites, FilterMode, FragmentState, GpuArrayBuffer, MultisampleState, PipelineCache,
+    PrimitiveState, RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor,
+    ShaderStages, TextureFormat, TextureSampleType,
 };
 use bevy::render::renderer::RenderDevice;
 use bevy::render::view::ViewUniform;
 
 use crate::render::extract::ExtractedLightOccluder2d;
-use crate::render::light_map::PointLightMeta;
 
-use super::SDF_SHADER;
+use super::{OccluderMeta, OccluderVertices, SDF_SHADER};
 
 const SDF_PIPELINE: &str = "sdf_pipeline";
 const SDF_BIND_GROUP_LAYOUT: &str = "sdf_bind_group_layout";
@@ -21,6 +21,7 @@ const SDF_BIND_GROUP_LAYOUT: &str = "sdf_bind_group_layout";
 pub struct SdfPipeline {
     pub layout: BindGroupLayout,
     pub pipeline_id: CachedRenderPipelineId,
+    pub mask_sampler: Sampler,
 }
 
 impl FromWorld for SdfPipeline {
@@ -36,11 +37,27 @@ impl FromWorld for SdfPipeline {
                 (
                     uniform_buffer::<ViewUniform>(true),
                     GpuArrayBuffer::<ExtractedLightOccluder2d>::binding_layout(render_device),
-                    uniform_buffer::<PointLightMeta>(false),
+                    uniform_buffer::<OccluderMeta>(false),
+                    uniform_buffer::<OccluderVertices>(false),
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                    // Distance field textures are usually 32 bit floats, which can't always
+                    // be filtered, so they're interpolated in the shader instead.
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    GpuArrayBuffer::<ExtractedLightOccluder2d>::binding_layout(render_device),
                 ),
             ),
         );
 
+        // Alpha masks are sampled with linear filtering, so that their edges are smooth.
+        let mask_sampler = render_device.create_sampler(&SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+
         let pipeline_id = pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
             label: Some(SDF_PIPELINE.into()),
             layout: vec![layout.clone()],
@@ -65,6 +82,7 @@ impl FromWorld for SdfPipeline {
         Self {
             layout,
             pipeline_id,
+            mask_sampler,
         }
     }
 }
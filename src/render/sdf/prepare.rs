@@ -1,19 +1,33 @@
 use bevy::{
+    asset::AssetId,
     ecs::{
+        change_detection::{DetectChanges, DetectChangesMut},
         entity::Entity,
         system::{Commands, Query, Res, ResMut},
     },
+    image::Image,
+    log::warn_once,
+    math::{Vec2, Vec4},
     render::{
-        render_resource::{TextureDescriptor, TextureDimension, TextureFormat, TextureUsages},
+        render_asset::RenderAssets,
+        render_resource::{
+            GpuArrayBuffer, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
         renderer::{RenderDevice, RenderQueue},
-        texture::TextureCache,
+        texture::{GpuImage, TextureCache},
         view::ViewTarget,
     },
 };
 
-use crate::render::extract::ExtractedLightOccluder2d;
+use crate::render::extract::{
+    ExtractedLightOccluder2d, ExtractedLightOccluders2d, ExtractedOccluders,
+    OCCLUDER_SHAPE_POLYGON, OCCLUDER_SHAPE_SDF, OCCLUDER_SHAPE_TRIANGLES,
+};
 
-use super::{OccluderMeta, OccluderMetaBuffer, SdfTexture};
+use super::{
+    MAX_OCCLUDER_VERTICES, OccluderMeta, OccluderMetaBuffer, OccluderSdfTexture,
+    OccluderVertexBuffer, SdfTexture, StaticOccluderBuffer,
+};
 
 const SDF_TEXTURE: &str = "sdf_texture";
 
@@ -44,15 +58,183 @@ pub fn prepare_sdf_texture(
     }
 }
 
-pub fn prepare_occluder_meta(
+/// Chooses the distance field image sampled by occluders this frame, from the first occluder
+/// whose image has loaded.
+pub fn prepare_occluder_sdf_texture(
+    extracted_occluders: Res<ExtractedOccluders>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    mut occluder_sdf_texture: ResMut<OccluderSdfTexture>,
+) {
+    // The static occluders need to be uploaded again when the image changes, so it's only
+    // set when it differs.
+    occluder_sdf_texture.set_if_neq(OccluderSdfTexture {
+        image: extracted_occluders
+            .static_occluders
+            .values()
+            .chain(extracted_occluders.occluders.values())
+            .filter_map(|extracted_occluders| extracted_occluders.sdf_image)
+            .find(|image| gpu_images.contains_key(*image)),
+    });
+}
+
+/// Uploads the static occluders, but only when they've changed since they were last uploaded.
+pub fn prepare_static_occluders(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    extracted_occluders: Res<ExtractedOccluders>,
+    occluder_sdf_texture: Res<OccluderSdfTexture>,
+    mut static_occluder_buffer: ResMut<StaticOccluderBuffer>,
+    mut occluder_meta_buffer: ResMut<OccluderMetaBuffer>,
+    mut occluder_vertex_buffer: ResMut<OccluderVertexBuffer>,
+) {
+    if !extracted_occluders.static_changed && !occluder_sdf_texture.is_changed() {
+        return;
+    }
+
+    static_occluder_buffer.buffer.clear();
+    occluder_vertex_buffer.static_vertices.clear();
+
+    let (count, absorption) = push_occluders(
+        extracted_occluders.static_occluders.values(),
+        occluder_sdf_texture.image,
+        &mut static_occluder_buffer.buffer,
+        &mut occluder_vertex_buffer.static_vertices,
+    );
+
+    static_occluder_buffer
+        .buffer
+        .write_buffer(&render_device, &render_queue);
+
+    occluder_meta_buffer.static_count = count;
+    occluder_meta_buffer.static_absorption = absorption;
+}
+
+pub fn prepare_occluders(
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
-    occluders: Query<&ExtractedLightOccluder2d>,
+    extracted_occluders: Res<ExtractedOccluders>,
+    occluder_sdf_texture: Res<OccluderSdfTexture>,
+    mut occluder_buffer: ResMut<GpuArrayBuffer<ExtractedLightOccluder2d>>,
     mut occluder_meta_buffer: ResMut<OccluderMetaBuffer>,
+    mut occluder_vertex_buffer: ResMut<OccluderVertexBuffer>,
 ) {
-    let meta = OccluderMeta::new(occluders.iter().len() as u32);
+    occluder_buffer.clear();
+
+    // Static polygon vertices come first, so that the offsets of static occluders don't
+    // change.
+    let occluder_vertex_buffer = occluder_vertex_buffer.as_mut();
+    occluder_vertex_buffer
+        .vertices
+        .clone_from(&occluder_vertex_buffer.static_vertices);
+
+    let (count, absorption) = push_occluders(
+        extracted_occluders.occluders.values(),
+        occluder_sdf_texture.image,
+        &mut occluder_buffer,
+        &mut occluder_vertex_buffer.vertices,
+    );
+
+    occluder_buffer.write_buffer(&render_device, &render_queue);
+
+    let meta = OccluderMeta::new(count, occluder_meta_buffer.static_count);
     occluder_meta_buffer.buffer.set(meta);
+    occluder_meta_buffer.absorption = absorption || occluder_meta_buffer.static_absorption;
     occluder_meta_buffer
         .buffer
         .write_buffer(&render_device, &render_queue);
 }
+
+/// Pushes the shapes of extracted occluders to an occluder buffer, returning how many were
+/// pushed, and whether any of them absorb light.
+fn push_occluders<'a>(
+    extracted_occluders: impl Iterator<Item = &'a ExtractedLightOccluders2d>,
+    sdf_image: Option<AssetId<Image>>,
+    occluder_buffer: &mut GpuArrayBuffer<ExtractedLightOccluder2d>,
+    vertices: &mut Vec<Vec2>,
+) -> (u32, bool) {
+    // Compound occluders contribute one occluder per shape.
+    let mut count = 0;
+    let mut absorption = false;
+    for extracted_occluders in extracted_occluders {
+        for occluder in &extracted_occluders.occluders {
+            // Distance field occluders are skipped until their image has loaded, and only a
+            // single image can be sampled.
+            if occluder.shape == OCCLUDER_SHAPE_SDF {
+                let Some(sdf_image) = sdf_image else {
+                    continue;
+                };
+
+                if extracted_occluders.sdf_image != Some(sdf_image) {
+                    warn_once!(
+                        "Only a single distance field image can be used by occluders. Some \
+                        distance field occluders will not cast shadows."
+                    );
+                    continue;
+                }
+            }
+
+            let mut occluder = occluder.clone();
+
+            // Polygon vertices are combined into a single buffer, so their offsets need to be
+            // moved to their position within it.
+            if matches!(
+                occluder.shape,
+                OCCLUDER_SHAPE_POLYGON | OCCLUDER_SHAPE_TRIANGLES
+            ) {
+                let vertex_offset = vertices.len();
+                let vertex_count = occluder.shape_params.y as usize;
+
+                if vertex_offset + vertex_count > MAX_OCCLUDER_VERTICES {
+                    warn_once!(
+                        "Polygon occluders exceed the limit of {MAX_OCCLUDER_VERTICES} vertices. \
+                        Some polygon occluders will not cast shadows."
+                    );
+                    continue;
+                }
+
+                let start = occluder.shape_params.x as usize;
+                vertices
+                    .extend_from_slice(&extracted_occluders.vertices[start..start + vertex_count]);
+                occluder.shape_params.x = vertex_offset as u32;
+            }
+
+            absorption |= occluder.absorption > 0.0;
+
+            // WebGL2 only binds the first batch of a uniform array buffer.
+            if occluder_buffer
+                .push(occluder)
+                .dynamic_offset
+                .is_some_and(|offset| offset.get() > 0)
+            {
+                warn_once!(
+                    "Occluders exceed the limit of this platform. Some occluders will not cast \
+                    shadows."
+                );
+            }
+            count += 1;
+        }
+    }
+
+    (count, absorption)
+}
+
+pub fn prepare_occluder_vertices(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut occluder_vertex_buffer: ResMut<OccluderVertexBuffer>,
+) {
+    let occluder_vertex_buffer = occluder_vertex_buffer.as_mut();
+    let packed_vertices = &mut occluder_vertex_buffer.buffer.get_mut().vertices;
+
+    for (packed, pair) in packed_vertices
+        .iter_mut()
+        .zip(occluder_vertex_buffer.vertices.chunks(2))
+    {
+        let second = pair.get(1).copied().unwrap_or_default();
+        *packed = Vec4::new(pair[0].x, pair[0].y, second.x, second.y);
+    }
+
+    occluder_vertex_buffer
+        .buffer
+        .write_buffer(&render_device, &render_queue);
+}
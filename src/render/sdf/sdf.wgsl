@@ -1,14 +1,31 @@
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
 #import bevy_render::view::View
-#import bevy_light_2d::types::{LightOccluder2d, OccluderMeta};
+#import bevy_light_2d::types::{
+    LightOccluder2d,
+    OccluderMeta,
+    MAX_OCCLUDERS,
+    OCCLUDER_SHAPE_CAPSULE,
+    OCCLUDER_SHAPE_POLYGON,
+    OCCLUDER_SHAPE_TRIANGLES,
+    OCCLUDER_SHAPE_ELLIPSE,
+    OCCLUDER_SHAPE_ANNULUS,
+    OCCLUDER_SHAPE_ALPHA_MASK,
+    OCCLUDER_SHAPE_SDF,
+    occluder_end,
+    occluder_half_size,
+    occluder_mask_rect,
+    occluder_start,
+    occluder_vertex_count,
+    occluder_vertex_offset,
+};
 #import bevy_light_2d::view_transformations::{frag_coord_to_ndc, ndc_to_world};
 
-// We're currently only using a single uniform binding for occluders in
-// WebGL2, which is limited to 4kb in BatchedUniformBuffer, so we need to
-// ensure our occluders can fit in 4kb.
-//
-// As each occluder is 16 bytes, we can fit 4096 / 16 = 256 occluders.
-const MAX_OCCLUDERS: u32 = 256u;
+// Polygon vertices for all occluders, packed in pairs as uniform array elements
+// must be 16 byte aligned.
+const MAX_OCCLUDER_VERTICES: u32 = 512u;
+
+// The distance (in mask texels) that can be stored in the occluder mask atlas.
+const MASK_SDF_RANGE: f32 = 8.0;
 
 @group(0) @binding(0)
 var<uniform> view: View;
@@ -26,6 +43,27 @@ var<uniform> view: View;
 @group(0) @binding(2)
 var<uniform> occluder_meta: OccluderMeta;
 
+@group(0) @binding(3)
+var<uniform> occluder_vertices: array<vec4<f32>, MAX_OCCLUDER_VERTICES / 2u>;
+
+@group(0) @binding(4)
+var occluder_mask_atlas: texture_2d<f32>;
+
+@group(0) @binding(5)
+var occluder_mask_sampler: sampler;
+
+@group(0) @binding(6)
+var occluder_sdf_texture: texture_2d<f32>;
+
+// Occluders that don't move, which are only uploaded when they change.
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 6
+    @group(0) @binding(7)
+    var<storage> static_occluders: array<LightOccluder2d>;
+#else
+    @group(0) @binding(7)
+    var<uniform> static_occluders: array<LightOccluder2d, MAX_OCCLUDERS>;
+#endif
+
 @fragment
 fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let pos = ndc_to_world(frag_coord_to_ndc(in.position.xy));
@@ -33,28 +71,251 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     // WebGL2 does not support storage buffers (or runtime sized arrays), so we
     // need to use a fixed number of occluders.
 #if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 6
+    let static_count = occluder_meta.static_count;
     let occluder_count = occluder_meta.count;
 #else
+    let static_count = min(MAX_OCCLUDERS, occluder_meta.static_count);
     let occluder_count = min(MAX_OCCLUDERS, occluder_meta.count);
 #endif
 
     // If there aren't any occluders, use the max value for the texture.
-    if (occluder_count == 0) {
-        return vec4(255.0, 0.0, 0.0, 1.0);
+    if (static_count == 0 && occluder_count == 0) {
+        return vec4(255.0, 0.0, 0.0, 0.0);
     }
 
-    var sdf = occluder_sd(pos, occluders[0]);
+    var sdf = 0.0;
+    var nearest = 0u;
+
+    for (var i = 0u; i < static_count; i++) {
+        let occluder_sdf = occluder_sd(pos, static_occluders[i]);
+        if (i == 0u || occluder_sdf < sdf) {
+            sdf = occluder_sdf;
+            nearest = i;
+        }
+    }
 
-    for (var i = 1u; i < occluder_count; i++) {
-        sdf = min(sdf, occluder_sd(pos, occluders[i]));
+    // The rest of the occluders are indexed after the static occluders.
+    for (var i = 0u; i < occluder_count; i++) {
+        let occluder_sdf = occluder_sd(pos, occluders[i]);
+        if ((static_count == 0u && i == 0u) || occluder_sdf < sdf) {
+            sdf = occluder_sdf;
+            nearest = occluder_meta.static_count + i;
+        }
     }
 
-    return vec4(sdf, 0.0, 0.0, 1.0);
+    // The distance to the nearest occluder is stored in the red channel, and
+    // the index of that occluder in the green and blue channels. 16 bit floats
+    // can only represent integers exactly up to 2048, so the index is split
+    // across two channels. The occluder's softness is stored in the alpha
+    // channel, so that it can be sampled without looking up the occluder.
+    return vec4(sdf, f32(nearest % 2048u), f32(nearest / 2048u), get_occluder(nearest).softness);
+}
+
+// Returns an occluder by its index across the static and the rest of the
+// occluders.
+fn get_occluder(index: u32) -> LightOccluder2d {
+    if (index < occluder_meta.static_count) {
+        return static_occluders[index];
+    }
+    return occluders[index - occluder_meta.static_count];
 }
 
+// Occluders are inflated by their margin, and hollow occluders are only solid
+// within their border.
 fn occluder_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
-  let local_pos = occluder.center - p;
-  let d = abs(local_pos) - occluder.half_size;
+  let sd = shape_sd(p, occluder) - occluder.margin;
+  if (occluder.border_thickness >= 0.0) {
+    return abs(sd) - occluder.border_thickness * 0.5;
+  }
+
+  return sd;
+}
+
+fn shape_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  if (occluder.shape == OCCLUDER_SHAPE_CAPSULE) {
+    return capsule_sd(p, occluder);
+  }
+  if (occluder.shape == OCCLUDER_SHAPE_POLYGON) {
+    return polygon_sd(p, occluder_vertex_offset(occluder), occluder_vertex_count(occluder));
+  }
+  if (occluder.shape == OCCLUDER_SHAPE_TRIANGLES) {
+    return triangles_sd(p, occluder);
+  }
+  if (occluder.shape == OCCLUDER_SHAPE_ELLIPSE) {
+    return ellipse_sd(p, occluder);
+  }
+  if (occluder.shape == OCCLUDER_SHAPE_ANNULUS) {
+    return annulus_sd(p, occluder);
+  }
+  if (occluder.shape == OCCLUDER_SHAPE_ALPHA_MASK) {
+    return alpha_mask_sd(p, occluder);
+  }
+  if (occluder.shape == OCCLUDER_SHAPE_SDF) {
+    return sdf_texture_sd(p, occluder);
+  }
+
+  return rectangle_sd(p, occluder);
+}
+
+// Transforms a world space position into the occluder's local space.
+fn to_local(p: vec2f, occluder: LightOccluder2d) -> vec2f {
+  let d = p - occluder.center;
+  let r = occluder.rotation;
+  return vec2f(dot(d, r), dot(d, vec2f(-r.y, r.x)));
+}
+
+// Rectangles with a non-zero radius have rounded corners.
+fn rectangle_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  let local_pos = to_local(p, occluder);
+  let d = abs(local_pos) - (occluder_half_size(occluder) - occluder.radius);
+
+  return length(max(d, vec2f(0.))) + min(max(d.x, d.y), 0.) - occluder.radius;
+}
+
+fn capsule_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  return segment_distance(p, occluder_start(occluder), occluder_end(occluder)) - occluder.radius;
+}
+
+// Approximate ellipse distance.
+// See https://iquilezles.org/articles/ellipsedist/
+fn ellipse_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  let ab = occluder_half_size(occluder);
+
+  // Use the exact circle distance for circles, so that they match circular
+  // capsules exactly.
+  if (ab.x == ab.y) {
+    return length(p - occluder.center) - ab.x;
+  }
+
+  let local_pos = to_local(p, occluder);
+  let k0 = length(local_pos / ab);
+  let k1 = length(local_pos / (ab * ab));
+
+  if (k1 == 0.) {
+    return -min(ab.x, ab.y);
+  }
+
+  return k0 * (k0 - 1.) / k1;
+}
+
+// The inner and outer radius of the annulus are stored as its half size.
+fn annulus_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  let radii = occluder_half_size(occluder);
+  let inner_radius = radii.x;
+  let outer_radius = radii.y;
+  let mid_radius = (inner_radius + outer_radius) * 0.5;
+  let half_thickness = (outer_radius - inner_radius) * 0.5;
+
+  return abs(length(p - occluder.center) - mid_radius) - half_thickness;
+}
+
+// Alpha masks are baked into distance fields (in mask texels) within the
+// occluder mask atlas. The size of a mask texel in world units is stored in
+// `radius`.
+fn alpha_mask_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  let local_pos = to_local(p, occluder);
+  let half_size = occluder_half_size(occluder);
+  let clamped_pos = clamp(local_pos, -half_size, half_size);
+
+  // Outside of the mask's bounds, the distance to the bounds is added to the
+  // distance stored at the nearest edge of the mask.
+  let bounds_distance = length(local_pos - clamped_pos);
+
+  // Images store their first row at the top.
+  let uv = vec2f(0.5) + vec2f(clamped_pos.x, -clamped_pos.y) / (2. * half_size);
+  let mask_rect = occluder_mask_rect(occluder);
+  let atlas_uv = mix(mask_rect.xy, mask_rect.zw, uv);
+  let encoded = textureSampleLevel(occluder_mask_atlas, occluder_mask_sampler, atlas_uv, 0.).r;
+  let mask_distance = (encoded * 2. - 1.) * MASK_SDF_RANGE * occluder.radius;
+
+  return bounds_distance + mask_distance;
+}
+
+// Distance field textures store distances in their red channel, which are
+// scaled by the occluder's radius.
+fn sdf_texture_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  let local_pos = to_local(p, occluder);
+  let half_size = occluder_half_size(occluder);
+  let clamped_pos = clamp(local_pos, -half_size, half_size);
+
+  // Outside of the texture's bounds, the distance to the bounds is added to the
+  // distance stored at the nearest edge of the texture.
+  let bounds_distance = length(local_pos - clamped_pos);
+
+  // Images store their first row at the top.
+  let uv = vec2f(0.5) + vec2f(clamped_pos.x, -clamped_pos.y) / (2. * half_size);
+
+  // Float textures can't always be filtered, so neighbouring texels are
+  // interpolated manually.
+  let size = textureDimensions(occluder_sdf_texture);
+  let texel = clamp(uv * vec2f(size) - 0.5, vec2f(0.), vec2f(size - 1u));
+  let t = fract(texel);
+  let min_texel = vec2u(texel);
+  let max_texel = min(min_texel + 1u, size - 1u);
+
+  let d00 = textureLoad(occluder_sdf_texture, min_texel, 0).r;
+  let d10 = textureLoad(occluder_sdf_texture, vec2u(max_texel.x, min_texel.y), 0).r;
+  let d01 = textureLoad(occluder_sdf_texture, vec2u(min_texel.x, max_texel.y), 0).r;
+  let d11 = textureLoad(occluder_sdf_texture, max_texel, 0).r;
+  let texture_distance = mix(mix(d00, d10, t.x), mix(d01, d11, t.x), t.y);
+
+  return bounds_distance + texture_distance * occluder.radius;
+}
+
+fn occluder_vertex(index: u32) -> vec2f {
+  let pair = occluder_vertices[index / 2u];
+  return select(pair.xy, pair.zw, index % 2u == 1u);
+}
+
+// See https://iquilezles.org/articles/distfunctions2d/
+fn polygon_sd(p: vec2f, vertex_offset: u32, vertex_count: u32) -> f32 {
+  let first = occluder_vertex(vertex_offset);
+  var d = dot(p - first, p - first);
+  var s = 1.;
+  var j = vertex_count - 1u;
+
+  for (var i = 0u; i < vertex_count; i++) {
+    let vi = occluder_vertex(vertex_offset + i);
+    let vj = occluder_vertex(vertex_offset + j);
+    let e = vj - vi;
+    let w = p - vi;
+    let b = w - e * clamp(dot(w, e) / max(dot(e, e), 1e-6), 0., 1.);
+    d = min(d, dot(b, b));
+
+    let c = vec3<bool>(p.y >= vi.y, p.y < vj.y, e.x * w.y > e.y * w.x);
+    if (all(c) || all(!c)) {
+      s = -s;
+    }
+
+    j = i;
+  }
+
+  return s * sqrt(d);
+}
+
+// Concave polygons are decomposed into a list of triangles, whose union is the polygon.
+fn triangles_sd(p: vec2f, occluder: LightOccluder2d) -> f32 {
+  let vertex_offset = occluder_vertex_offset(occluder);
+  var sd = polygon_sd(p, vertex_offset, 3u);
+
+  for (var i = 3u; i < occluder_vertex_count(occluder); i += 3u) {
+    sd = min(sd, polygon_sd(p, vertex_offset + i, 3u));
+  }
+
+  return sd;
+}
+
+// Distance from a point to the line segment between `a` and `b`.
+// A zero length segment is treated as a single point.
+fn segment_distance(p: vec2f, a: vec2f, b: vec2f) -> f32 {
+  let pa = p - a;
+  let ba = b - a;
+  let len_sq = dot(ba, ba);
+
+  if (len_sq == 0.) {
+    return length(pa);
+  }
 
-  return length(max(d, vec2f(0.))) + min(max(d.x, d.y), 0.);
+  let h = clamp(dot(pa, ba) / len_sq, 0., 1.);
+  return length(pa - ba * h);
 }
@@ -2,25 +2,186 @@
 
 struct AmbientLight2d {
     color: vec4<f32>,
+    baked_lightmap_min: vec2<f32>,
+    baked_lightmap_max: vec2<f32>,
+    // The factor the view's lighting is multiplied by.
+    exposure: f32,
+    // The camera's render layers, as a bitmask.
+    render_layers: u32,
+    // How the light of overlapping lights is combined.
+    blend: u32,
+    // The largest linear luminance of the view's lighting.
+    max_brightness: f32,
+    // The top of the ambient light's gradient, whose bottom is `color`.
+    gradient_top: vec4<f32>,
+    // The world space heights of the bottom and top of the gradient.
+    gradient_range: vec2<f32>,
+    // Whether the ambient light has a gradient: 0 for none, 1 for a gradient
+    // across the screen and 2 for a gradient across `gradient_range`.
+    gradient: u32,
+    // The world space region covered by the ambient map, which is empty when
+    // there isn't one.
+    ambient_map_min: vec2<f32>,
+    ambient_map_max: vec2<f32>,
+    // The distance from occluders within which the ambient light is darkened,
+    // and by how much.
+    ambient_occlusion_radius: f32,
+    ambient_occlusion_strength: f32,
 }
 
+// We're currently only using a single uniform binding for occluders in
+// WebGL2, which is limited to 4kb in BatchedUniformBuffer, so we need to
+// ensure our occluders can fit in 4kb.
+//
+// As each occluder is 96 bytes, we can fit 4096 / 96 = 42 occluders.
+const MAX_OCCLUDERS: u32 = 42u;
+
+const OCCLUDER_SHAPE_RECTANGLE: u32 = 0u;
+const OCCLUDER_SHAPE_CAPSULE: u32 = 1u;
+const OCCLUDER_SHAPE_POLYGON: u32 = 2u;
+const OCCLUDER_SHAPE_TRIANGLES: u32 = 3u;
+const OCCLUDER_SHAPE_ELLIPSE: u32 = 4u;
+const OCCLUDER_SHAPE_ANNULUS: u32 = 5u;
+const OCCLUDER_SHAPE_ALPHA_MASK: u32 = 6u;
+const OCCLUDER_SHAPE_SDF: u32 = 7u;
+
 struct LightOccluder2d {
-    half_size: vec2<f32>,
     center: vec2<f32>,
+    rotation: vec2<f32>,
+    // The parameters of the occluder's shape, which are read with the
+    // `occluder_*` functions below.
+    shape_params: vec4<u32>,
+    // The world space direction a one-sided occluder faces, or zero if the
+    // occluder is two-sided.
+    facing: vec2<f32>,
+    radius: f32,
+    // The distance the occluder's shape is inflated by, or shrunk by if
+    // negative.
+    margin: f32,
+    // The thickness of a hollow occluder's border, or negative if the occluder
+    // is solid.
+    border_thickness: f32,
+    softness: f32,
+    shape: u32,
+    // The index of the occluder's entity, so that lights can ignore their own
+    // occluders.
+    owner: u32,
+    // The lights blocked by this occluder, as a bitmask of occlusion layers.
+    occlusion_layers: u32,
+    // The views the occluder casts shadows in, as a bitmask of render layers.
+    render_layers: u32,
+    opacity: f32,
+    // The length of the occluder's shadows relative to their distance from the
+    // light, or zero if they're unlimited.
+    height: f32,
+    // The furthest distance the occluder's shadows reach, or negative if
+    // they're unlimited.
+    max_shadow_distance: f32,
+    // The fraction of light absorbed per world unit travelled through the
+    // occluder.
+    absorption: f32,
+    // The color of light that passes through the occluder, or black if
+    // untinted, packed as unorm8x4.
+    tint: u32,
+    // The color that blocked light is modulated by, or black if shadows are
+    // black, packed as unorm8x4.
+    shadow_color: u32,
+}
+
+// The half size of a rectangle, ellipse, alpha mask or distance field
+// occluder, or the inner and outer radius of an annulus.
+fn occluder_half_size(occluder: LightOccluder2d) -> vec2<f32> {
+    return bitcast<vec2<f32>>(occluder.shape_params.xy);
+}
+
+// The world space endpoints of a capsule's inner segment.
+fn occluder_start(occluder: LightOccluder2d) -> vec2<f32> {
+    return bitcast<vec2<f32>>(occluder.shape_params.xy);
+}
+
+fn occluder_end(occluder: LightOccluder2d) -> vec2<f32> {
+    return bitcast<vec2<f32>>(occluder.shape_params.zw);
+}
+
+// Range of a polygon or triangles occluder's vertices within the occluder
+// vertex buffer.
+fn occluder_vertex_offset(occluder: LightOccluder2d) -> u32 {
+    return occluder.shape_params.x;
+}
+
+fn occluder_vertex_count(occluder: LightOccluder2d) -> u32 {
+    return occluder.shape_params.y;
+}
+
+// The alpha mask's texture coordinates within the occluder mask atlas.
+fn occluder_mask_rect(occluder: LightOccluder2d) -> vec4<f32> {
+    return vec4(
+        unpack_unorm16x2(occluder.shape_params.z),
+        unpack_unorm16x2(occluder.shape_params.w),
+    );
+}
+
+fn occluder_tint(occluder: LightOccluder2d) -> vec4<f32> {
+    return unpack_unorm8x4(occluder.tint);
+}
+
+fn occluder_shadow_color(occluder: LightOccluder2d) -> vec4<f32> {
+    return unpack_unorm8x4(occluder.shadow_color);
+}
+
+// `unpack4x8unorm` isn't available in WebGL2, so packed values are unpacked
+// manually.
+fn unpack_unorm8x4(packed: u32) -> vec4<f32> {
+    let bytes = (vec4(packed) >> vec4(0u, 8u, 16u, 24u)) & vec4(0xffu);
+    return vec4<f32>(bytes) / 255.0;
+}
+
+fn unpack_unorm16x2(packed: u32) -> vec2<f32> {
+    let halves = (vec2(packed) >> vec2(0u, 16u)) & vec2(0xffffu);
+    return vec2<f32>(halves) / 65535.0;
 }
 
 struct PointLight2d {
     center: vec2f,
     radius: f32,
+    // The light's height above the 2D plane, which is its z component in 3D.
+    height: f32,
     color: vec4<f32>,
     intensity: f32,
     falloff: f32,
-    cast_shadows: u32
+    // The light's local x axis, divided by its ellipticity along that axis.
+    ellipse_axis: vec2<f32>,
+    // The light's ellipticity along its x axis, divided by its ellipticity
+    // along its y axis.
+    ellipse_aspect: f32,
+    // The light's `LIGHT_FLAG_*` bits and falloff curve.
+    flags: u32,
+    occlusion_layers: u32,
+    // Range of the occluder owners this light ignores.
+    exclusion_offset: u32,
+    exclusion_count: u32,
+    // The exponent of the light's falloff curve, or its attenuation exponent
+    // if it's an inverse square curve.
+    falloff_exponent: f32,
+    // The radius within which the light is at its full intensity.
+    inner_radius: f32,
+    // The radius of the light's source, which softens its shadows.
+    source_radius: f32,
+    // The views the light illuminates, as a bitmask of render layers.
+    render_layers: u32,
+    // How much of the light its shadows block, between zero and one.
+    shadow_strength: f32,
+    // The distance the edges of the light's shadows are blurred over.
+    shadow_softness: f32,
+    // The index of the light's extras, or `NO_LIGHT_EXTRAS`.
+    extras: u32,
 }
 
 struct SpotLight2d {
     center: vec2f,
     radius: f32,
+    // The light's height above the 2D plane, which is its z component in 3D.
+    height: f32,
     color: vec4<f32>,
     intensity: f32,
     falloff: f32,
@@ -28,22 +189,171 @@ struct SpotLight2d {
     inner_angle: f32,
     outer_angle: f32,
     source_width: f32,
+    // The light's `LIGHT_FLAG_*` bits and falloff curve.
+    flags: u32,
+    occlusion_layers: u32,
+    // Range of the occluder owners this light ignores.
+    exclusion_offset: u32,
+    exclusion_count: u32,
+    // The brightness of the light scattered by the light's beam, or zero if it
+    // isn't volumetric.
+    volumetric_intensity: f32,
+    // The exponent of the light's falloff curve, or its attenuation exponent
+    // if it's an inverse square curve.
+    falloff_exponent: f32,
+    // The radius within which the light is at its full intensity.
+    inner_radius: f32,
+    // The views the light illuminates, as a bitmask of render layers.
+    render_layers: u32,
+    // How much of the light its shadows block, between zero and one.
+    shadow_strength: f32,
+    // The distance the edges of the light's shadows are blurred over.
+    shadow_softness: f32,
+    // The exponent the light's fade between its inner and outer angles is
+    // raised to.
+    edge_exponent: f32,
+    // The index of the light's extras, or `NO_LIGHT_EXTRAS`.
+    extras: u32,
+}
+
+// The data only some point and spot lights use, which is kept in a separate
+// buffer so that more lights fit in a uniform buffer on WebGL2.
+struct LightExtras2d {
+    // The texture coordinates of a point light's cookie, or a spot light's
+    // projection texture, within the light cookie atlas.
+    cookie_rect: vec4<f32>,
+    // The noise texture's texture coordinates within the light cookie atlas,
+    // or zero if the light has no noise texture.
+    noise_rect: vec4<f32>,
+    // The light's color at its radius, with an alpha of zero if the light is a
+    // single color.
+    edge_color: vec4<f32>,
+    // The cookie's rotation, as the cosine and sine of its angle.
+    cookie_rotation: vec2<f32>,
+    // The offset of the cookie's image, as a fraction of its size, or zero if
+    // it isn't offset.
+    cookie_offset: vec2<f32>,
+    // The world space size of each tile of the noise texture, and the speed it
+    // scrolls at, in world units per second.
+    noise_scale: vec2<f32>,
+    noise_scroll: vec2<f32>,
+    // The world space radius a point light's cookie covers, or zero if the
+    // light has no cookie.
+    cookie_radius: f32,
+    // The largest linear luminance of the light's contribution.
+    max_brightness: f32,
+}
+
+struct LineLight2d {
+    // World space endpoints of the light's segment.
+    start: vec2f,
+    end: vec2f,
+    radius: f32,
+    color: vec4<f32>,
+    intensity: f32,
+    falloff: f32,
     cast_shadows: u32,
+    occlusion_layers: u32,
+    // Range of the occluder owners this light ignores.
+    exclusion_offset: u32,
+    exclusion_count: u32,
+    // The radius of a glow around the end of the segment, used by beam lights.
+    end_glow_radius: f32,
+    // The views the light illuminates, as a bitmask of render layers.
+    render_layers: u32,
 }
 
-struct PointLightMeta {
-    count: u32,
-    // WebGL2 structs must be 16 byte aligned.
-    _padding: vec3<u32>
+struct AmbientLightZone2d {
+    // The zone's ambient light, plus every global light.
+    color: vec4<f32>,
+    center: vec2f,
+    // The world space half size of a rectangle zone, or the radius of a circle
+    // zone in x.
+    half_size: vec2f,
+    // The zone's rotation, as the cosine and sine of its angle.
+    rotation: vec2f,
+    // The zone's shape: 0 for a rectangle and 1 for a circle.
+    shape: u32,
+    edge_softness: f32,
+    // The views the zone affects, as a bitmask of render layers.
+    render_layers: u32,
+}
+
+struct PolygonLight2d {
+    color: vec4<f32>,
+    radius: f32,
+    intensity: f32,
+    falloff: f32,
+    // Range of the light's world space vertices.
+    vertex_offset: u32,
+    vertex_count: u32,
+    cast_shadows: u32,
+    occlusion_layers: u32,
+    // Range of the occluder owners this light ignores.
+    exclusion_offset: u32,
+    exclusion_count: u32,
+    // Whether the vertices form a closed polygon, rather than an open polyline.
+    closed: u32,
+    // The views the light illuminates, as a bitmask of render layers.
+    render_layers: u32,
+}
+
+struct RingLight2d {
+    center: vec2f,
+    radius: f32,
+    thickness: f32,
+    color: vec4<f32>,
+    intensity: f32,
+    falloff: f32,
+    // World space direction of the middle of the lit arc, and half of the arc's
+    // angle.
+    arc_direction: vec2f,
+    arc_half_angle: f32,
+    cast_shadows: u32,
+    occlusion_layers: u32,
+    // Range of the occluder owners this light ignores.
+    exclusion_offset: u32,
+    exclusion_count: u32,
+    // The views the light illuminates, as a bitmask of render layers.
+    render_layers: u32,
+}
+
+struct DirectionalLight2d {
+    color: vec4<f32>,
+    // The world space direction the light shines towards.
+    direction: vec2f,
+    cast_shadows: u32,
+    occlusion_layers: u32,
+    // Range of the occluder owners this light ignores.
+    exclusion_offset: u32,
+    exclusion_count: u32,
+    // The views the light illuminates, as a bitmask of render layers.
+    render_layers: u32,
+}
+
+struct EmissiveSprite2d {
+    // Rows of the matrix from world space into the sprite's local space, where
+    // the sprite covers -0.5 to 0.5 along both axes.
+    world_to_local_x: vec2f,
+    world_to_local_y: vec2f,
+    center: vec2f,
+    // The sprite's color, premultiplied by its alpha, and multiplied by its
+    // emissive color scale and intensity.
+    color: vec4<f32>,
+    // The image's texture coordinates within the light cookie atlas, swapped
+    // along flipped axes.
+    uv_rect: vec4<f32>,
 }
 
 struct OccluderMeta {
     count: u32,
+    // Static occluders are indexed before the rest of the occluders.
+    static_count: u32,
     // WebGL2 structs must be 16 byte aligned.
-    _padding: vec3<u32>
+    _padding: vec2<u32>
 }
 
-struct SpotLightMeta {
+struct EmissiveSpriteMeta {
     count: u32,
     // WebGL2 structs must be 16 byte aligned.
     _padding: vec3<u32>,
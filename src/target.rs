@@ -0,0 +1,96 @@
+//! A module which contains [`LightTarget2d`], which aims a spot light at another entity.
+
+use bevy::{
+    ecs::{
+        change_detection::DetectChanges,
+        component::Component,
+        entity::Entity,
+        system::{Query, Res},
+        world::Ref,
+    },
+    math::{Vec2, Vec3, Vec3Swizzles},
+    prelude::ReflectComponent,
+    reflect::Reflect,
+    transform::components::GlobalTransform,
+};
+
+use crate::{light::SpotLight2d, modulation::Light2dAnimationTime};
+
+/// Aims the [`SpotLight2d`] on the same entity at a target entity, like a searchlight or a
+/// security camera tracking an intruder.
+///
+/// The light's `direction` is updated every frame the light or its target moves. When the
+/// light's `direction_from_transform` is `true`, the direction is set relative to the rotation
+/// of the light's transform, so that the beam still points at the target.
+///
+/// If the target is despawned, the light is left pointing in its last direction.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct LightTarget2d {
+    /// The entity the light is aimed at.
+    #[entities]
+    pub target: Entity,
+    /// How quickly (in degrees per second) the light turns towards its target, or `None` to
+    /// snap to it immediately. Turning follows the [`Light2dAnimationTime`], so it slows down
+    /// and pauses along with it.
+    pub rotation_speed: Option<f32>,
+}
+
+impl LightTarget2d {
+    /// Returns a [`LightTarget2d`] which snaps the light to `target`.
+    pub fn new(target: Entity) -> Self {
+        Self {
+            target,
+            rotation_speed: None,
+        }
+    }
+}
+
+pub(crate) fn aim_lights_at_targets(
+    animation_time: Res<Light2dAnimationTime>,
+    mut lights: Query<(Ref<LightTarget2d>, Ref<GlobalTransform>, &mut SpotLight2d)>,
+    targets: Query<Ref<GlobalTransform>>,
+) {
+    for (light_target, global_transform, mut spot_light) in &mut lights {
+        let Ok(target_transform) = targets.get(light_target.target) else {
+            continue;
+        };
+        // Lights that snap to their target only need aiming when something moved, while lights
+        // that turn towards it keep turning until they reach it.
+        let moved = light_target.is_changed()
+            || global_transform.is_changed()
+            || target_transform.is_changed();
+        if !moved && light_target.rotation_speed.is_none() {
+            continue;
+        }
+
+        let to_target = target_transform.translation().xy() - global_transform.translation().xy();
+        if to_target == Vec2::ZERO {
+            continue;
+        }
+        let mut target_direction = to_target.to_angle().to_degrees();
+        if spot_light.direction_from_transform {
+            let rotation = (global_transform.rotation() * Vec3::X)
+                .xy()
+                .normalize_or(Vec2::X);
+            target_direction -= rotation.to_angle().to_degrees();
+        }
+
+        let mut turn = wrap_degrees(target_direction - spot_light.direction);
+        if let Some(rotation_speed) = light_target.rotation_speed {
+            let max_turn = rotation_speed.max(0.0) * animation_time.delta_secs();
+            turn = turn.clamp(-max_turn, max_turn);
+        }
+        // Lights already aimed at their target are left untouched, so that they aren't marked
+        // as changed.
+        if turn.abs() <= f32::EPSILON {
+            continue;
+        }
+        spot_light.direction = wrap_degrees(spot_light.direction + turn);
+    }
+}
+
+/// Wraps an angle (in degrees) between `-180.0` and `180.0`.
+fn wrap_degrees(angle: f32) -> f32 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
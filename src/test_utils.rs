@@ -0,0 +1,35 @@
+//! Helpers shared by the crate's tests.
+
+use bevy::{
+    camera::visibility::ViewVisibility,
+    ecs::{
+        system::{IntoSystem, RunSystemOnce},
+        world::World,
+    },
+    math::Vec3,
+    render::MainWorld,
+};
+
+/// Returns a [`ViewVisibility`] which is visible, as if set by the visibility systems.
+pub(crate) fn visible() -> ViewVisibility {
+    let mut view_visibility = ViewVisibility::default();
+    view_visibility.set();
+    view_visibility
+}
+
+/// Runs an extraction `system` on the `render_world`, which extracts from the `main_world`.
+pub(crate) fn run_extract<M>(
+    render_world: &mut World,
+    main_world: World,
+    system: impl IntoSystem<(), (), M>,
+) {
+    let mut extract_world = MainWorld::default();
+    *extract_world = main_world;
+    render_world.insert_resource(extract_world);
+    render_world.run_system_once(system).unwrap();
+}
+
+/// Returns the relative luminance of a linear color.
+pub(crate) fn luminance(color: Vec3) -> f32 {
+    color.dot(Vec3::new(0.2126, 0.7152, 0.0722))
+}
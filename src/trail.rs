@@ -0,0 +1,166 @@
+//! A module which contains [`TrailLight2d`], which leaves a fading trail of light behind a
+//! moving entity.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    camera::visibility::{self, Visibility, VisibilityClass},
+    color::Color,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        lifecycle::HookContext,
+        query::Has,
+        system::{Commands, Query, Res},
+        world::DeferredWorld,
+    },
+    math::{Vec2, Vec3Swizzles},
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::modulation::Light2dAnimationTime;
+
+/// A light that leaves a glowing trail behind a moving entity, like a projectile or a spark.
+///
+/// The entity's position is recorded every frame, and light is emitted along the segments
+/// between the recorded points, in the same way as a [`LineLight2d`]. Each point dims as it
+/// ages, until it expires after `point_lifetime` seconds, so the trail fades towards its tail.
+/// Trails age with the [`Light2dAnimationTime`], so they freeze while it's paused.
+///
+/// When the entity is despawned, or the component is removed, its trail is moved to a new
+/// entity, where it fades out over the remainder of its lifetime rather than vanishing.
+///
+/// Each segment of a trail is drawn as a line light. In WebGL2, line lights are limited to 51
+/// per view, so keep `max_points` low if you're targeting the web.
+///
+/// [`LineLight2d`]: crate::light::LineLight2d
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(TrailPoints2d, Transform, Visibility, VisibilityClass)]
+#[component(
+    on_add = visibility::add_visibility_class::<TrailLight2d>,
+    on_remove = detach_light_trail,
+)]
+pub struct TrailLight2d {
+    /// The maximum number of points the trail records. The oldest points are discarded once
+    /// this limit is reached.
+    pub max_points: usize,
+    /// How long each point of the trail lasts for, in seconds.
+    pub point_lifetime: f32,
+    /// The light's color tint.
+    pub color: Color,
+    /// The intensity of the light at the head of the trail. The light's attenutation is
+    /// multiplied by this value. The higher the intensity, the brighter the light.
+    pub intensity: f32,
+    /// The radius of the light. Illumination will only occur within this distance of the
+    /// trail.
+    pub radius: f32,
+    /// How quickly illumination from the light should deteriorate over distance.
+    /// A higher falloff value will result in less illumination at the light's maximum radius.
+    pub falloff: f32,
+}
+
+impl Default for TrailLight2d {
+    /// Returns a white [`TrailLight2d`] of up to `16` points, which each last half a second.
+    fn default() -> Self {
+        Self {
+            max_points: 16,
+            point_lifetime: 0.5,
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 0.5,
+            falloff: 0.0,
+        }
+    }
+}
+
+/// The points recorded by a [`TrailLight2d`], from oldest to newest.
+#[derive(Component, Clone, Default)]
+pub struct TrailPoints2d {
+    pub(crate) points: VecDeque<TrailPoint>,
+}
+
+impl TrailPoints2d {
+    /// Returns the world space positions of the trail's points, from oldest to newest.
+    pub fn positions(&self) -> impl Iterator<Item = Vec2> + '_ {
+        self.points.iter().map(|point| point.position)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct TrailPoint {
+    pub(crate) position: Vec2,
+    // How long ago the point was recorded, in seconds.
+    pub(crate) age: f32,
+}
+
+/// Marks a trail which has been detached from its entity, and is fading out.
+#[derive(Component)]
+pub(crate) struct DetachedTrailLight2d;
+
+fn detach_light_trail(mut world: DeferredWorld, context: HookContext) {
+    if world.get::<DetachedTrailLight2d>(context.entity).is_some() {
+        return;
+    }
+    let (Some(trail_light), Some(trail_points)) = (
+        world.get::<TrailLight2d>(context.entity),
+        world.get::<TrailPoints2d>(context.entity),
+    ) else {
+        return;
+    };
+    if trail_points.points.is_empty() {
+        return;
+    }
+
+    let detached = (
+        trail_light.clone(),
+        trail_points.clone(),
+        DetachedTrailLight2d,
+    );
+    world.commands().spawn(detached);
+}
+
+pub(crate) fn update_light_trails(
+    mut commands: Commands,
+    animation_time: Res<Light2dAnimationTime>,
+    mut trail_lights: Query<(
+        Entity,
+        &TrailLight2d,
+        &mut TrailPoints2d,
+        &GlobalTransform,
+        Has<DetachedTrailLight2d>,
+    )>,
+) {
+    let delta = animation_time.delta_secs();
+
+    for (entity, trail_light, mut trail_points, global_transform, detached) in &mut trail_lights {
+        for point in &mut trail_points.points {
+            point.age += delta;
+        }
+        trail_points
+            .points
+            .retain(|point| point.age < trail_light.point_lifetime);
+
+        // Detached trails no longer follow an entity, and are despawned once they've faded.
+        if detached {
+            if trail_points.points.is_empty() {
+                commands.entity(entity).despawn();
+            }
+            continue;
+        }
+
+        // Paused trails don't record new points, which would push the rest of the trail out.
+        if delta <= 0.0 {
+            continue;
+        }
+        trail_points.points.push_back(TrailPoint {
+            position: global_transform.translation().xy(),
+            age: 0.0,
+        });
+        while trail_points.points.len() > trail_light.max_points {
+            trail_points.points.pop_front();
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! A module which contains components for rendering a mask of what lights can see, such as
+//! for fog of war.
+
+use bevy::{
+    asset::{Handle, RenderAssetUsages},
+    ecs::component::Component,
+    image::Image,
+    math::UVec2,
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+};
+
+/// Marks a [`PointLight2d`] or [`SpotLight2d`] as a source of vision, such as a light carried
+/// by the player, for use with a [`VisionMask2d`].
+///
+/// Vision sources see everything within their radius (and cone, for spot lights) that isn't
+/// hidden behind an occluder, whether or not the light casts shadows, and regardless of its
+/// intensity.
+///
+/// [`PointLight2d`]: crate::light::PointLight2d
+/// [`SpotLight2d`]: crate::light::SpotLight2d
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default)]
+pub struct VisionSource2d;
+
+/// Renders a mask of the parts of a view that are seen by [`VisionSource2d`]s into an image,
+/// to be used with a `Camera2d` alongside [`Light2d`].
+///
+/// Each frame, the red channel of the image is set to how visible each point of the view is,
+/// from `0.0` for points no vision source can see, to `1.0` for points in plain sight. The
+/// image covers the whole view, but may have a lower resolution than it. Bind the image in
+/// your own materials to hide what can't be seen, such as enemies outside of the player's
+/// vision, or read it back to the CPU with Bevy's `Readback` component.
+///
+/// The image must be created with [`VisionMask2d::new_image`].
+///
+/// [`Light2d`]: crate::light::Light2d
+#[derive(Component, Clone, Default)]
+pub struct VisionMask2d {
+    /// The image the mask is rendered into.
+    pub image: Handle<Image>,
+}
+
+impl VisionMask2d {
+    /// Returns an image that a vision mask of `size` pixels can be rendered into.
+    pub fn new_image(size: UVec2) -> Image {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: size.x.max(1),
+                height: size.y.max(1),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0],
+            TextureFormat::R8Unorm,
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage |=
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC;
+        image
+    }
+}
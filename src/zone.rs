@@ -0,0 +1,159 @@
+//! A module which contains [`AmbientLightZone2d`], which changes the ambient light within a
+//! region of the world.
+
+use bevy::{
+    camera::{
+        primitives::Aabb,
+        visibility::{self, Visibility, VisibilityClass},
+    },
+    color::Color,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{Changed, Or},
+        system::{Commands, Query},
+    },
+    math::{Vec2, Vec3, Vec3Swizzles},
+    prelude::{ReflectComponent, ReflectDefault},
+    reflect::Reflect,
+    transform::components::{GlobalTransform, Transform},
+};
+
+/// A region of the world with its own ambient light, like a cave within a sunlit level.
+///
+/// Within the zone, the ambient light of each camera with a [`Light2d`] is replaced by the
+/// zone's, fading between the two over the zone's `edge_softness`, so walking into a cave
+/// doesn't snap the ambient light from one to the other. The zone's shape follows its entity's
+/// transform, so it can be rotated and scaled.
+///
+/// Overlapping zones are layered in order of their `priority`, with higher priorities on top,
+/// so each zone fades from the ambient light beneath it. Zones with the same priority are
+/// layered in an arbitrary, but stable, order.
+///
+/// Zones outside of every view aren't uploaded to the GPU. At most 32 zones are drawn in each
+/// frame, preferring those with the highest priorities.
+///
+/// Like a light, a zone with `RenderLayers` only affects the views of cameras that share one of
+/// its layers.
+///
+/// [`Light2d`]: crate::light::Light2d
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component, Default)]
+#[require(Transform, Visibility, VisibilityClass)]
+#[component(on_add = visibility::add_visibility_class::<AmbientLightZone2d>)]
+pub struct AmbientLightZone2d {
+    /// The zone's ambient light color tint.
+    pub color: Color,
+    /// The brightness of the zone's ambient light, which is multiplied against the linear RGB
+    /// representation of its color, like an [`AmbientLight2d`]'s.
+    ///
+    /// [`AmbientLight2d`]: crate::light::AmbientLight2d
+    pub brightness: f32,
+    /// The shape of the zone, centered on its entity.
+    pub shape: AmbientLightZoneShape,
+    /// The distance (in world units) inside the zone's edge over which its ambient light fades
+    /// in. A softness of `0.0` gives the zone a hard edge.
+    pub edge_softness: f32,
+    /// The order overlapping zones are layered in, with higher priorities on top.
+    pub priority: i32,
+}
+
+impl Default for AmbientLightZone2d {
+    /// Returns an [`AmbientLightZone2d`] of black ambient light, in a 100 by 100 square, which
+    /// fades in over 10 units.
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            brightness: 1.0,
+            shape: AmbientLightZoneShape::default(),
+            edge_softness: 10.0,
+            priority: 0,
+        }
+    }
+}
+
+/// The shape of an [`AmbientLightZone2d`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Default)]
+pub enum AmbientLightZoneShape {
+    /// A rectangle, with the given half size.
+    Rectangle {
+        /// Half of the rectangle's width and height.
+        half_size: Vec2,
+    },
+    /// A circle, with the given radius.
+    Circle {
+        /// The circle's radius.
+        radius: f32,
+    },
+}
+
+impl Default for AmbientLightZoneShape {
+    /// Returns a 100 by 100 square.
+    fn default() -> Self {
+        AmbientLightZoneShape::Rectangle {
+            half_size: Vec2::splat(50.0),
+        }
+    }
+}
+
+impl AmbientLightZone2d {
+    /// Returns the zone's shape in world space, given its entity's transform, as its half size,
+    /// or its radius in `x` if it's a circle.
+    pub(crate) fn world_half_size(&self, global_transform: &GlobalTransform) -> Vec2 {
+        let scale = global_transform.scale().xy().abs();
+        match self.shape {
+            AmbientLightZoneShape::Rectangle { half_size } => half_size.abs() * scale,
+            AmbientLightZoneShape::Circle { radius } => {
+                Vec2::new(radius.abs() * scale.max_element(), 0.0)
+            }
+        }
+    }
+
+    /// Returns how much of the zone's ambient light reaches a world space position, from `0.0`
+    /// outside the zone to `1.0` beyond its soft edge, matching the light map pass.
+    pub(crate) fn weight(&self, global_transform: &GlobalTransform, pos: Vec2) -> f32 {
+        let rotation = (global_transform.rotation() * Vec3::X)
+            .xy()
+            .normalize_or(Vec2::X);
+        let offset = pos - global_transform.translation().xy();
+        let local = Vec2::new(offset.dot(rotation), offset.dot(rotation.perp()));
+        let half_size = self.world_half_size(global_transform);
+        let dist = match self.shape {
+            AmbientLightZoneShape::Rectangle { .. } => {
+                let q = local.abs() - half_size;
+                q.max(Vec2::ZERO).length() + q.max_element().min(0.0)
+            }
+            AmbientLightZoneShape::Circle { .. } => local.length() - half_size.x,
+        };
+        if self.edge_softness <= 0.0 {
+            return if dist <= 0.0 { 1.0 } else { 0.0 };
+        }
+        let t = (-dist / self.edge_softness).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+pub(crate) fn calculate_ambient_zone_bounds(
+    mut commands: Commands,
+    zones: Query<
+        (Entity, &AmbientLightZone2d, &GlobalTransform),
+        Or<(Changed<AmbientLightZone2d>, Changed<GlobalTransform>)>,
+    >,
+) {
+    for (entity, zone, global_transform) in &zones {
+        // Bounds are in local space, while circles are scaled by their largest axis in world
+        // space, so they stay circular.
+        let scale = global_transform.scale().xy().abs().max(Vec2::splat(1e-6));
+        let half_size = match zone.shape {
+            AmbientLightZoneShape::Rectangle { half_size } => half_size.abs(),
+            AmbientLightZoneShape::Circle { radius } => {
+                Vec2::splat(radius.abs() * scale.max_element()) / scale
+            }
+        };
+        commands.entity(entity).insert(Aabb::from_min_max(
+            (-half_size).extend(0.0),
+            half_size.extend(0.0),
+        ));
+    }
+}